@@ -21,7 +21,7 @@ impl RequestId {
     #[cfg(all(feature = "dap", feature = "server"))]
     pub(crate) fn dap(id: RequestId) -> i64 {
         match id.0 {
-            IdRepr::I32(it) => it as i64,
+            IdRepr::Int(it) => it,
             IdRepr::String(it) => panic!("unexpected string ID in DAP: {it}"),
         }
     }
@@ -30,13 +30,21 @@ impl RequestId {
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[serde(untagged)]
 enum IdRepr {
-    I32(i32),
+    /// An integer ID, which is kept as `i64` to hold the sequence numbers of
+    /// DAP.
+    Int(i64),
     String(String),
 }
 
 impl From<i32> for RequestId {
     fn from(id: i32) -> RequestId {
-        RequestId(IdRepr::I32(id))
+        RequestId(IdRepr::Int(id.into()))
+    }
+}
+
+impl From<i64> for RequestId {
+    fn from(id: i64) -> RequestId {
+        RequestId(IdRepr::Int(id))
     }
 }
 
@@ -49,7 +57,7 @@ impl From<String> for RequestId {
 impl fmt::Display for RequestId {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match &self.0 {
-            IdRepr::I32(it) => fmt::Display::fmt(it, f),
+            IdRepr::Int(it) => fmt::Display::fmt(it, f),
             // Use debug here, to make it clear that `92` and `"92"` are
             // different, and to reduce WTF factor if the sever uses `" "` as an
             // ID.
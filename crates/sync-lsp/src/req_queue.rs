@@ -82,6 +82,11 @@ impl<I> Incoming<I> {
         !self.pending.is_empty()
     }
 
+    /// Gets the data of a pending request with the given ID.
+    pub fn get(&self, id: &RequestId) -> Option<&I> {
+        self.pending.get(id)
+    }
+
     /// Checks if a request with the given ID is completed.
     pub fn is_completed(&self, id: &RequestId) -> bool {
         !self.pending.contains_key(id)
@@ -5,6 +5,8 @@ mod dap_srv;
 
 #[cfg(feature = "lsp")]
 mod lsp_srv;
+#[cfg(feature = "lsp")]
+pub use lsp_srv::ProgressHandle;
 
 use core::fmt;
 use std::any::Any;
@@ -18,6 +20,7 @@ use futures::future::MaybeDone;
 use parking_lot::Mutex;
 use serde::Serialize;
 use serde_json::{from_value, Value as JsonValue};
+pub use tokio_util::sync::CancellationToken;
 
 #[cfg(feature = "lsp")]
 use crate::lsp::{Notification, Request};
@@ -91,6 +94,30 @@ pub struct Connection<M> {
     pub receiver: TConnectionRx<M>,
 }
 
+impl<M> Connection<M> {
+    /// Creates a connection that is not attached to any client, which is
+    /// useful to run the server in process, e.g. for headless queries.
+    ///
+    /// The messages sent to the client are kept in the channel and never
+    /// read, and no message is ever received from it.
+    pub fn detached() -> Self {
+        let (event_tx, event_rx) = crossbeam_channel::unbounded();
+        let (lsp_tx, lsp_rx) = crossbeam_channel::unbounded();
+        Self {
+            sender: TConnectionTx {
+                event: event_tx,
+                lsp: lsp_tx,
+                marker: std::marker::PhantomData,
+            },
+            receiver: TConnectionRx {
+                event: event_rx,
+                lsp: lsp_rx,
+                marker: std::marker::PhantomData,
+            },
+        }
+    }
+}
+
 impl<M: TryFrom<Message, Error = anyhow::Error>> From<Connection<Message>> for Connection<M> {
     fn from(conn: Connection<Message>) -> Self {
         Self {
@@ -199,6 +226,7 @@ impl LspClientRoot {
             msg_kind: M::get_message_kind(),
             sender: Arc::downgrade(&_strong),
             req_queue: Arc::new(Mutex::new(ReqQueue::default())),
+            progress: Arc::default(),
         };
         Self { weak, _strong }
     }
@@ -210,7 +238,7 @@ impl LspClientRoot {
 }
 
 type ReqHandler = Box<dyn for<'a> FnOnce(&'a mut dyn Any, LspOrDapResponse) + Send + Sync>;
-type ReqQueue = req_queue::ReqQueue<(String, Instant), ReqHandler>;
+type ReqQueue = req_queue::ReqQueue<(String, Instant, CancellationToken), ReqHandler>;
 
 /// The host for the language server, or known as the LSP client.
 #[derive(Debug, Clone)]
@@ -221,6 +249,9 @@ pub struct LspClient {
     pub(crate) msg_kind: MessageKind,
     pub(crate) sender: Weak<ConnectionTx>,
     pub(crate) req_queue: Arc<Mutex<ReqQueue>>,
+    /// The cancellation tokens of the ongoing work done progresses, keyed by
+    /// the progress tokens.
+    pub(crate) progress: Arc<Mutex<HashMap<String, CancellationToken>>>,
 }
 
 impl LspClient {
@@ -275,11 +306,7 @@ impl LspClient {
     #[cfg(feature = "dap")]
     pub fn complete_dap_request<S: Any>(&self, service: &mut S, response: dap::Response) {
         let mut req_queue = self.req_queue.lock();
-        let Some(handler) = req_queue
-            .outgoing
-            // todo: casting i64 to i32
-            .complete((response.request_seq as i32).into())
-        else {
+        let Some(handler) = req_queue.outgoing.complete(response.request_seq.into()) else {
             log::warn!("received response for unknown request");
             return;
         };
@@ -291,9 +318,31 @@ impl LspClient {
     pub fn register_request(&self, method: &str, id: &RequestId, received_at: Instant) {
         let mut req_queue = self.req_queue.lock();
         self.start_request(id, method);
+        let token = CancellationToken::new();
         req_queue
             .incoming
-            .register(id.clone(), (method.to_owned(), received_at));
+            .register(id.clone(), (method.to_owned(), received_at, token));
+    }
+
+    /// Gets the cancellation token of a pending client2server request. The
+    /// token is cancelled when the client cancels the request.
+    pub fn cancellation_token(&self, id: &RequestId) -> Option<CancellationToken> {
+        let req_queue = self.req_queue.lock();
+        let (_, _, token) = req_queue.incoming.get(id)?;
+        Some(token.clone())
+    }
+
+    /// Cancels a pending client2server request, and responds to the client
+    /// with a `RequestCanceled` error. The result of the request is dropped if
+    /// it is still running.
+    pub fn cancel_request(&self, id: RequestId) {
+        let Some(token) = self.cancellation_token(&id) else {
+            log::info!("ignored cancellation of ({id}): not pending");
+            return;
+        };
+
+        token.cancel();
+        self.respond_any_result(id, Err(resp_err(ErrorCode::RequestCanceled, "cancelled")));
     }
 
     /// Responds a typed result to the client.
@@ -323,7 +372,7 @@ impl LspClient {
     /// Completes an client2server request in the request queue.
     pub fn respond(&self, id: RequestId, response: Message) {
         let mut req_queue = self.req_queue.lock();
-        let Some((method, received_at)) = req_queue.incoming.complete(&id) else {
+        let Some((method, received_at, _)) = req_queue.incoming.complete(&id) else {
             return;
         };
 
@@ -181,7 +181,7 @@ where
     /// Registers and handles a request. This should only be called once per
     /// incoming request.
     fn on_request(&mut self, request_received: Instant, req: dap::Request) {
-        let req_id = (req.seq as i32).into();
+        let req_id = req.seq.into();
         self.client
             .register_request(&req.command, &req_id, request_received);
 
@@ -226,6 +226,13 @@ where
             (_, dapts::request::Initialize::COMMAND) => {
                 just_result(Err(invalid_request("server is already initialized")))
             }
+            (_, dapts::request::Cancel::COMMAND) => {
+                let id = req.arguments.get("requestId").and_then(JsonValue::as_i64);
+                if let Some(id) = id {
+                    self.client.cancel_request(id.into());
+                }
+                just_ok(JsonValue::Null)
+            }
             // todo: generalize this
             // (State::Ready(..), request::ExecuteCommand::METHOD) => {
             // reschedule!(self.on_execute_command(req))
@@ -1,5 +1,7 @@
 use super::*;
 
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use lsp_types::{notification::Notification as Notif, request::Request as Req, *};
 
 type PureHandler<S, T> = fn(srv: &mut S, args: T) -> LspResult<()>;
@@ -77,6 +79,195 @@ impl LspClient {
     }
 }
 
+impl LspClient {
+    /// Begins a server-initiated work done progress, which is shown by the
+    /// client until the returned handle is dropped.
+    ///
+    /// The caller must ensure that the client supports
+    /// `window.workDoneProgress` and that the server is initialized.
+    pub fn begin_progress(&self, title: impl Into<String>, cancellable: bool) -> ProgressHandle {
+        static PROGRESS_ID: AtomicU64 = AtomicU64::new(0);
+        let id = PROGRESS_ID.fetch_add(1, Ordering::Relaxed);
+        let token = ProgressToken::String(format!("tinymist/progress/{id}"));
+
+        // The notifications are queued until the client creates the progress.
+        let state = Arc::new(Mutex::new(ProgressState::Pending(vec![])));
+        let (client, token_, state_) = (self.clone(), token.clone(), state.clone());
+        self.send_lsp_request_::<request::WorkDoneProgressCreate>(
+            WorkDoneProgressCreateParams {
+                token: token.clone(),
+            },
+            move |_, resp| {
+                let mut state = state_.lock();
+                let queued = match std::mem::replace(&mut *state, ProgressState::Created) {
+                    ProgressState::Pending(queued) => queued,
+                    created_or_refused => {
+                        *state = created_or_refused;
+                        return;
+                    }
+                };
+
+                if let Some(err) = resp.error {
+                    log::warn!("failed to create work done progress: {err:?}");
+                    *state = ProgressState::Refused;
+                    return;
+                }
+                for value in queued {
+                    notify_progress(&client, &token_, value);
+                }
+            },
+        );
+
+        let cancel = CancellationToken::new();
+        self.start_progress(token, title.into(), cancellable, cancel, state)
+    }
+
+    /// Begins a work done progress with a token provided by the client, e.g.
+    /// the `workDoneToken` of a request.
+    pub fn begin_progress_with(
+        &self,
+        token: ProgressToken,
+        title: impl Into<String>,
+        cancellable: bool,
+    ) -> ProgressHandle {
+        let cancel = CancellationToken::new();
+        let state = Arc::new(Mutex::new(ProgressState::Created));
+        self.start_progress(token, title.into(), cancellable, cancel, state)
+    }
+
+    /// Begins a work done progress for a pending client2server request with
+    /// the `workDoneToken` of the request. The cancellation token of the
+    /// progress is also cancelled when the request is cancelled.
+    pub fn begin_request_progress(
+        &self,
+        id: &RequestId,
+        token: ProgressToken,
+        title: impl Into<String>,
+    ) -> ProgressHandle {
+        let cancel = match self.cancellation_token(id) {
+            Some(cancel) => cancel.child_token(),
+            None => CancellationToken::new(),
+        };
+        let state = Arc::new(Mutex::new(ProgressState::Created));
+        self.start_progress(token, title.into(), true, cancel, state)
+    }
+
+    fn start_progress(
+        &self,
+        token: ProgressToken,
+        title: String,
+        cancellable: bool,
+        cancel: CancellationToken,
+        state: Arc<Mutex<ProgressState>>,
+    ) -> ProgressHandle {
+        self.progress
+            .lock()
+            .insert(progress_key(&token), cancel.clone());
+
+        let progress = ProgressHandle {
+            client: self.clone(),
+            token,
+            cancel,
+            state,
+        };
+        progress.notify(WorkDoneProgress::Begin(WorkDoneProgressBegin {
+            title,
+            cancellable: Some(cancellable),
+            message: None,
+            percentage: None,
+        }));
+        progress
+    }
+
+    /// Cancels a work done progress on the request of the client.
+    pub fn cancel_progress(&self, token: &ProgressToken) {
+        let Some(cancel) = self.progress.lock().get(&progress_key(token)).cloned() else {
+            log::info!("ignored cancellation of progress ({token:?}): not ongoing");
+            return;
+        };
+
+        cancel.cancel();
+    }
+}
+
+fn progress_key(token: &ProgressToken) -> String {
+    match token {
+        NumberOrString::Number(id) => id.to_string(),
+        NumberOrString::String(id) => id.clone(),
+    }
+}
+
+fn notify_progress(client: &LspClient, token: &ProgressToken, value: WorkDoneProgress) {
+    client.send_notification::<notification::Progress>(&ProgressParams {
+        token: token.clone(),
+        value: ProgressParamsValue::WorkDone(value),
+    });
+}
+
+/// Whether a work done progress can be reported to the client.
+#[derive(Debug)]
+enum ProgressState {
+    /// The client has not responded to `window/workDoneProgress/create` yet,
+    /// so the notifications are queued.
+    Pending(Vec<WorkDoneProgress>),
+    /// The progress is created by the client.
+    Created,
+    /// The client has refused to create the progress, so the notifications
+    /// are dropped.
+    Refused,
+}
+
+/// A handle to a work done progress reported to the client. The progress is
+/// ended when the handle is dropped.
+#[derive(Debug)]
+pub struct ProgressHandle {
+    client: LspClient,
+    token: ProgressToken,
+    cancel: CancellationToken,
+    state: Arc<Mutex<ProgressState>>,
+}
+
+impl ProgressHandle {
+    /// Reports an intermediate state of the progress. The percentage is in
+    /// the range of `0..=100`.
+    pub fn report(&self, message: impl Into<String>, percentage: Option<u32>) {
+        self.notify(WorkDoneProgress::Report(WorkDoneProgressReport {
+            cancellable: None,
+            message: Some(message.into()),
+            percentage: percentage.map(|p| p.min(100)),
+        }));
+    }
+
+    /// Gets the cancellation token of the progress, which is cancelled when
+    /// the user cancels the progress in the client.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancel.clone()
+    }
+
+    /// Checks whether the user has cancelled the progress.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    fn notify(&self, value: WorkDoneProgress) {
+        match &mut *self.state.lock() {
+            ProgressState::Pending(queued) => queued.push(value),
+            ProgressState::Created => notify_progress(&self.client, &self.token, value),
+            ProgressState::Refused => {}
+        }
+    }
+}
+
+impl Drop for ProgressHandle {
+    fn drop(&mut self) {
+        self.client
+            .progress
+            .lock()
+            .remove(&progress_key(&self.token));
+        self.notify(WorkDoneProgress::End(WorkDoneProgressEnd { message: None }));
+    }
+}
+
 impl<Args: Initializer> LsBuilder<LspMessage, Args>
 where
     Args::S: 'static,
@@ -368,6 +559,24 @@ where
         };
 
         match (&mut self.state, &*not.method) {
+            (_, notification::Cancel::METHOD) => {
+                let result = from_json::<CancelParams>(not.params).map(|params| {
+                    self.client.cancel_request(match params.id {
+                        NumberOrString::Number(id) => id.into(),
+                        NumberOrString::String(id) => id.into(),
+                    })
+                });
+                self.client
+                    .stop_notification(&not.method, received_at, result);
+                Ok(())
+            }
+            (_, notification::WorkDoneProgressCancel::METHOD) => {
+                let result = from_json::<WorkDoneProgressCancelParams>(not.params)
+                    .map(|params| self.client.cancel_progress(&params.token));
+                self.client
+                    .stop_notification(&not.method, received_at, result);
+                Ok(())
+            }
             (state, notification::Initialized::METHOD) => {
                 let mut s = State::ShuttingDown;
                 std::mem::swap(state, &mut s);
@@ -1,13 +1,16 @@
 //! Transport layer for LSP messages.
 
 use std::{
-    io::{self, BufRead, Read, Write},
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    str::FromStr,
+    sync::Arc,
     thread,
 };
 
 use crossbeam_channel::{bounded, unbounded, Receiver, Sender};
 
-use crate::{Connection, ConnectionRx, ConnectionTx, GetMessageKind, Message};
+use crate::{Connection, ConnectionRx, ConnectionTx, GetMessageKind, Message, MessageKind};
 
 /// Convenience cli arguments for setting up a transport with an optional mirror
 /// or replay file.
@@ -76,14 +79,22 @@ fn with_stdio_transport_impl(
     };
     let o = || std::io::stdout().lock();
 
+    // Create the transport. Includes the stdio (stdin and stdout) versions, while
+    // the socket versions are created by `with_listen_transport`.
+    let (lsp_sender, lsp_receiver, io_threads) = io_transport(kind, i, o);
+
+    f(connection(lsp_sender, lsp_receiver))?;
+
+    io_threads.join_write()?;
+
+    Ok(())
+}
+
+/// Creates a connection with an event channel from the message channels.
+fn connection(lsp_sender: Sender<Message>, lsp_receiver: Receiver<Message>) -> Connection<Message> {
     let (event_sender, event_receiver) = unbounded::<crate::Event>();
 
-    // Create the transport. Includes the stdio (stdin and stdout) versions but this
-    // could also be implemented to use sockets or HTTP.
-    let (lsp_sender, lsp_receiver, io_threads) = io_transport(kind, i, o);
-    let connection = Connection {
-        // lsp_sender,
-        // lsp_receiver,
+    Connection {
         sender: ConnectionTx {
             event: event_sender,
             lsp: lsp_sender,
@@ -94,15 +105,168 @@ fn with_stdio_transport_impl(
             lsp: lsp_receiver,
             marker: std::marker::PhantomData,
         },
-    };
+    }
+}
 
-    f(connection)?;
+/// An address to listen on for serving editors over sockets.
+///
+/// The accepted formats are:
+/// - `tcp://127.0.0.1:9257`, or simply `127.0.0.1:9257`
+/// - `unix:///tmp/tinymist.sock`, on unix platforms
+/// - `ws://127.0.0.1:9257`, with the `ws` feature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenAddr {
+    /// A TCP socket address.
+    Tcp(String),
+    /// A path to a Unix domain socket.
+    #[cfg(unix)]
+    Unix(std::path::PathBuf),
+    /// A TCP socket address accepting WebSocket connections, in which each
+    /// text or binary message carries a single JSON-RPC message.
+    #[cfg(feature = "ws")]
+    WebSocket(String),
+}
 
-    io_threads.join_write()?;
+impl FromStr for ListenAddr {
+    type Err = anyhow::Error;
+
+    fn from_str(addr: &str) -> anyhow::Result<Self> {
+        if let Some(addr) = addr.strip_prefix("tcp://") {
+            return Ok(Self::Tcp(addr.to_owned()));
+        }
+        if let Some(path) = addr.strip_prefix("unix://") {
+            #[cfg(unix)]
+            return Ok(Self::Unix(path.into()));
+            #[cfg(not(unix))]
+            anyhow::bail!("unix domain sockets are not supported on this platform: {path}");
+        }
+        if let Some(addr) = addr.strip_prefix("ws://") {
+            #[cfg(feature = "ws")]
+            return Ok(Self::WebSocket(addr.to_owned()));
+            #[cfg(not(feature = "ws"))]
+            anyhow::bail!("websocket transport is not enabled: {addr}");
+        }
+        if addr.contains("://") {
+            anyhow::bail!("unsupported listen address: {addr}");
+        }
+
+        Ok(Self::Tcp(addr.to_owned()))
+    }
+}
+
+type ConnHandler = Arc<dyn Fn(Connection<Message>) -> anyhow::Result<()> + Send + Sync>;
+type IoTransport = (Sender<Message>, Receiver<Message>, IoThreads);
+
+/// Listens on the address and serves each accepted connection with `f` in a
+/// dedicated thread, so that a single server process can serve several
+/// editors. Unlike [`with_stdio_transport`], the function only returns when
+/// the listener fails.
+pub fn with_listen_transport<M: TryFrom<Message, Error = anyhow::Error> + GetMessageKind>(
+    addr: ListenAddr,
+    f: impl Fn(Connection<M>) -> anyhow::Result<()> + Send + Sync + 'static,
+) -> anyhow::Result<()> {
+    let kind = M::get_message_kind();
+    let f: ConnHandler = Arc::new(move |conn: Connection<Message>| f(conn.into()));
+
+    match addr {
+        ListenAddr::Tcp(addr) => {
+            let listener = TcpListener::bind(&addr)?;
+            log::info!("listening on tcp://{}", listener.local_addr()?);
+            serve(kind, listener.incoming(), stream_transport, f)
+        }
+        #[cfg(unix)]
+        ListenAddr::Unix(path) => {
+            use std::os::unix::fs::FileTypeExt;
+            use std::os::unix::net::UnixListener;
+
+            // Removes the socket left by a previous server.
+            let is_socket = std::fs::metadata(&path).is_ok_and(|m| m.file_type().is_socket());
+            if is_socket {
+                std::fs::remove_file(&path)?;
+            }
+
+            let listener = UnixListener::bind(&path)?;
+            log::info!("listening on unix://{}", path.display());
+            serve(kind, listener.incoming(), stream_transport, f)
+        }
+        #[cfg(feature = "ws")]
+        ListenAddr::WebSocket(addr) => {
+            let listener = TcpListener::bind(&addr)?;
+            log::info!("listening on ws://{}", listener.local_addr()?);
+            serve(kind, listener.incoming(), ws::ws_transport, f)
+        }
+    }
+}
+
+/// Accepts the incoming streams and serves each of them in a thread.
+fn serve<S: Send + 'static>(
+    kind: MessageKind,
+    incoming: impl Iterator<Item = io::Result<S>>,
+    transport: fn(MessageKind, S) -> io::Result<IoTransport>,
+    f: ConnHandler,
+) -> anyhow::Result<()> {
+    for stream in incoming {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                log::warn!("failed to accept connection: {err}");
+                continue;
+            }
+        };
+
+        let f = f.clone();
+        thread::spawn(move || {
+            let (lsp_sender, lsp_receiver, io_threads) = match transport(kind, stream) {
+                Ok(transport) => transport,
+                Err(err) => {
+                    log::warn!("failed to set up connection: {err}");
+                    return;
+                }
+            };
+
+            log::info!("serving new connection");
+            if let Err(err) = f(connection(lsp_sender, lsp_receiver)) {
+                log::error!("connection failed: {err:?}");
+            }
+            if let Err(err) = io_threads.join_write() {
+                log::warn!("failed to close connection: {err}");
+            }
+            log::info!("connection closed");
+        });
+    }
 
     Ok(())
 }
 
+/// A stream that can be split into a reading half and a writing half.
+trait SplitStream: Read + Write + Send + Sync + Sized + 'static {
+    fn try_clone(&self) -> io::Result<Self>;
+}
+
+impl SplitStream for TcpStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        TcpStream::try_clone(self)
+    }
+}
+
+#[cfg(unix)]
+impl SplitStream for std::os::unix::net::UnixStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        std::os::unix::net::UnixStream::try_clone(self)
+    }
+}
+
+/// Creates a transport over a byte stream, in which messages are framed by the
+/// `Content-Length` header as the stdio transport does.
+fn stream_transport<S: SplitStream>(kind: MessageKind, stream: S) -> io::Result<IoTransport> {
+    let out = stream.try_clone()?;
+    Ok(io_transport(
+        kind,
+        move || BufReader::new(stream),
+        move || out,
+    ))
+}
+
 /// Creates an LSP connection via io.
 ///
 /// # Example
@@ -233,3 +397,239 @@ impl<R: Read + BufRead, W: Write> BufRead for MirrorWriter<R, W> {
         self.0.consume(amt);
     }
 }
+
+#[cfg(feature = "ws")]
+mod ws {
+    use std::time::Duration;
+
+    use crossbeam_channel::TryRecvError;
+    use tungstenite::error::Error;
+    use tungstenite::WebSocket;
+
+    use super::*;
+
+    /// The interval to check the messages to send while waiting for the
+    /// messages from the client.
+    const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+    /// Creates a transport over a WebSocket connection. The messages are
+    /// re-framed by the `Content-Length` header to share the stream transport.
+    ///
+    /// The WebSocket is owned by a single I/O thread, so that the control
+    /// frames replied by the protocol, e.g. pongs, never interleave with the
+    /// messages being sent.
+    pub(super) fn ws_transport(kind: MessageKind, stream: TcpStream) -> io::Result<IoTransport> {
+        let ws = tungstenite::accept(stream).map_err(|err| io::Error::other(err.to_string()))?;
+        ws.get_ref().set_read_timeout(Some(POLL_INTERVAL))?;
+
+        let (inp_sender, inp_receiver) = unbounded::<Vec<u8>>();
+        let (out_sender, out_receiver) = unbounded::<String>();
+        thread::spawn(move || {
+            if let Err(err) = ws_io(ws, inp_sender, out_receiver) {
+                log::warn!("websocket connection failed: {err}");
+            }
+        });
+
+        let inp = WsReader {
+            rx: inp_receiver,
+            buf: Vec::new(),
+            pos: 0,
+        };
+        let out = WsWriter {
+            tx: out_sender,
+            buf: Vec::new(),
+        };
+        Ok(io_transport(kind, move || inp, move || out))
+    }
+
+    /// Reads the messages from the WebSocket and sends the messages to it, until
+    /// either side closes the connection.
+    fn ws_io(
+        mut ws: WebSocket<TcpStream>,
+        inp: Sender<Vec<u8>>,
+        out: Receiver<String>,
+    ) -> io::Result<()> {
+        let ws_err = |err: Error| io::Error::other(err.to_string());
+
+        loop {
+            // Sends the pending messages first.
+            loop {
+                match out.try_recv() {
+                    Ok(msg) => ws.send(tungstenite::Message::text(msg)).map_err(ws_err)?,
+                    Err(TryRecvError::Empty) => break,
+                    // The server has stopped writing.
+                    Err(TryRecvError::Disconnected) => {
+                        ws.close(None).map_err(ws_err)?;
+                        return ws.flush().or_else(ignore_closed).map_err(ws_err);
+                    }
+                }
+            }
+
+            let msg = match ws.read() {
+                Ok(msg) => msg,
+                Err(Error::Io(err)) if is_timeout(&err) => continue,
+                Err(err) => return ignore_closed(err).map_err(ws_err),
+            };
+            if msg.is_close() {
+                return Ok(());
+            }
+            // Pings and pongs are handled by the protocol.
+            if !msg.is_text() && !msg.is_binary() {
+                continue;
+            }
+            if inp.send(msg.into_data()).is_err() {
+                // The server has stopped reading.
+                return Ok(());
+            }
+        }
+    }
+
+    fn is_timeout(err: &io::Error) -> bool {
+        matches!(
+            err.kind(),
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+        )
+    }
+
+    fn ignore_closed(err: Error) -> Result<(), Error> {
+        match err {
+            Error::ConnectionClosed | Error::AlreadyClosed => Ok(()),
+            err => Err(err),
+        }
+    }
+
+    /// Reads the messages received by the I/O thread.
+    struct WsReader {
+        rx: Receiver<Vec<u8>>,
+        buf: Vec<u8>,
+        pos: usize,
+    }
+
+    impl Read for WsReader {
+        fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+            let buf = self.fill_buf()?;
+            let len = buf.len().min(out.len());
+            out[..len].copy_from_slice(&buf[..len]);
+            self.consume(len);
+            Ok(len)
+        }
+    }
+
+    impl BufRead for WsReader {
+        fn fill_buf(&mut self) -> io::Result<&[u8]> {
+            if self.pos >= self.buf.len() {
+                // The connection is closed if the I/O thread has finished.
+                let Ok(data) = self.rx.recv() else {
+                    return Ok(&[]);
+                };
+                self.buf.clear();
+                self.pos = 0;
+                write!(self.buf, "Content-Length: {}\r\n\r\n", data.len())?;
+                self.buf.extend_from_slice(&data);
+            }
+
+            Ok(&self.buf[self.pos..])
+        }
+
+        fn consume(&mut self, amt: usize) {
+            self.pos += amt;
+        }
+    }
+
+    /// Splits the written bytes into messages and passes them to the I/O
+    /// thread.
+    struct WsWriter {
+        tx: Sender<String>,
+        buf: Vec<u8>,
+    }
+
+    impl Write for WsWriter {
+        fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+            self.buf.extend_from_slice(data);
+            Ok(data.len())
+        }
+
+        /// Sends the complete messages in the buffer, and keeps the incomplete
+        /// one for the next flush.
+        fn flush(&mut self) -> io::Result<()> {
+            let mut consumed = 0;
+            let mut rest = &self.buf[..];
+            while let Some(header_end) = rest.windows(4).position(|w| w == b"\r\n\r\n") {
+                let body_start = header_end + 4;
+                let len = content_length(&rest[..header_end])?;
+                let Some(body) = rest.get(body_start..body_start + len) else {
+                    break;
+                };
+
+                let body = String::from_utf8(body.to_vec()).map_err(crate::invalid_data)?;
+                self.tx
+                    .send(body)
+                    .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+                consumed += body_start + len;
+                rest = &rest[body_start + len..];
+            }
+
+            self.buf.drain(..consumed);
+            Ok(())
+        }
+    }
+
+    /// Gets the `Content-Length` from the headers of a message.
+    fn content_length(headers: &[u8]) -> io::Result<usize> {
+        let headers = std::str::from_utf8(headers).map_err(crate::invalid_data)?;
+        let len = headers.split("\r\n").find_map(|header| {
+            let (name, value) = header.split_once(": ")?;
+            name.eq_ignore_ascii_case("Content-Length")
+                .then(|| value.trim().parse::<usize>())
+        });
+        match len {
+            Some(len) => len.map_err(crate::invalid_data),
+            None => Err(crate::invalid_data("no Content-Length")),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn split_written_messages() {
+            let (tx, rx) = unbounded();
+            let mut out = WsWriter {
+                tx,
+                buf: Vec::new(),
+            };
+
+            // Two messages and the head of a third one are written at once.
+            let data = "Content-Length: 2\r\n\r\n{}Content-Length: 4\r\n\r\n[1]\nContent-Length: 3";
+            out.write_all(data.as_bytes()).unwrap();
+            out.flush().unwrap();
+            assert_eq!(rx.try_iter().collect::<Vec<_>>(), ["{}", "[1]\n"]);
+
+            out.write_all(b"\r\n\r\nnul").unwrap();
+            out.flush().unwrap();
+            assert_eq!(rx.try_iter().collect::<Vec<_>>(), ["nul"]);
+            assert!(out.buf.is_empty());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ListenAddr;
+
+    #[test]
+    fn parse_listen_addr() {
+        let parse = |addr: &str| addr.parse::<ListenAddr>().ok();
+
+        let tcp = Some(ListenAddr::Tcp("127.0.0.1:9257".to_owned()));
+        assert_eq!(parse("127.0.0.1:9257"), tcp);
+        assert_eq!(parse("tcp://127.0.0.1:9257"), tcp);
+        #[cfg(unix)]
+        assert_eq!(
+            parse("unix:///tmp/tinymist.sock"),
+            Some(ListenAddr::Unix("/tmp/tinymist.sock".into()))
+        );
+        assert_eq!(parse("http://127.0.0.1:9257"), None);
+    }
+}
@@ -1191,6 +1191,13 @@ pub struct TypeInfo {
     pub local_binds: snapshot_map::SnapshotMap<DeclExpr, Ty>,
     /// The typing on syntax structures
     pub mapping: FxHashMap<Span, FxHashSet<Ty>>,
+    /// The record types inferred from the contents of data files
+    pub data_records: FxHashSet<Interned<RecordTy>>,
+    /// The content hashes of the data files loaded, which are checked to
+    /// reuse the typing
+    pub data_deps: FxHashMap<FileId, u128>,
+    /// The fields selected on data but missing in the data files
+    pub missing_fields: Vec<(Span, StrRef)>,
 
     pub(super) cano_cache: Mutex<TypeCanoStore>,
 }
@@ -3,7 +3,7 @@ use std::{path::Path, sync::OnceLock};
 use clap::ValueHint;
 use tinymist_std::{bail, error::prelude::Result};
 
-pub use tinymist_world::args::{CompileFontArgs, CompilePackageArgs};
+pub use tinymist_world::args::{CompileFontArgs, CompilePackageArgs, DiagnosticArgs};
 
 use crate::model::*;
 use crate::PROJECT_ROUTE_USER_ACTION_PRIORITY;
@@ -16,6 +16,8 @@ pub enum DocCommands {
     New(DocNewArgs),
     /// Configure document priority in workspace.
     Configure(DocConfigureArgs),
+    /// Build a static documentation site for a package.
+    Build(DocBuildArgs),
 }
 
 /// Declare a document (project's input).
@@ -114,6 +116,21 @@ pub struct DocConfigureArgs {
     pub priority: u32,
 }
 
+/// Build a static documentation site for a package.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct DocBuildArgs {
+    /// The package to build docs for, e.g. `@preview/example:0.1.0`.
+    #[clap(long)]
+    pub id: String,
+    /// The path of the package. If not provided, the package will be resolved
+    /// by the package registry.
+    #[clap(long)]
+    pub path: Option<String>,
+    /// The output directory of the site.
+    #[clap(short, long, default_value = "docs")]
+    pub output: String,
+}
+
 /// Declare an compile task.
 #[derive(Debug, Clone, clap::Parser)]
 pub struct TaskCompileArgs {
@@ -129,6 +146,19 @@ pub struct TaskCompileArgs {
     #[arg(long = "when")]
     pub when: Option<TaskWhen>,
 
+    /// The idle time in milliseconds before running an `onIdle` task.
+    #[arg(long = "debounce", value_name = "MS")]
+    pub debounce: Option<u64>,
+
+    /// The glob patterns, relative to the root, of the files whose changes run
+    /// an `onChange` task. Can be specified multiple times.
+    #[arg(long = "watch", value_name = "GLOB")]
+    pub watch: Vec<String>,
+
+    /// The tasks that must be run before this task. Separated by commas.
+    #[arg(long = "depends-on", value_delimiter = ',')]
+    pub depends_on: Vec<String>,
+
     /// Path to output file (PDF, PNG, SVG, or HTML). Use `-` to write output to
     /// stdout.
     ///
@@ -157,6 +187,12 @@ pub struct TaskCompileArgs {
     #[arg(long = "pages", value_delimiter = ',')]
     pub pages: Option<Vec<Pages>>,
 
+    /// The variants of the document to export, e.g. `draft:lang=en,draft=true`.
+    /// Each variant is exported with its inputs added to `sys.inputs`. The
+    /// output path can be substituted with `$variant` and `$inputs.<key>`.
+    #[arg(long = "variant")]
+    pub variants: Vec<TaskVariant>,
+
     /// The argument to export to PDF.
     #[clap(flatten)]
     pub pdf: PdfExportArgs,
@@ -205,10 +241,16 @@ impl TaskCompileArgs {
             });
         }
 
+        // todo: write to stdout
+        let output = self.output.filter(|output| output != "-");
+
         let export = ExportTask {
             when,
-            output: None,
+            debounce: self.debounce,
+            watch: self.watch,
+            output: output.map(|output| PathPattern::new(&output)),
             transform: transforms,
+            matrix: self.variants,
         };
 
         let config = match output_format {
@@ -216,6 +258,16 @@ impl TaskCompileArgs {
                 export,
                 pdf_standards: self.pdf.pdf_standard.clone(),
                 creation_timestamp: None,
+                ident: self.pdf.pdf_ident.clone(),
+                title: self.pdf.pdf_title.clone(),
+                author: self.pdf.pdf_author.clone(),
+                keywords: self.pdf.pdf_keywords.clone(),
+                attachments: self
+                    .pdf
+                    .pdf_attach
+                    .iter()
+                    .map(|p| PathPattern::new(p))
+                    .collect(),
             }),
             OutputFormat::Png => ProjectTask::ExportPng(ExportPngTask {
                 export,
@@ -229,6 +281,7 @@ impl TaskCompileArgs {
         Ok(ApplyProjectTask {
             id: task_id.clone(),
             document: doc_id,
+            depends_on: self.depends_on.into_iter().map(Id::new).collect(),
             task: config,
         })
     }
@@ -241,6 +294,29 @@ pub struct PdfExportArgs {
     /// conformance with.
     #[arg(long = "pdf-standard", value_delimiter = ',')]
     pub pdf_standard: Vec<PdfStandard>,
+
+    /// A stable identifier of the document, which is kept across the exports
+    /// of the document.
+    #[arg(long = "pdf-ident")]
+    pub pdf_ident: Option<String>,
+
+    /// Overrides the title of the document.
+    #[arg(long = "pdf-title")]
+    pub pdf_title: Option<String>,
+
+    /// Overrides the authors of the document. Can be specified multiple
+    /// times, once per author.
+    #[arg(long = "pdf-author", value_name = "AUTHOR")]
+    pub pdf_author: Vec<String>,
+
+    /// Overrides the keywords of the document. Can be specified multiple
+    /// times, once per keyword.
+    #[arg(long = "pdf-keywords", value_name = "KEYWORD")]
+    pub pdf_keywords: Vec<String>,
+
+    /// Attaches a file to the PDF. Can be specified multiple times.
+    #[arg(long = "pdf-attach", value_name = "PATH", value_hint = ValueHint::FilePath)]
+    pub pdf_attach: Vec<String>,
 }
 
 /// Declare arguments for exporting a document to PNG.
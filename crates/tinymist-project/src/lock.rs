@@ -1,8 +1,10 @@
 #![allow(missing_docs)]
 
 use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::io::{Read, Seek, SeekFrom, Write};
-use std::{path::Path, sync::Arc};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use ecow::{eco_vec, EcoVec};
 use tinymist_std::error::prelude::*;
@@ -25,6 +27,51 @@ impl LockFile {
         self.task.iter().find(|i| &i.id == id)
     }
 
+    /// Schedules the tasks together with their transitive dependencies. Each
+    /// returned stage only depends on the stages before it. If `ids` is
+    /// empty, all tasks are scheduled.
+    pub fn schedule(&self, ids: &[Id]) -> Result<Vec<Vec<&ApplyProjectTask>>> {
+        let mut pending: Vec<&ApplyProjectTask> = vec![];
+        let mut stack: Vec<&Id> = if ids.is_empty() {
+            self.task.iter().map(|task| &task.id).collect()
+        } else {
+            ids.iter().collect()
+        };
+        while let Some(id) = stack.pop() {
+            if pending.iter().any(|task| &task.id == id) {
+                continue;
+            }
+            let Some(task) = self.get_task(id) else {
+                bail!("task {id} is not found");
+            };
+            pending.push(task);
+            stack.extend(task.depends_on.iter());
+        }
+        // Keeps the order in the lock file.
+        pending.sort_by_key(|task| self.task.iter().position(|i| i.id == task.id));
+
+        let mut done = HashSet::new();
+        let mut stages = vec![];
+        while !pending.is_empty() {
+            let (ready, rest): (Vec<_>, Vec<_>) = pending
+                .into_iter()
+                .partition(|task| task.depends_on.iter().all(|dep| done.contains(dep)));
+            if ready.is_empty() {
+                let ids = rest.iter().map(|task| task.id.to_string());
+                bail!(
+                    "cyclic dependencies among tasks: {}",
+                    ids.collect::<Vec<_>>().join(", ")
+                );
+            }
+
+            done.extend(ready.iter().map(|task| &task.id));
+            stages.push(ready);
+            pending = rest;
+        }
+
+        Ok(stages)
+    }
+
     pub fn replace_document(&mut self, input: ProjectInput) {
         let id = input.id.clone();
         let index = self.document.iter().position(|i| i.id == id);
@@ -303,35 +350,35 @@ impl LockFileUpdate {
 
     pub fn commit(self) {
         super::LockFile::update(&self.root, |l| {
-            let root: EcoString = unix_slash(&self.root).into();
-            let root_hash = tinymist_std::hash::hash128(&root);
             for update in self.updates {
                 match update {
                     LockUpdate::Input(input) => {
                         l.replace_document(input);
                     }
-                    LockUpdate::Task(task) => {
+                    LockUpdate::Task(mut task) => {
+                        // The dependencies are only declared by the user, and are kept
+                        // when the task is updated by an export.
+                        if let Some(prev) = l.get_task(task.id()) {
+                            if task.depends_on.is_empty() {
+                                task.depends_on = prev.depends_on.clone();
+                            }
+                        }
+                        if !task.depends_on.is_empty() {
+                            log::warn!(
+                                "ProjectCompiler: dependencies of task {} are not run, which are only run by `tinymist build`",
+                                task.id()
+                            );
+                        }
                         l.replace_task(task);
                     }
                     LockUpdate::Material(mut mat) => {
-                        let root: EcoString = unix_slash(&self.root).into();
-                        mat.root = root.clone();
-                        let cache_dir = dirs::cache_dir();
-                        if let Some(cache_dir) = cache_dir {
-                            let id = tinymist_std::hash::hash128(&mat.id);
-                            let root_lo = root_hash & 0xfff;
-                            let root_hi = root_hash >> 12;
-                            let id_lo = id & 0xfff;
-                            let id_hi = id >> 12;
-
-                            let hash_str =
-                                format!("{root_lo:03x}/{root_hi:013x}/{id_lo:03x}/{id_hi:016x}");
-
-                            let cache_dir = cache_dir.join("tinymist/projects").join(hash_str);
-                            let _ = std::fs::create_dir_all(&cache_dir);
+                        mat.root = unix_slash(&self.root).into();
+                        if let Some(path) = material_cache_path(&self.root, &mat.id) {
+                            if let Some(cache_dir) = path.parent() {
+                                let _ = std::fs::create_dir_all(cache_dir);
+                            }
 
                             let data = serde_json::to_string(&mat).unwrap();
-                            let path = cache_dir.join("path-material.json");
                             tinymist_std::fs::paths::write_atomic(path, data)
                                 .log_error("ProjectCompiler: write material error");
 
@@ -351,6 +398,29 @@ impl LockFileUpdate {
     }
 }
 
+/// Reads the materials of a document recorded by the last compilation in the
+/// project.
+pub fn read_materials(root: &Path, id: &Id) -> Option<ProjectPathMaterial> {
+    let data = std::fs::read(material_cache_path(root, id)?).ok()?;
+    serde_json::from_slice(&data).ok()
+}
+
+/// Gets the path to the materials of a document in the cache directory.
+fn material_cache_path(root: &Path, id: &Id) -> Option<PathBuf> {
+    let root: EcoString = unix_slash(root).into();
+    let root_hash = tinymist_std::hash::hash128(&root);
+    let id = tinymist_std::hash::hash128(id);
+    let root_lo = root_hash & 0xfff;
+    let root_hi = root_hash >> 12;
+    let id_lo = id & 0xfff;
+    let id_hi = id >> 12;
+
+    let hash_str = format!("{root_lo:03x}/{root_hi:013x}/{id_lo:03x}/{id_hi:016x}");
+
+    let cache_dir = dirs::cache_dir()?.join("tinymist/projects").join(hash_str);
+    Some(cache_dir.join("path-material.json"))
+}
+
 struct Version<'a>(&'a str);
 
 impl PartialEq for Version<'_> {
@@ -369,3 +439,72 @@ impl PartialOrd for Version<'_> {
         Some(lhs.cmp(&rhs))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tinymist_task::{PreviewTask, ProjectTask, TaskWhen};
+
+    use super::*;
+
+    fn task(id: &str, depends_on: &[&str]) -> ApplyProjectTask {
+        ApplyProjectTask {
+            id: Id::new(id.to_owned()),
+            document: Id::new("main.typ".to_owned()),
+            depends_on: depends_on
+                .iter()
+                .map(|id| Id::new(id.to_string()))
+                .collect(),
+            task: ProjectTask::Preview(PreviewTask {
+                when: TaskWhen::Never,
+            }),
+        }
+    }
+
+    fn schedule(lock: &LockFile, ids: &[&str]) -> Result<Vec<Vec<String>>> {
+        let ids = ids
+            .iter()
+            .map(|id| Id::new(id.to_string()))
+            .collect::<Vec<_>>();
+        let stages = lock.schedule(&ids)?;
+        Ok(stages
+            .into_iter()
+            .map(|stage| stage.into_iter().map(|task| task.id.to_string()).collect())
+            .collect())
+    }
+
+    #[test]
+    fn test_schedule() {
+        let lock = LockFile {
+            task: vec![
+                task("main", &["glossary", "index"]),
+                task("glossary", &[]),
+                task("index", &["glossary"]),
+                task("slides", &[]),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            schedule(&lock, &[]).unwrap(),
+            vec![vec!["glossary", "slides"], vec!["index"], vec!["main"]]
+        );
+        assert_eq!(
+            schedule(&lock, &["index"]).unwrap(),
+            vec![vec!["glossary"], vec!["index"]]
+        );
+        assert!(schedule(&lock, &["unknown"]).is_err());
+    }
+
+    #[test]
+    fn test_schedule_cycle() {
+        let lock = LockFile {
+            task: vec![task("a", &["b"]), task("b", &["a"]), task("c", &[])],
+            ..Default::default()
+        };
+
+        let err = schedule(&lock, &[]).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("cyclic dependencies among tasks: a, b"));
+    }
+}
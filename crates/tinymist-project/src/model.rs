@@ -93,6 +93,16 @@ pub struct ProjectInput {
     pub package_cache_path: Option<ResourcePath>,
 }
 
+impl ProjectInput {
+    /// Returns the input of a variant of the project.
+    pub fn with_variant(&self, variant: &TaskVariant) -> Self {
+        let mut input = self.clone();
+        input.inputs.extend(variant.inputs.iter().cloned());
+        input.font_paths.extend(variant.font_paths.iter().cloned());
+        input
+    }
+}
+
 /// A project route specifier.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -123,6 +133,7 @@ impl ProjectPathMaterial {
     pub fn from_deps(doc_id: Id, files: EcoVec<ImmutPath>) -> Self {
         let mut files: Vec<_> = files.into_iter().map(|p| p.as_ref().to_owned()).collect();
         files.sort();
+        files.dedup();
 
         ProjectPathMaterial {
             root: EcoString::default(),
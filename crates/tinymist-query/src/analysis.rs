@@ -20,10 +20,14 @@ pub mod stats;
 pub use stats::*;
 pub mod definition;
 pub use definition::*;
+pub mod docs_index;
+pub use docs_index::*;
 pub mod signature;
 pub use signature::*;
 pub mod semantic_tokens;
 pub use semantic_tokens::*;
+mod sys_inputs;
+pub(crate) use sys_inputs::*;
 use tinymist_std::ImmutPath;
 use tinymist_world::vfs::WorkspaceResolver;
 use tinymist_world::WorldDeps;
@@ -1,8 +1,47 @@
-use typst::foundations::Bytes;
+use core::fmt;
+
+use hayagriva::citationberg::{IndependentStyle, Locale};
+use hayagriva::{
+    BibliographyDriver, BibliographyRequest, BufWriteFormat, CitationItem, CitationRequest, Library,
+};
+use typst::foundations::{Bytes, IntoValue, Packed, StyleChain};
+use typst::introspection::Introspector;
+use typst::model::BibliographyElem;
 use yaml_rust2::{parser::Event, parser::MarkedEventReceiver, scanner::Marker};
 
 use super::prelude::*;
 
+/// Finds the bibliography element of a document and analyzes its sources.
+pub(crate) fn find_bib(
+    world: &dyn World,
+    introspector: &Introspector,
+) -> Option<(Packed<BibliographyElem>, Arc<BibInfo>)> {
+    let bib_elem = BibliographyElem::find(introspector.track()).ok()?;
+    let Value::Array(paths) = bib_elem.sources.clone().into_value() else {
+        return None;
+    };
+
+    let bib_paths = paths
+        .into_iter()
+        .flat_map(|path| path.cast().ok())
+        .collect();
+    let bib_info = super::analyze_bib(world.track(), bib_paths, bib_elem.span().id()?)?;
+    Some((bib_elem, bib_info))
+}
+
+/// Gets the citation style of a bibliography, which is `ieee` by default.
+///
+/// The elements in the introspector have their fields materialized from the
+/// style chain, so the style given by a `set bibliography(..)` rule is also
+/// read here.
+pub(crate) fn bib_style(bib_elem: &Packed<BibliographyElem>) -> Arc<IndependentStyle> {
+    let style = bib_elem.style(StyleChain::default());
+    Arc::new(style.derived.get().clone())
+}
+
+/// The locales used to render the bibliography entries.
+static LOCALES: LazyLock<Vec<Locale>> = LazyLock::new(hayagriva::archive::locales);
+
 pub(crate) fn bib_info(files: EcoVec<(TypstFileId, Bytes)>) -> Option<Arc<BibInfo>> {
     let mut worker = BibWorker {
         info: BibInfo::default(),
@@ -24,6 +63,108 @@ pub(crate) fn bib_info(files: EcoVec<(TypstFileId, Bytes)>) -> Option<Arc<BibInf
 pub struct BibInfo {
     /// The bibliography entries.
     pub entries: indexmap::IndexMap<String, BibEntry>,
+    /// The problems found in the bibliography files.
+    pub issues: Vec<BibIssue>,
+    /// The parsed bibliography entries, which are used to render references.
+    pub library: Library,
+}
+
+impl BibInfo {
+    /// Renders an entry as it is shown in the bibliography of the `style`.
+    pub fn render(&self, key: &str, style: &IndependentStyle) -> Option<String> {
+        let entry = self.library.get(key)?;
+
+        let mut driver = BibliographyDriver::new();
+        let items = vec![CitationItem::with_entry(entry)];
+        driver.citation(CitationRequest::from_items(items, style, &LOCALES));
+        let rendered = driver.finish(BibliographyRequest::new(style, None, &LOCALES));
+
+        let content = match rendered.bibliography {
+            Some(bib) => bib.items.into_iter().next()?.content,
+            // Note styles may have no bibliography, so we show the citation.
+            None => rendered.citations.into_iter().next()?.citation,
+        };
+        let mut reference = String::new();
+        content
+            .write_buf(&mut reference, BufWriteFormat::Plain)
+            .ok()?;
+        Some(reference)
+    }
+
+    /// Summarizes an entry by its authors, year, title and venue.
+    pub fn summary(&self, key: &str) -> Option<BibSummary> {
+        let entry = self.library.get(key)?;
+
+        let authors = entry.authors().unwrap_or_default();
+        let authors = authors.iter().map(|author| author.name.clone()).collect();
+        let venue = entry.parents().first().and_then(|parent| parent.title());
+
+        Some(BibSummary {
+            authors,
+            year: entry.date_any().map(|date| date.year),
+            title: entry.title().map(|title| title.to_string()),
+            venue: venue.map(|venue| venue.to_string()),
+        })
+    }
+}
+
+/// The summary of a bibliography entry.
+#[derive(Debug, Clone)]
+pub struct BibSummary {
+    /// The family names of the authors.
+    pub authors: Vec<String>,
+    /// The year of publication.
+    pub year: Option<i32>,
+    /// The title of the entry.
+    pub title: Option<String>,
+    /// The title of the parent entry, e.g. the journal or proceedings.
+    pub venue: Option<String>,
+}
+
+impl fmt::Display for BibSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.authors.as_slice() {
+            [] => write!(f, "Anonymous")?,
+            [author] => write!(f, "{author}")?,
+            [first, second] => write!(f, "{first} and {second}")?,
+            [first, ..] => write!(f, "{first} et al.")?,
+        }
+        if let Some(year) = self.year {
+            write!(f, " ({year})")?;
+        }
+        if let Some(title) = &self.title {
+            write!(f, ". {title}")?;
+        }
+        if let Some(venue) = &self.venue {
+            write!(f, ". {venue}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A problem found in a bibliography file.
+#[derive(Debug, Clone)]
+pub struct BibIssue {
+    /// The key of the entry.
+    pub key: String,
+    /// The entry that has the problem.
+    pub entry: BibEntry,
+    /// The kind of the problem.
+    pub kind: BibIssueKind,
+}
+
+/// The kind of a problem found in a bibliography file.
+#[derive(Debug, Clone)]
+pub enum BibIssueKind {
+    /// The key is already defined by another entry.
+    Duplicate(BibEntry),
+    /// The entry misses some fields required by its type.
+    MissingFields {
+        /// The type of the entry.
+        ty: String,
+        /// The missing fields, each is a list of alternatives.
+        fields: Vec<&'static [&'static str]>,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -43,10 +184,14 @@ impl BibWorker {
         let content = std::str::from_utf8(&content).ok()?;
 
         match file_extension.to_lowercase().as_str() {
-            "yml" | "yaml" => self.yaml_bib(file_id, content),
+            "yml" | "yaml" => {
+                self.yaml_bib(file_id, content);
+                self.library(hayagriva::io::from_yaml_str(content).ok());
+            }
             "bib" => {
                 let bibliography = biblatex::RawBibliography::parse(content).ok()?;
-                self.tex_bib(file_id, bibliography)
+                self.tex_bib(file_id, bibliography);
+                self.library(hayagriva::io::from_biblatex_str(content).ok());
             }
             _ => return None,
         };
@@ -54,22 +199,150 @@ impl BibWorker {
         Some(())
     }
 
+    fn library(&mut self, library: Option<Library>) {
+        for entry in library.iter().flat_map(Library::iter) {
+            // Keeps the first entry, as the same as `insert`.
+            if self.info.library.get(entry.key()).is_none() {
+                self.info.library.push(entry);
+            }
+        }
+    }
+
     fn yaml_bib(&mut self, file_id: TypstFileId, content: &str) {
         let yaml = YamlBib::from_content(content, file_id);
-        self.info.entries.extend(yaml.entries);
+        for ((name, entry), (ty, fields)) in yaml.entries.into_iter().zip(yaml.fields) {
+            let missing = match ty {
+                Some(ty) => missing_fields(YAML_REQUIRED_FIELDS, &ty.to_lowercase(), &fields),
+                None => Some((String::new(), vec![TYPE])),
+            };
+            self.insert(name, entry, missing);
+        }
     }
 
     fn tex_bib(&mut self, file_id: TypstFileId, bibliography: biblatex::RawBibliography) {
         for entry in bibliography.entries {
+            let fields = entry.v.fields.iter().map(|pair| pair.key.v.to_lowercase());
+            let fields = fields.collect::<Vec<_>>();
+            let ty = entry.v.kind.v.to_lowercase();
+            let missing = missing_fields(TEX_REQUIRED_FIELDS, &ty, &fields);
+
             let name = entry.v.key;
             let entry = BibEntry {
                 file_id,
                 name_range: name.span,
                 range: entry.span,
             };
-            self.info.entries.insert(name.v.to_owned(), entry);
+            self.insert(name.v.to_owned(), entry, missing);
         }
     }
+
+    fn insert(
+        &mut self,
+        name: String,
+        entry: BibEntry,
+        missing: Option<(String, Vec<&'static [&'static str]>)>,
+    ) {
+        if let Some((ty, fields)) = missing {
+            self.info.issues.push(BibIssue {
+                key: name.clone(),
+                entry: entry.clone(),
+                kind: BibIssueKind::MissingFields { ty, fields },
+            });
+        }
+
+        // Keeps the first entry, which is also the one picked by typst.
+        if let Some(first) = self.info.entries.get(&name) {
+            self.info.issues.push(BibIssue {
+                key: name,
+                entry,
+                kind: BibIssueKind::Duplicate(first.clone()),
+            });
+            return;
+        }
+
+        self.info.entries.insert(name, entry);
+    }
+}
+
+/// A list of entry types and the fields they require. Each required field is
+/// a list of alternatives, e.g. either `year` or `date` must be present.
+type RequiredFields = &'static [(&'static [&'static str], &'static [&'static [&'static str]])];
+
+const TYPE: &[&str] = &["type"];
+const AUTHOR: &[&str] = &["author"];
+const AUTHOR_OR_EDITOR: &[&str] = &["author", "editor"];
+const TITLE: &[&str] = &["title"];
+const DATE: &[&str] = &["year", "date"];
+const BOOKTITLE: &[&str] = &["booktitle"];
+const INSTITUTION: &[&str] = &["institution", "school"];
+
+/// The required fields of BibLaTeX entries, following the BibLaTeX manual.
+const TEX_REQUIRED_FIELDS: RequiredFields = &[
+    (
+        &["article"],
+        &[AUTHOR, TITLE, &["journaltitle", "journal"], DATE],
+    ),
+    (&["book", "mvbook"], &[AUTHOR, TITLE, DATE]),
+    (
+        &["inbook", "bookinbook", "suppbook"],
+        &[AUTHOR, TITLE, BOOKTITLE, DATE],
+    ),
+    (
+        &["incollection", "suppcollection"],
+        &[AUTHOR, TITLE, BOOKTITLE, DATE],
+    ),
+    (
+        &["inproceedings", "conference"],
+        &[AUTHOR, TITLE, BOOKTITLE, DATE],
+    ),
+    (
+        &["collection", "mvcollection", "reference", "mvreference"],
+        &[&["editor"], TITLE, DATE],
+    ),
+    (&["proceedings", "mvproceedings"], &[TITLE, DATE]),
+    (
+        &["thesis", "phdthesis", "mastersthesis"],
+        &[AUTHOR, TITLE, INSTITUTION, DATE],
+    ),
+    (
+        &["report", "techreport"],
+        &[AUTHOR, TITLE, INSTITUTION, DATE],
+    ),
+    (
+        &["online", "electronic", "www"],
+        &[AUTHOR_OR_EDITOR, TITLE, DATE, &["url", "doi", "eprint"]],
+    ),
+    (&["patent"], &[AUTHOR, TITLE, &["number"], DATE]),
+    (&["unpublished"], &[AUTHOR, TITLE, DATE]),
+    (&["manual", "misc"], &[AUTHOR_OR_EDITOR, TITLE, DATE]),
+];
+
+/// The required fields of Hayagriva entries, besides the `type` field which is
+/// checked separately.
+const YAML_REQUIRED_FIELDS: RequiredFields =
+    &[(&["article", "book", "thesis", "report"], &[TITLE])];
+
+/// Gets the required fields missing in an entry of the given type.
+fn missing_fields(
+    required: RequiredFields,
+    ty: &str,
+    fields: &[String],
+) -> Option<(String, Vec<&'static [&'static str]>)> {
+    // Inherited fields are not resolved here.
+    if fields.iter().any(|f| f == "crossref" || f == "xdata") {
+        return None;
+    }
+
+    let (_, required) = required.iter().find(|(tys, _)| tys.contains(&ty))?;
+
+    let has_field = |alts: &&[&str]| alts.iter().any(|alt| fields.iter().any(|f| f == alt));
+    let missing = required
+        .iter()
+        .filter(|alts| !has_field(alts))
+        .copied()
+        .collect::<Vec<_>>();
+
+    (!missing.is_empty()).then(|| (ty.to_owned(), missing))
 }
 
 #[derive(Debug, Clone)]
@@ -78,20 +351,31 @@ struct BibSpanned<T> {
     range: Range<usize>,
 }
 
+/// The type and the field names of a yaml entry.
+type YamlFields = (Option<String>, Vec<String>);
+
 #[derive(Default)]
 struct YamlBibLoader {
     depth: usize,
     start: Option<BibSpanned<String>>,
     key: Option<BibSpanned<String>>,
-    content: Vec<(BibSpanned<String>, Range<usize>)>,
+    /// Whether the next scalar in the entry mapping is a field name.
+    expect_field: bool,
+    fields: YamlFields,
+    content: Vec<(BibSpanned<String>, Range<usize>, YamlFields)>,
 }
 
 impl MarkedEventReceiver for YamlBibLoader {
     fn on_event(&mut self, event: Event, mark: Marker) {
         match event {
-            Event::MappingStart(..) => {
-                if self.depth == 1 {
+            Event::MappingStart(..) | Event::SequenceStart(..) => {
+                if self.depth == 1 && matches!(event, Event::MappingStart(..)) {
                     self.start = self.key.take();
+                    self.expect_field = true;
+                    self.fields = YamlFields::default();
+                } else if self.depth == 2 {
+                    // A composite field value, the next scalar is a field name.
+                    self.expect_field = true;
                 }
                 self.depth += 1;
             }
@@ -101,8 +385,23 @@ impl MarkedEventReceiver for YamlBibLoader {
                         value: s.to_owned(),
                         range: mark.index()..mark.index() + s.chars().count(),
                     });
+                } else if self.depth == 2 {
+                    if self.expect_field {
+                        self.fields.1.push(s.to_lowercase());
+                    } else if self.fields.1.last().is_some_and(|f| f == "type") {
+                        self.fields.0 = Some(s.to_owned());
+                    }
+                    self.expect_field = !self.expect_field;
                 }
             }
+            Event::Alias(..) => {
+                if self.depth == 2 {
+                    self.expect_field = !self.expect_field;
+                }
+            }
+            Event::SequenceEnd => {
+                self.depth -= 1;
+            }
             Event::MappingEnd => {
                 self.depth -= 1;
                 if self.depth == 1 {
@@ -112,7 +411,8 @@ impl MarkedEventReceiver for YamlBibLoader {
                         return;
                     };
                     let span = start.range.start..end;
-                    self.content.push((start, span));
+                    let fields = std::mem::take(&mut self.fields);
+                    self.content.push((start, span, fields));
                 }
             }
             _ => {}
@@ -123,6 +423,8 @@ impl MarkedEventReceiver for YamlBibLoader {
 #[derive(Debug)]
 struct YamlBib {
     entries: Vec<(String, BibEntry)>,
+    /// The type and the field names of each entry in `entries`.
+    fields: Vec<YamlFields>,
 }
 
 impl YamlBib {
@@ -135,7 +437,7 @@ impl YamlBib {
         let mut char_offsets = loader
             .content
             .iter()
-            .flat_map(|(name, span)| [name.range.start, name.range.end, span.start, span.end])
+            .flat_map(|(name, span, _)| [name.range.start, name.range.end, span.start, span.end])
             .map(|offset| (offset, None))
             .collect::<Vec<_>>();
         char_offsets.sort_by_key(|(offset, _)| *offset);
@@ -165,7 +467,7 @@ impl YamlBib {
             let end = char_map.get(&range.end).copied()?;
             Some(start..end)
         };
-        let to_entry = |(name, range): (BibSpanned<String>, Range<usize>)| {
+        let to_entry = |(name, range, fields): (BibSpanned<String>, Range<usize>, YamlFields)| {
             let name_range = map_range(name.range)?;
             let range = map_range(range)?;
             let entry = BibEntry {
@@ -173,11 +475,11 @@ impl YamlBib {
                 name_range,
                 range,
             };
-            Some((name.value, entry))
+            Some(((name.value, entry), fields))
         };
 
-        let entries = loader.content.into_iter().filter_map(to_entry).collect();
-        Self { entries }
+        let (entries, fields) = loader.content.into_iter().filter_map(to_entry).unzip();
+        Self { entries, fields }
     }
 }
 
@@ -186,6 +488,7 @@ mod tests {
     use core::fmt;
     use std::path::Path;
 
+    use itertools::Itertools;
     use typst::syntax::{FileId, VirtualPath};
 
     // This is a workaround for slashes in the path on Windows and Linux
@@ -224,4 +527,57 @@ Euclid3
         let file_id = FileId::new_fake(VirtualPath::new(Path::new("test.yml")));
         super::YamlBib::from_content(content, file_id);
     }
+
+    #[test]
+    fn bib_issues() {
+        let tex = r#"@article{a, title = {A}, author = {X}}
+@book{b, title = {B}, author = {Y}, year = {2020}}
+@misc{c, crossref = {b}}
+"#;
+        let yaml = r#"
+b:
+  type: book
+  title: B
+  author: [Y, Z]
+d:
+  title: D
+e:
+  type: article
+  parent:
+    title: E
+"#;
+        let files = ecow::eco_vec![
+            (
+                FileId::new_fake(VirtualPath::new(Path::new("test.bib"))),
+                typst::foundations::Bytes::from_string(tex),
+            ),
+            (
+                FileId::new_fake(VirtualPath::new(Path::new("test.yml"))),
+                typst::foundations::Bytes::from_string(yaml),
+            ),
+        ];
+        let info = super::bib_info(files).unwrap();
+        let keys = info.entries.keys().collect::<Vec<_>>();
+        insta::assert_snapshot!(format!("{keys:?}"), @r###"["a", "b", "c", "d", "e"]"###);
+
+        let issues = info
+            .issues
+            .iter()
+            .map(|issue| match &issue.kind {
+                super::BibIssueKind::Duplicate(first) => {
+                    format!("{}: duplicate of {:?}", issue.key, first.name_range)
+                }
+                super::BibIssueKind::MissingFields { ty, fields } => {
+                    let fields = fields.iter().map(|alts| alts.join("|"));
+                    format!("{}: `{ty}` misses {}", issue.key, fields.format(", "))
+                }
+            })
+            .join("\n");
+        insta::assert_snapshot!(issues, @r###"
+        a: `article` misses journaltitle|journal, year|date
+        b: duplicate of 45..46
+        d: `` misses type
+        e: `article` misses title
+        "###);
+    }
 }
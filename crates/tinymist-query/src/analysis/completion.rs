@@ -28,7 +28,7 @@ use typst_shim::{syntax::LinkedNodeExt, utils::hash128};
 use unscanny::Scanner;
 
 use crate::adt::interner::Interned;
-use crate::analysis::{BuiltinTy, LocalContext, PathPreference, Ty};
+use crate::analysis::{find_bib, is_sys_inputs_at, BuiltinTy, LocalContext, PathPreference, Ty};
 use crate::completion::{
     Completion, CompletionCommand, CompletionContextKey, CompletionItem, CompletionKind,
     EcoTextEdit, ParsedSnippet, PostfixSnippet, PostfixSnippetScope, PrefixSnippet,
@@ -37,16 +37,17 @@ use crate::completion::{
 use crate::prelude::*;
 use crate::syntax::{
     classify_context, interpret_mode_at, is_ident_like, node_ancestors, previous_decls,
-    surrounding_syntax, InterpretMode, PreviousDecl, SurroundingSyntax, SyntaxClass, SyntaxContext,
-    VarClass,
+    surrounding_syntax, ArgClass, InterpretMode, PreviousDecl, SurroundingSyntax, SyntaxClass,
+    SyntaxContext, VarClass,
 };
 use crate::ty::{
     DynTypeBounds, Iface, IfaceChecker, InsTy, SigTy, TyCtx, TypeInfo, TypeInterface, TypeVar,
 };
-use crate::upstream::{plain_docs_sentence, summarize_font_family};
+use crate::upstream::{plain_docs_sentence, summarize_font_family, truncated_repr};
 
 use super::SharedContext;
 
+mod auto_import;
 mod field_access;
 mod func;
 mod import;
@@ -93,6 +94,8 @@ pub struct CompletionFeat {
     pub postfix_ufcs_right: Option<bool>,
     /// Postfix snippets.
     pub postfix_snippets: Option<EcoVec<PostfixSnippet>>,
+    /// Whether to enable completion of definitions that are not imported yet.
+    pub auto_import: Option<bool>,
 }
 
 impl CompletionFeat {
@@ -121,6 +124,11 @@ impl CompletionFeat {
         self.postfix() && self.postfix_ufcs_right.unwrap_or(true)
     }
 
+    /// Whether to enable auto-import completion.
+    pub(crate) fn auto_import(&self) -> bool {
+        self.auto_import.unwrap_or(true)
+    }
+
     /// Gets the postfix snippets.
     pub(crate) fn postfix_snippets(&self) -> &EcoVec<PostfixSnippet> {
         self.postfix_snippets
@@ -506,20 +514,8 @@ impl<'a> CompletionWorker<'a> {
         if let Some(SelectedNode::Ident(from_ident)) = cursor.selected_node() {
             let ident_prefix = cursor.text[from_ident.offset()..cursor.cursor].to_string();
 
-            self.completions.retain(|item| {
-                let mut prefix_matcher = item.label.chars();
-                'ident_matching: for ch in ident_prefix.chars() {
-                    for item in prefix_matcher.by_ref() {
-                        if item == ch {
-                            continue 'ident_matching;
-                        }
-                    }
-
-                    return false;
-                }
-
-                true
-            });
+            self.completions
+                .retain(|item| is_fuzzy_match(&item.label, &ident_prefix));
         }
 
         for item in &mut self.completions {
@@ -580,7 +576,29 @@ impl CompletionPair<'_, '_, '_> {
                     }
                 };
             }
-            Some(SyntaxContext::Arg { args, .. }) => {
+            Some(SyntaxContext::Arg {
+                callee,
+                args,
+                target,
+                ..
+            }) => {
+                // The keys of `sys.inputs` read by `sys.inputs.at(..)`
+                let is_key = matches!(
+                    target,
+                    ArgClass::Positional {
+                        positional: 0,
+                        is_spread: false,
+                        ..
+                    }
+                );
+                if is_key && callee.cast().is_some_and(is_sys_inputs_at) {
+                    if self.cursor.leaf.kind() == SyntaxKind::Str {
+                        self.cursor.from = self.cursor.leaf.offset();
+                    }
+                    self.sys_input_completions();
+                    return Some(());
+                }
+
                 // The existing arguments are not interesting
                 let args = args.cast::<ast::Args>()?;
                 for arg in args.items() {
@@ -829,6 +847,12 @@ impl CompletionPair<'_, '_, '_> {
     }
 }
 
+/// Whether the label contains the characters of the typed prefix in order.
+fn is_fuzzy_match(label: &str, prefix: &str) -> bool {
+    let mut label = label.chars();
+    prefix.chars().all(|ch| label.any(|item| item == ch))
+}
+
 /// If is printable, return the symbol itself.
 /// Otherwise, return the symbol's unicode detailed description.
 pub fn symbol_detail(ch: char) -> EcoString {
@@ -0,0 +1,299 @@
+//! Completion of definitions that are not imported yet.
+
+use crate::syntax::{resolve_id_by_path, Expr};
+
+use super::*;
+
+/// The maximum number of unimported definitions suggested in a completion.
+const MAX_AUTO_IMPORT_ITEMS: usize = 64;
+
+/// A module that can be imported by the current file.
+struct ImportCandidate {
+    /// The file id of the module.
+    fid: TypstFileId,
+    /// The import path of the module, e.g. `"utils.typ"` or
+    /// `"@preview/example:0.1.0"`.
+    path: EcoString,
+    /// Whether the module is a package.
+    is_package: bool,
+}
+
+impl CompletionPair<'_, '_, '_> {
+    /// Add completions for definitions exported by other workspace modules and
+    /// used packages, which are imported on selection.
+    pub fn auto_import_completions(&mut self, in_scope: &HashSet<EcoString>, parens: bool) {
+        if !self.worker.ctx.analysis.completion_feat.auto_import()
+            || !matches!(self.cursor.surrounding_syntax, SurroundingSyntax::Regular)
+            || matches!(self.cursor.leaf_mode(), InterpretMode::Math)
+        {
+            return;
+        }
+
+        // Only suggests the unimported definitions when some characters are typed,
+        // since there could be many of them.
+        let Some(SelectedNode::Ident(ident)) = self.cursor.selected_node() else {
+            return;
+        };
+        let prefix = self.cursor.text[ident.offset()..self.cursor.cursor].to_owned();
+        if prefix.is_empty() {
+            return;
+        }
+
+        let mut budget = MAX_AUTO_IMPORT_ITEMS;
+        for candidate in self.import_candidates() {
+            if budget == 0 {
+                break;
+            }
+            let Some(exprs) = self.worker.ctx.expr_stage_by_id(candidate.fid) else {
+                continue;
+            };
+
+            // Filters the exported names first, so that only the modules having
+            // matched names are type checked.
+            let mut keys = vec![];
+            for (key, expr) in exprs.exports.iter() {
+                let name: EcoString = key.as_ref().into();
+                if in_scope.contains(&name) || !is_fuzzy_match(&name, &prefix) {
+                    continue;
+                }
+                // The definitions imported by a workspace module are suggested from
+                // the module defining them.
+                if !candidate.is_package && !is_own_def(expr, candidate.fid) {
+                    continue;
+                }
+
+                keys.push((key, name));
+                budget -= 1;
+                if budget == 0 {
+                    break;
+                }
+            }
+            if keys.is_empty() {
+                continue;
+            }
+
+            let types = self.worker.ctx.type_check_by_id(candidate.fid);
+            let mut names = vec![];
+            for (key, name) in keys {
+                let ty = types.exports.get(key).cloned();
+                let ty = ty.map(|ty| types.simplify(ty, false)).unwrap_or(Ty::Any);
+                names.push((name, ty));
+            }
+
+            let current = self.cursor.source.id();
+            for (name, ty) in names {
+                let world = self.worker.world();
+                let is_candidate =
+                    |path: &str| resolve_id_by_path(world, current, path) == Some(candidate.fid);
+                let names = std::slice::from_ref(&name);
+                let Some((range, text)) =
+                    import_edit(&self.cursor.source, is_candidate, &candidate.path, names)
+                else {
+                    continue;
+                };
+                let edit = EcoTextEdit::new(self.cursor.lsp_range_of(range), text);
+                let detail = eco_format!(" (import {})", candidate.path);
+
+                let start = self.worker.completions.len();
+                self.def_completions(
+                    Defines {
+                        types: types.clone(),
+                        defines: BTreeMap::from([(name, ty)]),
+                        docs: Default::default(),
+                    },
+                    parens,
+                );
+                for item in &mut self.worker.completions[start..] {
+                    item.additional_text_edits = Some(vec![edit.clone()]);
+                    let label_details = item.label_details.get_or_insert_with(Default::default);
+                    label_details.detail = Some(detail.clone());
+                }
+            }
+        }
+    }
+
+    /// Gets the workspace modules and the used packages that can be imported by
+    /// the current file.
+    fn import_candidates(&mut self) -> Vec<ImportCandidate> {
+        let ctx = &self.worker.ctx;
+        let current = self.cursor.source.id();
+
+        let mut files = ctx.source_files().clone();
+        let mut packages = vec![];
+        for fid in ctx.depended_files() {
+            match fid.package() {
+                Some(spec) if Some(spec) != current.package() && !packages.contains(spec) => {
+                    packages.push(spec.clone());
+                }
+                Some(..) => {}
+                None if !files.contains(&fid) => files.push(fid),
+                None => {}
+            }
+        }
+
+        let current_dir = current.vpath().as_rooted_path().parent();
+        let mut candidates = vec![];
+        for fid in files {
+            let ext = fid.vpath().as_rooted_path().extension();
+            let is_source = ext.is_some_and(|ext| ext == "typ");
+            if fid == current || fid.package() != current.package() || !is_source {
+                continue;
+            }
+
+            let path = fid.vpath().as_rooted_path();
+            let path = match current_dir.and_then(|dir| tinymist_std::path::diff(path, dir)) {
+                Some(path) => unix_slash(&path),
+                None => unix_slash(path),
+            };
+            candidates.push(ImportCandidate {
+                fid,
+                path: eco_format!("\"{path}\""),
+                is_package: false,
+            });
+        }
+        for spec in packages {
+            let path = eco_format!("\"{spec}\"");
+            let Some(fid) = resolve_id_by_path(ctx.world(), current, &spec.to_string()) else {
+                continue;
+            };
+            candidates.push(ImportCandidate {
+                fid,
+                path,
+                is_package: true,
+            });
+        }
+
+        candidates
+    }
+}
+
+/// Whether the exported expression is defined in the module.
+fn is_own_def(expr: &Expr, fid: TypstFileId) -> bool {
+    matches!(expr, Expr::Decl(decl)
+        if decl.file_id() == Some(fid)
+            && matches!(decl.as_ref(), Decl::Func(..) | Decl::Var(..) | Decl::Closure(..)))
+}
+
+/// Computes the edit importing the names from the module. The names are merged
+/// into an existing import of the module if possible, otherwise a new import
+/// is added after the last top-level import. Returns `None` if all the names
+/// are imported from the module already.
+pub(super) fn import_edit(
+    source: &Source,
+    is_candidate: impl Fn(&str) -> bool,
+    path: &str,
+    names: &[EcoString],
+) -> Option<(Range<usize>, EcoString)> {
+    let root = LinkedNode::new(source.root());
+
+    let mut last_import = None;
+    let mut merge_into = None;
+    let mut imported = HashSet::new();
+    for child in root.children() {
+        let Some(ast::Expr::Import(import)) = child.get().cast() else {
+            continue;
+        };
+        last_import = Some(child.range());
+
+        let is_candidate = match import.source() {
+            ast::Expr::Str(path) => is_candidate(&path.get()),
+            _ => false,
+        };
+        if !is_candidate || import.new_name().is_some() {
+            continue;
+        }
+
+        let items = match import.imports() {
+            Some(ast::Imports::Wildcard) => return None,
+            Some(ast::Imports::Items(items)) => items,
+            None => continue,
+        };
+        for item in items.iter() {
+            imported.insert(item.bound_name().get().clone());
+        }
+
+        let items = child
+            .children()
+            .find(|n| n.kind() == SyntaxKind::ImportItems);
+        if let Some(items) = items.filter(|_| merge_into.is_none()) {
+            let last = items.children().rev().find(|n| !n.kind().is_trivia());
+            let trailing_comma = matches!(last.map(|n| n.kind()), Some(SyntaxKind::Comma));
+            merge_into = Some((items.range().end, trailing_comma));
+        }
+    }
+
+    let names = names
+        .iter()
+        .filter(|name| !imported.contains(*name))
+        .map(EcoString::as_str)
+        .collect::<Vec<_>>();
+    if names.is_empty() {
+        return None;
+    }
+    let names = names.join(", ");
+
+    if let Some((end, trailing_comma)) = merge_into {
+        let text = match trailing_comma {
+            true => eco_format!(" {names}"),
+            false => eco_format!(", {names}"),
+        };
+        return Some((end..end, text));
+    }
+
+    let import = eco_format!("#import {path}: {names}");
+    Some(match last_import {
+        Some(range) => (range.end..range.end, eco_format!("\n{import}")),
+        None => (0..0, eco_format!("{import}\n")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(text: &str, path: &str, name: &str) -> String {
+        let source = Source::detached(text);
+        let is_candidate = |import: &str| import == path.trim_matches('"');
+
+        let names = name.split(", ").map(EcoString::from).collect::<Vec<_>>();
+        let Some((range, new_text)) = import_edit(&source, is_candidate, path, &names) else {
+            return text.to_owned();
+        };
+        let mut text = text.to_owned();
+        text.replace_range(range, &new_text);
+        text
+    }
+
+    #[test]
+    fn test_import_edit() {
+        assert_eq!(edit("= A", "\"a.typ\"", "x"), "#import \"a.typ\": x\n= A");
+        assert_eq!(
+            edit("#import \"b.typ\": y\n= A", "\"a.typ\"", "x"),
+            "#import \"b.typ\": y\n#import \"a.typ\": x\n= A"
+        );
+        assert_eq!(
+            edit("#import \"a.typ\": y\n= A", "\"a.typ\"", "x"),
+            "#import \"a.typ\": y, x\n= A"
+        );
+        assert_eq!(
+            edit("#import \"a.typ\": (y,)\n= A", "\"a.typ\"", "x"),
+            "#import \"a.typ\": (y, x)\n= A"
+        );
+        assert_eq!(
+            edit("#import \"a.typ\" as a\n= A", "\"a.typ\"", "x"),
+            "#import \"a.typ\" as a\n#import \"a.typ\": x\n= A"
+        );
+        assert_eq!(
+            edit("#import \"a.typ\": x\n= A", "\"a.typ\"", "x, y"),
+            "#import \"a.typ\": x, y\n= A"
+        );
+        assert_eq!(
+            edit("#import \"a.typ\": x\n= A", "\"a.typ\"", "x"),
+            "#import \"a.typ\": x\n= A"
+        );
+        assert_eq!(
+            edit("#import \"a.typ\": *\n= A", "\"a.typ\"", "x"),
+            "#import \"a.typ\": *\n= A"
+        );
+    }
+}
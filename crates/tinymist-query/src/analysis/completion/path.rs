@@ -62,6 +62,9 @@ impl CompletionPair<'_, '_, '_> {
         // todo: test it correctly
         for path in self.worker.ctx.completion_files(preference) {
             crate::log_debug_ct!("compl_check_path: {path:?}");
+            if self.worker.ctx.is_cancelled() {
+                return None;
+            }
 
             // Skip self smartly
             if *path == base {
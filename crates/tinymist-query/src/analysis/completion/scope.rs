@@ -47,7 +47,9 @@ impl CompletionPair<'_, '_, '_> {
             return;
         };
 
+        let in_scope = defines.defines.keys().cloned().collect();
         self.def_completions(defines, parens);
+        self.auto_import_completions(&in_scope, parens);
     }
 
     pub fn scope_defs(&mut self) -> Option<Defines> {
@@ -6,7 +6,11 @@
 //! A postfix snippet is a snippet that modifies existing items by the dot
 //! accessor syntax. For example `$ RR.abs| $` is completed as `$ abs(RR) $`.
 
+use tinymist_world::EntryReader;
+
+use super::auto_import::import_edit;
 use super::*;
+use crate::syntax::resolve_id_by_path;
 
 impl CompletionPair<'_, '_, '_> {
     /// Add a (prefix) snippet completion.
@@ -44,25 +48,71 @@ impl CompletionPair<'_, '_, '_> {
         let applies_to = |snippet: &PrefixSnippet| keys.iter().any(|key| snippet.applies_to(key));
 
         for snippet in DEFAULT_PREFIX_SNIPPET.iter() {
-            if !applies_to(snippet) {
-                continue;
+            if applies_to(snippet) {
+                self.prefix_snippet_completion(snippet);
+            }
+        }
+
+        for library in self.snippet_libraries() {
+            for snippet in library.iter() {
+                if applies_to(snippet) {
+                    self.prefix_snippet_completion(snippet);
+                }
+            }
+        }
+    }
+
+    /// Gets the snippets loaded from the snippet files.
+    fn snippet_libraries(&self) -> Vec<Arc<Vec<PrefixSnippet>>> {
+        let analysis = &self.worker.ctx.analysis;
+        let root = self.worker.world().entry_state().workspace_root();
+
+        let files = analysis.snippet_files.iter().filter_map(|path| {
+            if path.is_absolute() {
+                Some(path.clone())
+            } else {
+                Some(root.as_ref()?.join(path))
             }
+        });
+        files
+            .map(|path| analysis.snippets.snippets(&path))
+            .collect()
+    }
+
+    fn prefix_snippet_completion(&mut self, snippet: &PrefixSnippet) {
+        let analysis = &self.worker.ctx.analysis;
+        let command = match snippet.command {
+            Some(CompletionCommand::TriggerSuggest) => analysis.trigger_suggest(true),
+            None => analysis.trigger_on_snippet(snippet.snippet.contains("${")),
+        };
 
-            let analysis = &self.worker.ctx.analysis;
-            let command = match snippet.command {
-                Some(CompletionCommand::TriggerSuggest) => analysis.trigger_suggest(true),
-                None => analysis.trigger_on_snippet(snippet.snippet.contains("${")),
+        let mut edits = vec![];
+        for import in &snippet.imports {
+            let world = self.worker.world();
+            let current = self.cursor.source.id();
+            let target = resolve_id_by_path(world, current, &import.path);
+            let is_candidate = |path: &str| match target {
+                Some(target) => resolve_id_by_path(world, current, path) == Some(target),
+                None => path == import.path,
             };
 
-            self.push_completion(Completion {
-                kind: CompletionKind::Syntax,
-                label: snippet.label.as_ref().into(),
-                apply: Some(snippet.snippet.as_ref().into()),
-                detail: Some(snippet.description.as_ref().into()),
-                command: command.map(From::from),
-                ..Completion::default()
-            });
+            let path = eco_format!("\"{}\"", import.path);
+            let edit = import_edit(&self.cursor.source, is_candidate, &path, &import.items);
+            if let Some((range, text)) = edit {
+                edits.push(EcoTextEdit::new(self.cursor.lsp_range_of(range), text));
+            }
         }
+
+        self.push_completion(Completion {
+            kind: CompletionKind::Syntax,
+            label: snippet.label.as_ref().into(),
+            label_details: snippet.label_detail.clone(),
+            apply: Some(snippet.snippet.as_ref().into()),
+            detail: Some(snippet.description.as_ref().into()),
+            command: command.map(From::from),
+            additional_text_edits: (!edits.is_empty()).then_some(edits),
+            ..Completion::default()
+        });
     }
 
     pub fn postfix_completions(&mut self, node: &LinkedNode, ty: Ty) -> Option<()> {
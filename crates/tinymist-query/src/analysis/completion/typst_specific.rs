@@ -24,6 +24,16 @@ impl CompletionPair<'_, '_, '_> {
         // todo: add me
     }
 
+    /// Add completions for the keys of `sys.inputs`, with their current values
+    /// as details.
+    pub fn sys_input_completions(&mut self) {
+        let inputs = self.worker.world().inputs();
+        for (key, value) in inputs.iter() {
+            let detail = truncated_repr(value);
+            self.value_completion(None, &Value::Str(key.clone()), false, Some(detail.as_str()));
+        }
+    }
+
     /// Add completions for all available packages.
     pub fn package_completions(&mut self, all_versions: bool) {
         let w = self.worker.world().clone();
@@ -93,6 +103,7 @@ impl CompletionPair<'_, '_, '_> {
             return;
         };
         let (labels, split) = analyze_labels(document);
+        let bib_info = find_bib(self.worker.world(), document.introspector()).map(|(_, info)| info);
 
         let head = &self.cursor.text[..self.cursor.from];
         let at = head.ends_with('@');
@@ -111,7 +122,7 @@ impl CompletionPair<'_, '_, '_> {
         for DynLabel {
             label,
             label_desc,
-            detail,
+            mut detail,
             bib_title,
         } in labels.into_iter().skip(skip).take(take)
         {
@@ -119,6 +130,20 @@ impl CompletionPair<'_, '_, '_> {
                 continue;
             }
             let label: EcoString = label.resolve().as_str().into();
+
+            // Searches bibliography entries by their authors as well, e.g. `@smith`.
+            let mut authors = EcoString::new();
+            if let Some(summary) = bib_title
+                .as_ref()
+                .and_then(|_| bib_info.as_ref()?.summary(&label))
+            {
+                for author in &summary.authors {
+                    authors.push(' ');
+                    authors.push_str(author);
+                }
+                detail = Some(summary.to_string().into());
+            }
+
             let completion = Completion {
                 kind: CompletionKind::Reference,
                 apply: Some(eco_format!(
@@ -129,7 +154,7 @@ impl CompletionPair<'_, '_, '_> {
                 )),
                 label: label.clone(),
                 label_details: label_desc.clone(),
-                filter_text: Some(label.clone()),
+                filter_text: Some(eco_format!("{label}{authors}")),
                 detail: detail.clone(),
                 ..Completion::default()
             };
@@ -141,7 +166,7 @@ impl CompletionPair<'_, '_, '_> {
                     kind: CompletionKind::Constant,
                     label: bib_title.clone(),
                     label_details: Some(label),
-                    filter_text: Some(bib_title),
+                    filter_text: Some(eco_format!("{bib_title}{authors}")),
                     detail,
                     ..completion.clone()
                 });
@@ -1,11 +1,10 @@
 //! Linked definition analysis
 
 use tinymist_std::typst::TypstDocument;
-use typst::foundations::{IntoValue, Label, Selector, Type};
+use typst::foundations::{Label, Selector, Type};
 use typst::introspection::Introspector;
-use typst::model::BibliographyElem;
 
-use super::{prelude::*, InsTy, SharedContext};
+use super::{find_bib, prelude::*, BibEntry, InsTy, SharedContext};
 use crate::syntax::{Decl, DeclExpr, Expr, ExprInfo, SyntaxClass, VarClass};
 use crate::ty::DocSource;
 
@@ -169,17 +168,39 @@ fn bib_definition(
     introspector: &Introspector,
     key: &str,
 ) -> Option<Definition> {
-    let bib_elem = BibliographyElem::find(introspector.track()).ok()?;
-    let Value::Array(paths) = bib_elem.sources.clone().into_value() else {
-        return None;
-    };
-
-    let bib_paths = paths.into_iter().flat_map(|path| path.cast().ok());
-    let bib_info = ctx.analyze_bib(bib_elem.span(), bib_paths)?;
+    let (_, bib_info) = find_bib(&ctx.world, introspector)?;
 
     let entry = bib_info.entries.get(key)?;
     crate::log_debug_ct!("find_bib_definition: {key} => {entry:?}");
 
+    Some(bib_entry_definition(key, entry))
+}
+
+/// Finds the definition of the bibliography entry whose key is under the
+/// cursor, if the source is a bibliography file of the document.
+pub(crate) fn bib_entry_at(
+    ctx: &Arc<SharedContext>,
+    source: &Source,
+    document: Option<&TypstDocument>,
+    cursor: usize,
+) -> Option<Definition> {
+    let fid = source.id();
+    let ext = fid.vpath().as_rooted_path().extension()?.to_str()?;
+    if !matches!(ext.to_lowercase().as_str(), "yaml" | "yml" | "bib") {
+        return None;
+    }
+
+    let (_, bib_info) = find_bib(&ctx.world, document?.introspector())?;
+    let (key, entry) = bib_info.entries.iter().find(|(_, entry)| {
+        let range = &entry.name_range;
+        entry.file_id == fid && range.start <= cursor && cursor <= range.end
+    })?;
+    crate::log_debug_ct!("find_bib_entry_at: {cursor} => {key}");
+
+    Some(bib_entry_definition(key, entry))
+}
+
+fn bib_entry_definition(key: &str, entry: &BibEntry) -> Definition {
     // todo: rename with regard to string format: yaml-key/bib etc.
     let decl = Decl::bib_entry(
         key.into(),
@@ -187,7 +208,7 @@ fn bib_definition(
         entry.name_range.clone(),
         Some(entry.range.clone()),
     );
-    Some(Definition::new(decl.into(), None))
+    Definition::new(decl.into(), None)
 }
 
 fn ref_definition(
@@ -47,6 +47,76 @@ impl<'a> DocumentHighlightWorker<'a> {
         }
     }
 
+    /// Starts to work for linked editing, which finds the occurrences to edit
+    /// together with the node, i.e. a label and its references, a definition
+    /// and its local uses, or the pair of dollars of an equation.
+    pub fn work_linked(&mut self, node: &'a LinkedNode<'a>) -> Option<()> {
+        match node.kind() {
+            SyntaxKind::Label => {
+                let name = node.cast::<ast::Label>()?.get();
+                self.work_label(name)
+            }
+            SyntaxKind::RefMarker => {
+                let name = node.parent()?.cast::<ast::Ref>()?.target();
+                self.work_label(name)
+            }
+            SyntaxKind::Ident | SyntaxKind::MathIdent => self.work_ident(node),
+            SyntaxKind::Dollar => self.work_dollars(node),
+            _ => None,
+        }
+    }
+
+    fn work_label(&mut self, name: &str) -> Option<()> {
+        self.worklist.push(LinkedNode::new(self.source.root()));
+        self.check(|this, node| this.check_label(node, name));
+        self.annotated.sort_by_key(|it| it.range.start);
+        Some(())
+    }
+
+    fn work_ident(&mut self, node: &'a LinkedNode<'a>) -> Option<()> {
+        let syntax = classify_syntax(node.clone(), node.offset())?;
+        let def = self.ctx.def_of_syntax(self.source, None, syntax)?;
+        // Only the local definitions are linked, whose uses are all known.
+        let is_local = def.decl.file_id() == Some(self.source.id());
+        if !is_local || !matches!(def.decl.as_ref(), Decl::Func(..) | Decl::Var(..)) {
+            return None;
+        }
+
+        let ei = self.ctx.expr_stage(self.source);
+        let refs = ei.get_refs(def.decl.clone()).map(|(span, _)| *span);
+        let mut ranges = std::iter::once(def.decl.span())
+            .chain(refs)
+            .filter_map(|span| self.source.range(span))
+            .filter(|range| &self.source.text()[range.clone()] == def.decl.name().as_ref())
+            .collect::<Vec<_>>();
+        ranges.sort_by_key(|range| range.start);
+        ranges.dedup();
+
+        for range in ranges {
+            self.annotate_range(range);
+        }
+        Some(())
+    }
+
+    fn work_dollars(&mut self, node: &'a LinkedNode<'a>) -> Option<()> {
+        let parent = node.parent()?;
+        if parent.kind() != SyntaxKind::Equation {
+            return None;
+        }
+
+        // Both dollars must exist, which is not the case in an unclosed equation.
+        let first = parent.children().next()?;
+        let last = parent.children().next_back()?;
+        let is_dollar = |node: &LinkedNode| node.kind() == SyntaxKind::Dollar;
+        if !is_dollar(&first) || !is_dollar(&last) || first.offset() == last.offset() {
+            return None;
+        }
+
+        self.annotate_range(first.range());
+        self.annotate_range(last.range());
+        Some(())
+    }
+
     fn work_loop(&mut self, node: &'a LinkedNode<'a>) -> Option<()> {
         let _ = self.ctx;
 
@@ -89,6 +159,11 @@ impl<'a> DocumentHighlightWorker<'a> {
             rng.start -= 1;
         }
 
+        self.annotate_range(rng);
+    }
+
+    /// Annotate the range for highlight
+    fn annotate_range(&mut self, rng: Range<usize>) {
         self.annotated.push(DocumentHighlight {
             range: self.ctx.to_lsp_range(rng, self.source),
             kind: None,
@@ -116,6 +191,26 @@ impl<'a> DocumentHighlightWorker<'a> {
         }
     }
 
+    fn check_label(&mut self, node: LinkedNode<'a>, name: &str) {
+        let rng = node.range();
+        match node.kind() {
+            // The name of a label, i.e. `<name>`.
+            SyntaxKind::Label if node.cast::<ast::Label>().is_some_and(|l| l.get() == name) => {
+                self.annotate_range(rng.start + 1..rng.end - 1);
+            }
+            // The name of a reference, i.e. `@name`.
+            SyntaxKind::RefMarker
+                if node
+                    .parent()
+                    .and_then(|p| p.cast::<ast::Ref>())
+                    .is_some_and(|r| r.target() == name) =>
+            {
+                self.annotate_range(rng.start + 1..rng.end);
+            }
+            _ => self.check_children(&node),
+        }
+    }
+
     fn check_loop(&mut self, node: LinkedNode<'a>) {
         match node.kind() {
             SyntaxKind::ForLoop
@@ -0,0 +1,263 @@
+//! Persistent on-disk index of the documentation of package definitions.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use ecow::EcoString;
+use parking_lot::Mutex;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use typst::syntax::package::PackageSpec;
+
+use crate::docs::DefDocs;
+
+/// The on-disk documentation index, which keeps the rendered documentation
+/// of the definitions in packages across restarts, as shown on hover. It is
+/// not an index of the analysis results: the exported names, signatures and
+/// types used by completion and signature help are not persisted, and are
+/// still computed on demand.
+///
+/// The index is stored per package and loaded lazily on first access. Each
+/// file in the index is keyed by its content hash, so entries of a changed
+/// file are invalidated.
+///
+/// Only non-local packages are indexed, since the documentation of a
+/// definition also depends on the files it imports, which are only immutable
+/// in published packages.
+pub struct DocsIndex {
+    /// The directory to store the index.
+    dir: PathBuf,
+    /// The loaded packages.
+    packages: Mutex<FxHashMap<PackageSpec, Arc<Mutex<PackageIndex>>>>,
+    /// Whether a background flush is scheduled or running.
+    flushing: AtomicBool,
+    /// The time of the last flush.
+    last_flush: Mutex<Option<Instant>>,
+}
+
+/// The key to a definition in the index.
+#[derive(Debug, Clone)]
+pub struct DocsIndexKey {
+    /// The package containing the definition.
+    pub spec: PackageSpec,
+    /// The path to the file in the package.
+    pub path: EcoString,
+    /// The content hash of the file.
+    pub hash: u128,
+    /// The identifier of the definition in the file.
+    pub def: EcoString,
+}
+
+impl DocsIndexKey {
+    /// Whether the package of the definition is indexed.
+    pub fn is_indexed(spec: &PackageSpec) -> bool {
+        spec.namespace != "local"
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct PackageIndex {
+    #[serde(skip)]
+    dirty: bool,
+    files: BTreeMap<EcoString, FileIndex>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FileIndex {
+    hash: String,
+    defs: BTreeMap<EcoString, DefDocs>,
+}
+
+impl DocsIndex {
+    /// The minimum interval between two background flushes.
+    pub const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+    /// Creates an index stored in the directory.
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            packages: Mutex::default(),
+            flushing: AtomicBool::new(false),
+            last_flush: Mutex::new(None),
+        }
+    }
+
+    /// Creates an index stored in the cache directory of the system. The index
+    /// is versioned with the analyzer, since the rendered documentation may
+    /// change across versions.
+    pub fn in_cache_dir() -> Option<Self> {
+        let dir = dirs::cache_dir()?.join("tinymist").join("docs-index");
+        Some(Self::new(dir.join(env!("CARGO_PKG_VERSION"))))
+    }
+
+    /// Gets the documentation of a definition from the index.
+    pub fn def_docs(&self, key: &DocsIndexKey) -> Option<DefDocs> {
+        let package = self.package(&key.spec);
+        let package = package.lock();
+        let file = package.files.get(&key.path)?;
+        if file.hash != hash_repr(key.hash) {
+            return None;
+        }
+
+        file.defs.get(&key.def).cloned()
+    }
+
+    /// Inserts the documentation of a definition into the index. The entries
+    /// of the file are discarded if the file has changed.
+    pub fn insert_def_docs(&self, key: &DocsIndexKey, docs: DefDocs) {
+        let package = self.package(&key.spec);
+        let mut package = package.lock();
+
+        let hash = hash_repr(key.hash);
+        let file = package
+            .files
+            .entry(key.path.clone())
+            .or_insert_with(|| FileIndex {
+                hash: hash.clone(),
+                defs: BTreeMap::new(),
+            });
+        if file.hash != hash {
+            file.hash = hash;
+            file.defs.clear();
+        }
+
+        file.defs.insert(key.def.clone(), docs);
+        package.dirty = true;
+    }
+
+    /// Writes the changed packages to the disk in a background thread.
+    ///
+    /// At most one flush is scheduled at a time, which waits for
+    /// [`Self::FLUSH_INTERVAL`] after the last flush, since the index changes
+    /// on almost every analysis of a package.
+    pub fn schedule_flush(self: &Arc<Self>) {
+        if !self.is_dirty() || self.flushing.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let index = self.clone();
+        std::thread::spawn(move || {
+            let elapsed = index.last_flush.lock().map(|time| time.elapsed());
+            if let Some(wait) = elapsed.and_then(|e| Self::FLUSH_INTERVAL.checked_sub(e)) {
+                std::thread::sleep(wait);
+            }
+
+            index.flush();
+            index.flushing.store(false, Ordering::SeqCst);
+        });
+    }
+
+    /// Writes the changed packages to the disk.
+    pub fn flush(&self) {
+        *self.last_flush.lock() = Some(Instant::now());
+        let packages = self.packages.lock().clone();
+        for (spec, package) in packages {
+            let mut package = package.lock();
+            if !package.dirty {
+                continue;
+            }
+
+            let path = self.package_path(&spec);
+            let res = serde_json::to_vec(&*package)
+                .map_err(|err| err.to_string())
+                .and_then(|data| write_index(&path, &data));
+            if let Err(err) = res {
+                log::warn!("failed to write documentation index of {spec} to {path:?}: {err}");
+            }
+
+            package.dirty = false;
+        }
+    }
+
+    /// Whether some packages are changed but not written to the disk.
+    pub fn is_dirty(&self) -> bool {
+        let packages = self.packages.lock();
+        packages.values().any(|package| package.lock().dirty)
+    }
+
+    fn package(&self, spec: &PackageSpec) -> Arc<Mutex<PackageIndex>> {
+        let mut packages = self.packages.lock();
+        if let Some(package) = packages.get(spec) {
+            return package.clone();
+        }
+
+        let path = self.package_path(spec);
+        let package = match std::fs::read(&path) {
+            Ok(data) => serde_json::from_slice(&data).unwrap_or_else(|err| {
+                log::warn!("discarding corrupted documentation index {path:?}: {err}");
+                PackageIndex::default()
+            }),
+            Err(_) => PackageIndex::default(),
+        };
+
+        let package = Arc::new(Mutex::new(package));
+        packages.insert(spec.clone(), package.clone());
+        package
+    }
+
+    fn package_path(&self, spec: &PackageSpec) -> PathBuf {
+        self.dir
+            .join(spec.namespace.as_str())
+            .join(spec.name.as_str())
+            .join(format!("{}.json", spec.version))
+    }
+}
+
+fn hash_repr(hash: u128) -> String {
+    format!("{hash:032x}")
+}
+
+fn write_index(path: &Path, data: &[u8]) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    }
+    tinymist_std::fs::paths::write_atomic(path, data).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn key(hash: u128, def: &str) -> DocsIndexKey {
+        DocsIndexKey {
+            spec: PackageSpec::from_str("@preview/example:0.1.0").unwrap(),
+            path: "/lib.typ".into(),
+            hash,
+            def: def.into(),
+        }
+    }
+
+    fn docs(docs: &str) -> DefDocs {
+        DefDocs::Plain { docs: docs.into() }
+    }
+
+    #[test]
+    fn test_persist_and_invalidate() {
+        let dir = std::env::temp_dir().join(format!("tinymist-docs-index-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let index = DocsIndex::new(dir.clone());
+        assert!(index.def_docs(&key(1, "f@0")).is_none());
+        index.insert_def_docs(&key(1, "f@0"), docs("f"));
+        index.insert_def_docs(&key(1, "g@8"), docs("g"));
+        assert!(index.is_dirty());
+        index.flush();
+        assert!(!index.is_dirty());
+
+        // Loads the index lazily from the disk.
+        let index = DocsIndex::new(dir.clone());
+        let f = index.def_docs(&key(1, "f@0"));
+        assert_eq!(f.map(|f| f.docs().clone()), Some("f".into()));
+        // The entries are invalidated by the content hash.
+        assert!(index.def_docs(&key(2, "f@0")).is_none());
+        index.insert_def_docs(&key(2, "f@0"), docs("f2"));
+        assert!(index.def_docs(&key(2, "g@8")).is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
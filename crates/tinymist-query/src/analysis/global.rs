@@ -1,5 +1,6 @@
 use std::num::NonZeroUsize;
 use std::ops::DerefMut;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::OnceLock;
 use std::{collections::HashSet, ops::Deref};
@@ -17,6 +18,7 @@ use tinymist_std::typst::TypstDocument;
 use tinymist_world::debug_loc::DataSource;
 use tinymist_world::vfs::{FileId, PathResolution, WorkspaceResolver};
 use tinymist_world::{EntryReader, DETACHED_ENTRY};
+use tokio_util::sync::CancellationToken;
 use typst::diag::{eco_format, At, FileError, FileResult, SourceResult, StrResult};
 use typst::foundations::{Bytes, Module, Styles};
 use typst::layout::Position;
@@ -27,10 +29,12 @@ use typst_shim::eval::{eval_compat, Eval};
 use crate::adt::revision::{RevisionLock, RevisionManager, RevisionManagerLike, RevisionSlot};
 use crate::analysis::prelude::*;
 use crate::analysis::{
-    analyze_signature, bib_info, definition, post_type_check, AnalysisStats, BibInfo,
-    CompletionFeat, Definition, PathPreference, QueryStatGuard, SemanticTokenCache,
-    SemanticTokenContext, SemanticTokens, Signature, SignatureTarget, Ty, TypeInfo,
+    analyze_signature, bib_entry_at, bib_info, definition, post_type_check, AnalysisStats, BibInfo,
+    CompletionFeat, Definition, DocsIndex, DocsIndexKey, PathPreference, QueryStatGuard,
+    SemanticTokenCache, SemanticTokenContext, SemanticTokens, Signature, SignatureTarget, Ty,
+    TypeInfo,
 };
+use crate::completion::SnippetLibrary;
 use crate::docs::{DefDocs, TidyModuleDocs};
 use crate::syntax::{
     classify_syntax, construct_module_dependencies, is_mark, resolve_id_by_path,
@@ -75,6 +79,14 @@ pub struct Analysis {
     pub caches: AnalysisGlobalCaches,
     /// The revision-managed cache for analysis.
     pub analysis_rev_cache: Arc<Mutex<AnalysisRevCache>>,
+    /// The on-disk index of the documentation of package definitions, which is
+    /// absent if the documentation is not persisted.
+    pub docs_index: Option<Arc<DocsIndex>>,
+    /// The snippet libraries loaded from the snippet files.
+    pub snippets: Arc<SnippetLibrary>,
+    /// The snippet files to load prefix snippets from, where a relative path is
+    /// resolved against the workspace root.
+    pub snippet_files: Vec<PathBuf>,
     /// The statistics about the analyzers.
     pub stats: Arc<AnalysisStats>,
 }
@@ -98,6 +110,7 @@ impl Analysis {
             local: LocalContext {
                 tokens,
                 caches: AnalysisLocalCaches::default(),
+                cancel: CancellationToken::new(),
                 shared: Arc::new(SharedContext {
                     slot,
                     lifetime,
@@ -235,6 +248,10 @@ impl Drop for LocalContextGuard {
 
 impl LocalContextGuard {
     fn gc(&self) {
+        if let Some(index) = &self.analysis.docs_index {
+            index.schedule_flush();
+        }
+
         let lifetime = self.lifetime;
         loop {
             let latest_clear_lifetime = self.analysis.caches.clear_lifetime.load(Ordering::Relaxed);
@@ -271,6 +288,9 @@ pub struct LocalContext {
     pub(crate) tokens: Option<SemanticTokenContext>,
     /// Local caches for analysis.
     pub caches: AnalysisLocalCaches,
+    /// The token to cancel the analysis, e.g. when the client cancels the
+    /// request.
+    pub cancel: CancellationToken,
     /// The shared context
     pub shared: Arc<SharedContext>,
 }
@@ -308,6 +328,12 @@ impl LocalContext {
         self.caches.root_files.get_or_init(f);
     }
 
+    /// Checks whether the analysis is cancelled. The analyses that scan many
+    /// files should check it and abort early.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
     /// Get all the source files in the workspace.
     pub(crate) fn completion_files(
         &self,
@@ -469,6 +495,40 @@ impl LocalContext {
     }
 
     pub(crate) fn def_docs(&mut self, def: &Definition) -> Option<DefDocs> {
+        let Some(index) = self.analysis.docs_index.clone() else {
+            return self.def_docs_(def);
+        };
+        let Some(key) = self.docs_index_key(def) else {
+            return self.def_docs_(def);
+        };
+
+        if let Some(docs) = index.def_docs(&key) {
+            return Some(docs);
+        }
+        let docs = self.def_docs_(def)?;
+        index.insert_def_docs(&key, docs.clone());
+        Some(docs)
+    }
+
+    /// Gets the key to the definition in the documentation index if it is in
+    /// an indexed package.
+    fn docs_index_key(&mut self, def: &Definition) -> Option<DocsIndexKey> {
+        let fid = def.decl.file_id()?;
+        let spec = fid
+            .package()
+            .filter(|spec| DocsIndexKey::is_indexed(spec))?;
+        let source = self.source_by_id(fid).ok()?;
+        let range = source.range(def.decl.span())?;
+
+        Some(DocsIndexKey {
+            spec: spec.clone(),
+            path: eco_format!("{}", fid.vpath().as_rooted_path().display()),
+            hash: hash128(source.text()),
+            def: eco_format!("{}@{}", def.decl.name(), range.start),
+        })
+    }
+
+    fn def_docs_(&mut self, def: &Definition) -> Option<DefDocs> {
         // let plain_docs = sym.head.docs.as_deref();
         // let plain_docs = plain_docs.or(sym.head.oneliner.as_deref());
         match def.decl.kind() {
@@ -749,6 +809,14 @@ impl SharedContext {
                 if prev.revision != ei.revision {
                     return None;
                 }
+                // The data files loaded by the source could be changed alone.
+                let data_changed = prev
+                    .data_deps
+                    .iter()
+                    .any(|(fid, hash)| hash128(&self.world.file(*fid).ok()) != *hash);
+                if data_changed {
+                    return None;
+                }
 
                 Some(prev)
             });
@@ -822,6 +890,18 @@ impl SharedContext {
         definition(self, source, doc, syntax)
     }
 
+    /// Gets the definition of the bibliography entry under position, if the
+    /// source is a bibliography file of the document.
+    pub(crate) fn def_of_bib_entry(
+        self: &Arc<Self>,
+        source: &Source,
+        doc: Option<&TypstDocument>,
+        position: LspPosition,
+    ) -> Option<Definition> {
+        let cursor = self.to_typst_pos(position, source)?;
+        bib_entry_at(self, source, doc, cursor)
+    }
+
     pub(crate) fn type_of_span(self: &Arc<Self>, span: Span) -> Option<Ty> {
         self.type_of_span_(&self.source_by_id(span.id()?).ok()?, span)
     }
@@ -1263,7 +1343,7 @@ fn ceil_char_boundary(text: &str, mut cursor: usize) -> usize {
 }
 
 #[comemo::memoize]
-fn analyze_bib(
+pub(crate) fn analyze_bib(
     world: Tracked<dyn World + '_>,
     bib_paths: EcoVec<EcoString>,
     elem_fid: FileId,
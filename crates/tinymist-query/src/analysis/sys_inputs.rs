@@ -0,0 +1,203 @@
+//! Analysis of the keys read from `sys.inputs`.
+
+use std::collections::HashSet;
+
+use typst::foundations::{Dict, Type};
+
+use super::prelude::*;
+
+/// A key read from `sys.inputs` in a source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct InputRead {
+    /// The read key.
+    pub key: EcoString,
+    /// The range of the key, i.e. the string literal or the field name.
+    pub range: Range<usize>,
+    /// Whether the read is optional, i.e. it has a default value or is
+    /// guarded by `"key" in sys.inputs`.
+    pub is_optional: bool,
+}
+
+/// Collects the keys read from `sys.inputs` in a source file, i.e.
+/// `sys.inputs.at("key")` and `sys.inputs.key`.
+///
+/// No key is collected if `sys` is rebound in the file, since `sys.inputs` may
+/// not be the inputs of the document then.
+pub(crate) fn input_reads(source: &Source) -> Vec<InputRead> {
+    let root = LinkedNode::new(source.root());
+    if binds_sys(&root) {
+        return vec![];
+    }
+
+    let mut reads = vec![];
+    let mut guarded = HashSet::new();
+    collect_input_reads(root, &mut reads, &mut guarded);
+
+    for read in &mut reads {
+        read.is_optional |= guarded.contains(&read.key);
+    }
+    reads
+}
+
+fn collect_input_reads(
+    node: LinkedNode,
+    reads: &mut Vec<InputRead>,
+    guarded: &mut HashSet<EcoString>,
+) {
+    match node.cast::<ast::Expr>() {
+        Some(ast::Expr::FuncCall(call)) if is_sys_inputs_at(call.callee()) => {
+            let is_optional = call.args().items().any(
+                |arg| matches!(arg, ast::Arg::Named(named) if named.name().as_str() == "default"),
+            );
+            if let Some(ast::Arg::Pos(ast::Expr::Str(key))) = call.args().items().next() {
+                if let Some(key_node) = node.find(key.span()) {
+                    reads.push(InputRead {
+                        key: key.get(),
+                        range: key_node.range(),
+                        is_optional,
+                    });
+                }
+            }
+        }
+        Some(ast::Expr::FieldAccess(access)) if is_sys_inputs(access.target()) => {
+            let field = access.field();
+            // Methods of dictionaries are accessed as fields when called.
+            let is_method = Type::of::<Dict>().scope().get(field.as_str()).is_some();
+            if let Some(key_node) = node.find(field.span()).filter(|_| !is_method) {
+                reads.push(InputRead {
+                    key: field.get().clone(),
+                    range: key_node.range(),
+                    is_optional: false,
+                });
+            }
+        }
+        Some(ast::Expr::Binary(binary))
+            if binary.op() == ast::BinOp::In && is_sys_inputs(binary.rhs()) =>
+        {
+            if let ast::Expr::Str(key) = binary.lhs() {
+                guarded.insert(key.get());
+            }
+        }
+        _ => {}
+    }
+
+    for child in node.children() {
+        collect_input_reads(child, reads, guarded);
+    }
+}
+
+/// Whether `sys` is bound by a let binding, a parameter, a loop or an import
+/// under the node.
+fn binds_sys(node: &LinkedNode) -> bool {
+    let is_sys = |ident: ast::Ident| ident.as_str() == "sys";
+    let binds = match node.cast::<ast::Expr>() {
+        Some(ast::Expr::Let(binding)) => binding.kind().bindings().into_iter().any(is_sys),
+        Some(ast::Expr::Closure(closure)) => closure.params().children().any(|param| match param {
+            ast::Param::Pos(pos) => pos.bindings().into_iter().any(is_sys),
+            ast::Param::Named(named) => is_sys(named.name()),
+            ast::Param::Spread(spread) => spread.sink_ident().is_some_and(is_sys),
+        }),
+        Some(ast::Expr::For(for_loop)) => for_loop.pattern().bindings().into_iter().any(is_sys),
+        Some(ast::Expr::Import(import)) => match import.imports() {
+            Some(ast::Imports::Items(items)) => items.iter().any(|item| is_sys(item.bound_name())),
+            _ => false,
+        },
+        _ => false,
+    };
+
+    binds || node.children().any(|child| binds_sys(&child))
+}
+
+/// Gets the key of `sys.inputs` read at the leaf node, which is either the
+/// first argument of `sys.inputs.at` or a field of `sys.inputs`.
+pub(crate) fn input_key_at(leaf: &LinkedNode) -> Option<EcoString> {
+    match leaf.kind() {
+        SyntaxKind::Str => {
+            let args = leaf
+                .parent()
+                .filter(|node| node.kind() == SyntaxKind::Args)?;
+            let call = args.parent()?.cast::<ast::FuncCall>()?;
+            if !is_sys_inputs_at(call.callee()) {
+                return None;
+            }
+            match call.args().items().next()? {
+                ast::Arg::Pos(ast::Expr::Str(key)) if key.span() == leaf.span() => Some(key.get()),
+                _ => None,
+            }
+        }
+        SyntaxKind::Ident => {
+            let access = leaf.parent()?.cast::<ast::FieldAccess>()?;
+            let field = access.field();
+            let is_key = is_sys_inputs(access.target()) && field.span() == leaf.span();
+            is_key.then(|| field.get().clone())
+        }
+        _ => None,
+    }
+}
+
+/// Whether the expression is `sys.inputs`.
+pub(crate) fn is_sys_inputs(expr: ast::Expr) -> bool {
+    let ast::Expr::FieldAccess(access) = expr else {
+        return false;
+    };
+    access.field().as_str() == "inputs"
+        && matches!(access.target(), ast::Expr::Ident(ident) if ident.as_str() == "sys")
+}
+
+/// Whether the expression is `sys.inputs.at`.
+pub(crate) fn is_sys_inputs_at(expr: ast::Expr) -> bool {
+    let ast::Expr::FieldAccess(access) = expr else {
+        return false;
+    };
+    access.field().as_str() == "at" && is_sys_inputs(access.target())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reads(text: &str) -> Vec<(String, bool)> {
+        let source = Source::detached(text);
+        let reads = input_reads(&source);
+        for read in &reads {
+            let key = &source.text()[read.range.clone()];
+            assert!(key.contains(read.key.as_str()), "{key:?} vs {read:?}");
+        }
+        reads
+            .into_iter()
+            .map(|read| (read.key.into(), read.is_optional))
+            .collect()
+    }
+
+    #[test]
+    fn test_input_reads() {
+        assert_eq!(
+            reads(r#"#sys.inputs.at("a") #sys.inputs.at("b", default: 1) #sys.inputs.c"#),
+            vec![("a".into(), false), ("b".into(), true), ("c".into(), false)]
+        );
+        assert_eq!(
+            reads(r#"#if "a" in sys.inputs { sys.inputs.a } #sys.inputs.keys()"#),
+            vec![("a".into(), true)]
+        );
+        assert_eq!(reads(r#"#inputs.at("a") #sys.at("a")"#), vec![]);
+        assert_eq!(
+            reads(r#"#let sys = (inputs: (a: 1)) #sys.inputs.a"#),
+            vec![]
+        );
+        assert_eq!(
+            reads(r#"#let f(sys) = sys.inputs.a #sys.inputs.at("b")"#),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_input_key_at() {
+        let source = Source::detached(r#"#sys.inputs.at("key") #sys.inputs.field #x.at("y")"#);
+        let root = LinkedNode::new(source.root());
+        let key_at = |cursor: usize| input_key_at(&root.leaf_at_compat(cursor).unwrap());
+
+        assert_eq!(key_at(17), Some("key".into()));
+        assert_eq!(key_at(36), Some("field".into()));
+        assert_eq!(key_at(47), None);
+    }
+}
@@ -15,6 +15,7 @@ use crate::{
 };
 
 mod apply;
+mod data;
 mod docs;
 mod select;
 mod syntax;
@@ -196,6 +197,11 @@ impl TypeChecker<'_> {
                         if let Some(ext_docs) = ext_type_info.var_docs.get(decl) {
                             self.info.var_docs.insert(decl.clone(), ext_docs.clone());
                         }
+                        // Keeps track of the data loaded by the module.
+                        let data_records = ext_type_info.data_records.iter().cloned();
+                        self.info.data_records.extend(data_records);
+                        let data_deps = ext_type_info.data_deps.clone();
+                        self.info.data_deps.extend(data_deps);
 
                         ext_type_info.simplify(ext_ty, false)
                     }
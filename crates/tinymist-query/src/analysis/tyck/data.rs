@@ -0,0 +1,359 @@
+//! Type checking on data files loaded by `json`, `yaml`, `toml` and `csv`
+
+use tinymist_std::hash::hash128;
+use typst::foundations::{Array, Dict, Str, Type};
+
+use super::*;
+use crate::syntax::resolve_id_by_path;
+use crate::ty::ArgsTy;
+
+/// The maximum number of array elements sampled to infer the element type.
+const ARRAY_SAMPLE_LIMIT: usize = 64;
+/// The maximum depth of nested data inferred structurally.
+const DATA_DEPTH_LIMIT: usize = 16;
+
+/// The format of a data file loaded by a builtin function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DataFormat {
+    Json,
+    Yaml,
+    Toml,
+    Csv,
+}
+
+impl DataFormat {
+    fn from_func(func: &Func) -> Option<Self> {
+        // The builtin loaders are native functions, which have no span.
+        if !func.span().is_detached() {
+            return None;
+        }
+
+        Some(match func.name()? {
+            "json" => Self::Json,
+            "yaml" => Self::Yaml,
+            "toml" => Self::Toml,
+            "csv" => Self::Csv,
+            _ => return None,
+        })
+    }
+}
+
+impl TypeChecker<'_> {
+    /// Infers the type of a call loading a data file from a literal path, by
+    /// reading the structure of the file.
+    pub(super) fn check_data_load(&mut self, callee: &Ty, args: &ArgsTy) -> Option<Ty> {
+        let Ty::Value(callee) = callee else {
+            return None;
+        };
+        let Value::Func(func) = &callee.val else {
+            return None;
+        };
+        let format = DataFormat::from_func(func)?;
+
+        let Some(Ty::Value(path)) = args.pos(0) else {
+            return None;
+        };
+        let Value::Str(path) = &path.val else {
+            return None;
+        };
+
+        let fid = resolve_id_by_path(&self.ctx.world, self.ei.fid, path)?;
+        let bytes = self.ctx.world.file(fid).ok();
+        // The typing depends on the content of the file, which is not tracked by
+        // the expressions of the source.
+        self.info.data_deps.insert(fid, hash128(&bytes));
+        let bytes = bytes?;
+        let value = match format {
+            DataFormat::Json => serde_json::from_slice(&bytes).ok()?,
+            DataFormat::Yaml => serde_yaml::from_slice(&bytes).ok()?,
+            DataFormat::Toml => toml::from_str(std::str::from_utf8(&bytes).ok()?).ok()?,
+            DataFormat::Csv => {
+                let delimiter = match args.named(&"delimiter".into()) {
+                    Some(Ty::Value(delimiter)) => match &delimiter.val {
+                        Value::Str(delimiter) => delimiter.chars().next()?,
+                        _ => return None,
+                    },
+                    _ => ',',
+                };
+                let by_dict = match args.named(&"row-type".into()) {
+                    Some(Ty::Value(row_type)) => match &row_type.val {
+                        Value::Type(ty) => *ty == Type::of::<Dict>(),
+                        _ => false,
+                    },
+                    _ => false,
+                };
+                load_csv(std::str::from_utf8(&bytes).ok()?, delimiter, by_dict)
+            }
+        };
+
+        crate::log_debug_ct!("data load: {format:?} from {fid:?}");
+        Some(self.check_data(&value, 0, false))
+    }
+
+    /// Infers the structural type of the loaded data.
+    ///
+    /// The keys missing in the inferred records are reported, unless the
+    /// records are `sampled` from a truncated array, whose unchecked elements
+    /// may have more keys.
+    fn check_data(&mut self, value: &Value, depth: usize, sampled: bool) -> Ty {
+        match value {
+            Value::Dict(dict) if depth < DATA_DEPTH_LIMIT => {
+                let fields = dict
+                    .iter()
+                    .map(|(name, value)| {
+                        let ty = self.check_data(value, depth + 1, sampled);
+                        (name.as_str().into(), ty)
+                    })
+                    .collect();
+                let record = RecordTy::new(fields);
+                if !sampled {
+                    self.info.data_records.insert(record.clone());
+                }
+                Ty::Dict(record)
+            }
+            Value::Array(array) if depth < DATA_DEPTH_LIMIT => {
+                let sampled = sampled || array.len() > ARRAY_SAMPLE_LIMIT;
+                let mut elems = array
+                    .iter()
+                    .take(ARRAY_SAMPLE_LIMIT)
+                    .map(|elem| self.check_data(elem, depth + 1, sampled))
+                    .collect::<Vec<_>>();
+                elems.sort();
+                elems.dedup();
+                Ty::Array(Ty::from_types(elems.into_iter()).into())
+            }
+            value => BuiltinTy::from_builtin(value.ty()),
+        }
+    }
+}
+
+/// Loads a CSV file as the `csv` function does, in which all the cells are
+/// strings.
+fn load_csv(text: &str, delimiter: char, by_dict: bool) -> Value {
+    let rows = parse_csv(text, delimiter);
+    if !by_dict {
+        let rows = rows.into_iter().map(|row| {
+            let row = row.into_iter().map(|cell| Value::Str(cell.into()));
+            Value::Array(row.collect())
+        });
+        return Value::Array(rows.collect());
+    }
+
+    let mut rows = rows.into_iter();
+    let header = rows.next().unwrap_or_default();
+    let rows = rows.map(|row| {
+        let cells = header.iter().zip(row).map(|(key, cell)| {
+            let key: Str = key.as_str().into();
+            (key, Value::Str(cell.into()))
+        });
+        Value::Dict(cells.collect())
+    });
+    Value::Array(rows.collect::<Array>())
+}
+
+/// Splits a CSV text into rows of cells, following the quoting rules of RFC
+/// 4180.
+fn parse_csv(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut cell = String::new();
+    let mut quoted = false;
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    chars.next();
+                    cell.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            c if quoted => cell.push(c),
+            '"' if cell.is_empty() => quoted = true,
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut cell));
+                rows.push(std::mem::take(&mut row));
+            }
+            c if c == delimiter => row.push(std::mem::take(&mut cell)),
+            c => cell.push(c),
+        }
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push(row);
+    }
+
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use tinymist_world::ShadowApi;
+    use typst::foundations::Bytes;
+
+    use super::*;
+    use crate::analysis::Analysis;
+    use crate::tests::*;
+    use crate::{CompletionRequest, HoverRequest, StatefulRequest};
+
+    #[test]
+    fn test_missing_data_keys() {
+        let sources = r#"/// path: config.yaml
+name: Tinymist
+author:
+  name: Myriad
+tags: [a, b]
+-----
+#let data = yaml("config.yaml")
+#data.name #data.author.name #data.tags.len()
+#data.author.email #data.keys() #data.title
+"#;
+
+        run_with_sources(sources, |verse, path| {
+            run_with_ctx(verse, path, &|ctx, path| {
+                let source = ctx.source_by_path(&path).unwrap();
+                let ti = ctx.type_check(&source);
+                assert_eq!(ti.data_records.len(), 2);
+
+                let mut missing = ti
+                    .missing_fields
+                    .iter()
+                    .map(|(_, key)| key.to_string())
+                    .collect::<Vec<_>>();
+                missing.sort();
+                missing.dedup();
+                assert_eq!(missing, vec!["email", "title"]);
+            })
+        });
+    }
+
+    #[test]
+    fn test_truncated_data_array() {
+        let items = (0..ARRAY_SAMPLE_LIMIT).map(|_| r#"{ "name": "a" }"#);
+        let items = items.chain([r#"{ "name": "b", "extra": "c" }"#]);
+        let items = items.collect::<Vec<_>>().join(", ");
+        let data = format!(r#"{{ "items": [{items}] }}"#);
+        let sources = format!(
+            r#"/// path: data.json
+{data}
+-----
+#let data = json("data.json")
+#data.items.first().extra #data.title
+"#
+        );
+
+        run_with_sources(&sources, |verse, path| {
+            run_with_ctx(verse, path, &|ctx, path| {
+                let source = ctx.source_by_path(&path).unwrap();
+                let ti = ctx.type_check(&source);
+                // The items sampled from the truncated array are not checked.
+                assert_eq!(ti.data_records.len(), 1);
+
+                let missing = ti.missing_fields.iter().map(|(_, key)| key.to_string());
+                assert_eq!(missing.collect::<Vec<_>>(), vec!["title"]);
+            })
+        });
+    }
+
+    #[test]
+    fn test_edit_data_file() {
+        let sources = r#"/// path: config.yaml
+name: Tinymist
+-----
+#let data = yaml("config.yaml")
+#data.name #data.title
+"#;
+
+        run_with_sources(sources, |verse, path| {
+            // The analysis is shared across the edit to reuse the cached typing.
+            let analysis = Analysis::default();
+            let missing_fields = |verse: &LspUniverse| {
+                let mut world = verse.snapshot();
+                world.set_is_compiling(false);
+                let mut ctx = analysis.snapshot(world);
+
+                let source = ctx.source_by_path(&path).unwrap();
+                let ti = ctx.type_check(&source);
+                let missing = ti.missing_fields.iter().map(|(_, key)| key.to_string());
+                missing.collect::<Vec<_>>()
+            };
+            assert_eq!(missing_fields(verse), vec!["title"]);
+
+            let data = Bytes::from_string("name: Tinymist\ntitle: Docs\n".to_owned());
+            let data_path = path.with_file_name("config.yaml");
+            verse.map_shadow(&data_path, data).unwrap();
+            assert!(missing_fields(verse).is_empty());
+        });
+    }
+
+    #[test]
+    fn test_data_field_queries() {
+        let sources = r#"/// path: config.yaml
+author:
+  name: Myriad
+-----
+#let data = yaml("config.yaml")
+#data.author.na
+"#;
+
+        run_with_sources(sources, |verse, path| {
+            run_with_ctx(verse, path, &|ctx, path| {
+                let source = ctx.source_by_path(&path).unwrap();
+                let graph = compile_doc_for_test(ctx, &Default::default());
+                let field_end = source.text().find(".na").unwrap() + ".na".len();
+                let author = source.text().find("author").unwrap();
+
+                let request = CompletionRequest {
+                    path: path.clone(),
+                    position: ctx.to_lsp_pos(field_end, &source),
+                    explicit: false,
+                    trigger_character: None,
+                };
+                let list = request.request(ctx, graph.clone()).unwrap();
+                let labels = list.items.iter().map(|item| item.label.as_str());
+                assert!(labels.collect::<Vec<_>>().contains(&"name"));
+
+                let request = HoverRequest {
+                    path: path.clone(),
+                    position: ctx.to_lsp_pos(author + 1, &source),
+                };
+                let hover = request.request(ctx, graph).unwrap();
+                let hover = serde_json::to_string(&hover).unwrap();
+                assert!(hover.contains("name"), "{hover}");
+            })
+        });
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let rows = parse_csv("a,b\r\n1,\"x, \"\"y\"\"\"\n2,\n", ',');
+        assert_eq!(
+            rows,
+            vec![
+                vec!["a".to_owned(), "b".to_owned()],
+                vec!["1".to_owned(), "x, \"y\"".to_owned()],
+                vec!["2".to_owned(), "".to_owned()],
+            ]
+        );
+
+        let rows = parse_csv("a;b\n1;2", ';');
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1], vec!["1".to_owned(), "2".to_owned()]);
+    }
+
+    #[test]
+    fn test_load_csv_by_dict() {
+        let value = load_csv("name,age\nAlice,30\nBob,25\n", ',', true);
+        let Value::Array(rows) = value else {
+            panic!("expected an array, got {value:?}");
+        };
+        assert_eq!(rows.len(), 2);
+        let Some(Value::Dict(first)) = rows.first() else {
+            panic!("expected a dictionary row, got {rows:?}");
+        };
+        assert_eq!(first.get("age").ok(), Some(&Value::Str("30".into())));
+    }
+}
@@ -1,5 +1,7 @@
 //! Type checking at select site
 
+use typst::foundations::{Dict, Type};
+
 use super::*;
 use crate::analysis::SelectChecker;
 
@@ -8,6 +10,8 @@ use crate::analysis::SelectChecker;
 pub struct SelectFieldChecker<'a, 'b> {
     pub(super) base: &'a mut TypeChecker<'b>,
     pub resultant: Vec<Ty>,
+    /// Whether the field is missing in a record inferred from a data file.
+    pub missing_in_data: bool,
 }
 
 impl SelectChecker for SelectFieldChecker<'_, '_> {
@@ -15,7 +19,12 @@ impl SelectChecker for SelectFieldChecker<'_, '_> {
         crate::log_debug_ct!("selecting field: {iface:?} {key:?}");
         let _ = pol;
 
+        let in_data =
+            matches!(iface, Iface::Dict(record) if self.base.info.data_records.contains(record));
         let Some(res) = iface.select(self.base, key) else {
+            // Methods of dictionaries are also selected on the data.
+            let is_method = Type::of::<Dict>().scope().get(key).is_some();
+            self.missing_in_data |= in_data && !is_method;
             return;
         };
 
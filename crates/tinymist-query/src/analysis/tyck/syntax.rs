@@ -347,9 +347,16 @@ impl TypeChecker<'_> {
         let mut worker = SelectFieldChecker {
             base: self,
             resultant: vec![base],
+            missing_in_data: false,
         };
         ty.select(&field, true, &mut worker);
+        // The field is missing if it is not found in any of the selected types.
+        let is_missing = worker.missing_in_data && worker.resultant.len() == 1;
         let res = Ty::from_types(worker.resultant.into_iter());
+        if is_missing {
+            let key_site = select.key.span();
+            self.info.missing_fields.push((key_site, field.clone()));
+        }
         self.info.witness_at_least(select_site, res.clone());
         res
     }
@@ -361,6 +368,11 @@ impl TypeChecker<'_> {
         crate::log_debug_ct!("func_call: {callee:?} with {args:?}");
 
         if let Ty::Args(args) = args {
+            if let Some(res) = self.check_data_load(&callee, &args) {
+                self.info.witness_at_least(apply.span, res.clone());
+                return res;
+            }
+
             let mut worker = ApplyTypeChecker {
                 base: self,
                 call_site: apply.callee.span(),
@@ -1,6 +1,8 @@
 use crate::analysis::{CompletionCursor, CompletionWorker};
 use crate::prelude::*;
 
+pub(crate) mod library;
+pub use library::*;
 pub(crate) mod proto;
 pub use proto::*;
 pub(crate) mod snippet;
@@ -161,6 +163,7 @@ mod tests {
                         sort_text: item.sort_text,
                         kind: item.kind,
                         text_edit: item.text_edit,
+                        additional_text_edits: item.additional_text_edits,
                         command: item.command,
                         ..Default::default()
                     })
@@ -0,0 +1,198 @@
+//! Snippet libraries loaded from snippet files.
+//!
+//! A snippet file is a TOML file listing prefix snippets, for example:
+//!
+//! ```toml
+//! [[snippet]]
+//! label = "todo"
+//! snippet = "todo[${content}]"
+//! description = "Inserts a todo note."
+//! context = [{ mode = "markup" }, { mode = "code" }]
+//! imports = [{ path = "/lib/notes.typ", items = ["todo"] }]
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use parking_lot::Mutex;
+use rustc_hash::FxHashMap;
+use serde::Deserialize;
+
+use super::{CompletionContext, PrefixSnippet};
+
+/// The path to the project-level snippet file, relative to the workspace root.
+pub const PROJECT_SNIPPET_FILE: &str = ".tinymist/snippets.toml";
+
+/// The snippet libraries loaded from the snippet files.
+///
+/// The files are checked on access and reloaded once their modification time
+/// changes, so edits to a snippet file take effect on the next completion.
+#[derive(Default)]
+pub struct SnippetLibrary {
+    /// The loaded snippet files.
+    files: Mutex<FxHashMap<PathBuf, LoadedSnippets>>,
+}
+
+struct LoadedSnippets {
+    mtime: Option<SystemTime>,
+    snippets: Arc<Vec<PrefixSnippet>>,
+}
+
+#[derive(Deserialize)]
+struct SnippetFile {
+    #[serde(default)]
+    snippet: Vec<PrefixSnippet>,
+}
+
+impl SnippetLibrary {
+    /// Gets the default snippet files, i.e. the project-level snippet file,
+    /// which is relative to the workspace root, and the user-level snippet
+    /// file in the config directory of the system.
+    pub fn default_files() -> Vec<PathBuf> {
+        let user_file = dirs::config_dir().map(|dir| dir.join("tinymist").join("snippets.toml"));
+        std::iter::once(PathBuf::from(PROJECT_SNIPPET_FILE))
+            .chain(user_file)
+            .collect()
+    }
+
+    /// Gets the snippets in the file, which is empty if the file doesn't exist
+    /// or is invalid.
+    pub fn snippets(&self, path: &Path) -> Arc<Vec<PrefixSnippet>> {
+        let mtime = std::fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .ok();
+
+        let mut files = self.files.lock();
+        if let Some(loaded) = files.get(path).filter(|loaded| loaded.mtime == mtime) {
+            return loaded.snippets.clone();
+        }
+
+        let snippets = match mtime {
+            Some(..) => Arc::new(load_snippets(path)),
+            None => Arc::default(),
+        };
+        files.insert(
+            path.to_owned(),
+            LoadedSnippets {
+                mtime,
+                snippets: snippets.clone(),
+            },
+        );
+        snippets
+    }
+}
+
+fn load_snippets(path: &Path) -> Vec<PrefixSnippet> {
+    let snippets = std::fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|text| parse_snippets(&text));
+    match snippets {
+        Ok(snippets) => {
+            log::info!("loaded {} snippets from {path:?}", snippets.len());
+            snippets
+        }
+        Err(err) => {
+            log::warn!("failed to load snippets from {path:?}: {err}");
+            vec![]
+        }
+    }
+}
+
+/// Parses the snippets in a snippet file. A snippet without context applies
+/// in any context.
+fn parse_snippets(text: &str) -> Result<Vec<PrefixSnippet>, String> {
+    let file: SnippetFile = toml::from_str(text).map_err(|err| err.to_string())?;
+
+    let mut snippets = file.snippet;
+    snippets.retain(|snippet| !snippet.label.is_empty() && !snippet.snippet.is_empty());
+    for snippet in &mut snippets {
+        if snippet.context.is_empty() {
+            snippet.context.push(CompletionContext::default());
+        }
+    }
+    Ok(snippets)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::completion::{CompletionContextKey, ContextSelector};
+    use crate::syntax::{InterpretMode, SurroundingSyntax};
+
+    #[test]
+    fn test_parse_snippets() {
+        let snippets = parse_snippets(
+            r#"
+[[snippet]]
+label = "todo"
+snippet = "todo[${content}]"
+description = "Inserts a todo note."
+context = [{ mode = "markup" }, { mode = "code", syntax = "Regular" }]
+imports = [{ path = "/lib/notes.typ", items = ["todo"] }]
+
+[[snippet]]
+label = "anywhere"
+snippet = "x"
+
+[[snippet]]
+label = "empty"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(snippets.len(), 2);
+        let todo = &snippets[0];
+        assert_eq!(
+            todo.context[1].mode,
+            ContextSelector::Positive(Some(InterpretMode::Code))
+        );
+        assert_eq!(todo.imports[0].path, "/lib/notes.typ");
+        assert_eq!(todo.imports[0].items.as_slice(), &["todo"]);
+
+        let markup = CompletionContextKey::new(Some(InterpretMode::Markup), None);
+        let math = CompletionContextKey::new(Some(InterpretMode::Math), None);
+        let code =
+            CompletionContextKey::new(Some(InterpretMode::Code), Some(SurroundingSyntax::Regular));
+        assert!(todo.applies_to(&markup));
+        assert!(todo.applies_to(&code));
+        assert!(!todo.applies_to(&math));
+
+        let anywhere = &snippets[1];
+        assert!(anywhere.applies_to(&CompletionContextKey::new(None, None)));
+    }
+
+    #[test]
+    fn test_reload_snippets() {
+        let dir = std::env::temp_dir().join(format!("tinymist-snippets-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("snippets.toml");
+        let library = SnippetLibrary::default();
+        assert!(library.snippets(&path).is_empty());
+
+        let file = std::fs::File::create(&path).unwrap();
+        std::fs::write(&path, "[[snippet]]\nlabel = \"a\"\nsnippet = \"a\"\n").unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH).unwrap();
+        assert_eq!(library.snippets(&path).len(), 1);
+
+        // The file is reloaded once it is modified.
+        std::fs::write(&path, "[[snippet]]\nlabel = \"b\"\nsnippet = \"b\"\n").unwrap();
+        let labels = |snippets: Arc<Vec<PrefixSnippet>>| {
+            let labels = snippets.iter().map(|snippet| snippet.label.to_string());
+            labels.collect::<Vec<_>>()
+        };
+        file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(labels(library.snippets(&path)), vec!["b"]);
+
+        // The loaded snippets are kept if the file is not modified.
+        std::fs::write(&path, "").unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(labels(library.snippets(&path)), vec!["b"]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
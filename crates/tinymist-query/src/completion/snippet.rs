@@ -93,8 +93,10 @@ where
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct CompletionContext {
     /// The mode in which the snippet is applicable.
+    #[serde(default)]
     pub mode: ContextSelector<InterpretMode>,
     /// The syntax in which the snippet is applicable.
+    #[serde(default)]
     pub syntax: ContextSelector<SurroundingSyntax>,
 }
 
@@ -146,8 +148,19 @@ pub struct PostfixSnippet {
     pub parsed_snippet: OnceLock<Option<ParsedSnippet>>,
 }
 
+/// An import required by a prefix snippet, which is inserted into the
+/// document along with the snippet if the items are not imported yet.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct SnippetImport {
+    /// The module to import from, e.g. `@preview/cetz:0.3.4` or `/lib.typ`.
+    pub path: EcoString,
+    /// The items to import from the module.
+    pub items: EcoVec<EcoString>,
+}
+
 /// A prefix completion snippet.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PrefixSnippet {
     /// The mode in which the snippet is applicable.
     pub context: EcoVec<CompletionContext>,
@@ -161,6 +174,8 @@ pub struct PrefixSnippet {
     pub description: EcoString,
     /// The command to execute.
     pub command: Option<CompletionCommand>,
+    /// The imports required by the snippet.
+    pub imports: EcoVec<SnippetImport>,
     /// Lazily expanded context.
     #[serde(skip)]
     pub expanded_context: OnceLock<HashSet<CompletionContextKey>>,
@@ -234,6 +249,7 @@ impl From<&ConstPrefixSnippet> for Interned<PrefixSnippet> {
             snippet: snippet.snippet.into(),
             description: snippet.description.into(),
             command: None,
+            imports: EcoVec::new(),
             expanded_context: OnceLock::new(),
         })
     }
@@ -258,6 +274,7 @@ impl From<&ConstPrefixSnippetWithSuggest> for Interned<PrefixSnippet> {
             snippet: snippet.snippet.into(),
             description: snippet.description.into(),
             command: Some(CompletionCommand::TriggerSuggest),
+            imports: EcoVec::new(),
             expanded_context: OnceLock::new(),
         })
     }
@@ -1,9 +1,15 @@
 use std::borrow::Cow;
+use std::collections::HashSet;
 
 use tinymist_project::LspWorld;
+use tinymist_std::typst::TypstDocument;
 use tinymist_world::vfs::WorkspaceResolver;
+use typst::foundations::{Label, Selector, StyleChain};
 use typst::{diag::SourceDiagnostic, syntax::Span};
 
+use crate::adt::interner::Interned;
+use crate::analysis::{find_bib, input_reads, BibIssueKind};
+use crate::syntax::get_index_info;
 use crate::{prelude::*, LspWorldExt};
 
 use regex::RegexSet;
@@ -16,16 +22,104 @@ type TypstSeverity = typst::diag::Severity;
 
 /// Converts a list of Typst diagnostics to LSP diagnostics,
 /// with potential refinements on the error messages.
+///
+/// If the compiled `doc` is given, the citations are also checked against its
+/// bibliography.
 pub fn check_doc<'a>(
     world: &LspWorld,
     errors: impl IntoIterator<Item = &'a TypstDiagnostic>,
+    doc: Option<&TypstDocument>,
     position_encoding: PositionEncoding,
 ) -> DiagnosticsMap {
+    let errors = errors.into_iter().collect::<Vec<_>>();
     CheckDocWorker::new(world, position_encoding)
         .check()
+        .check_bib(doc, &errors)
+        .check_inputs(&errors)
         .convert_all(errors)
 }
 
+/// Converts a list of Typst diagnostics to LSP diagnostics like [`check_doc`],
+/// and additionally checks the keys selected on the data loaded from files,
+/// e.g. `data.title` where `data` is loaded by `yaml("config.yaml")`.
+pub fn check_doc_with_analysis<'a>(
+    ctx: &mut LocalContext,
+    errors: impl IntoIterator<Item = &'a TypstDiagnostic>,
+    doc: Option<&TypstDocument>,
+) -> DiagnosticsMap {
+    let errors = errors.into_iter().collect::<Vec<_>>();
+    let missing_keys = check_data_keys(ctx, &errors);
+    let mut worker = CheckDocWorker::new(ctx.world(), ctx.position_encoding())
+        .check()
+        .check_bib(doc, &errors)
+        .check_inputs(&errors);
+    for (fid, range, message) in missing_keys {
+        worker.push_range(fid, range, DiagnosticSeverity::WARNING, message, vec![]);
+    }
+    worker.convert_all(errors)
+}
+
+/// Collects the keys selected on the data loaded from files but missing in
+/// the files.
+///
+/// The `errors` reported by the compiler are used to avoid reporting a
+/// missing key twice.
+fn check_data_keys(
+    ctx: &mut LocalContext,
+    errors: &[&TypstDiagnostic],
+) -> Vec<(TypstFileId, Range<usize>, String)> {
+    let has_ext = |fid: &TypstFileId, exts: &[&str]| {
+        let ext = fid.vpath().as_rooted_path().extension();
+        ext.and_then(|ext| ext.to_str())
+            .is_some_and(|ext| exts.contains(&ext))
+    };
+
+    // Only the documents loading data files are type checked.
+    let deps = ctx.depended_files();
+    if !deps
+        .iter()
+        .any(|dep| has_ext(dep, &["json", "yaml", "yml", "toml", "csv"]))
+    {
+        return vec![];
+    }
+
+    let mut issues = vec![];
+    for &dep in deps.iter() {
+        if WorkspaceResolver::is_package_file(dep) || !has_ext(&dep, &["typ"]) {
+            continue;
+        }
+        let Ok(source) = ctx.source_by_id(dep) else {
+            continue;
+        };
+
+        let error_ranges = errors
+            .iter()
+            .filter(|diag| diag.span.id() == Some(dep))
+            .filter_map(|diag| source.range(diag.span))
+            .collect::<Vec<_>>();
+
+        let ti = ctx.type_check(&source);
+        let mut seen = HashSet::new();
+        for (span, key) in &ti.missing_fields {
+            let Some(range) = source.range(*span) else {
+                continue;
+            };
+            if !seen.insert(range.start)
+                || error_ranges
+                    .iter()
+                    .any(|err| err.start <= range.start && range.end <= err.end)
+            {
+                continue;
+            }
+
+            let message = format!("key `{key}` does not exist in the data file");
+            issues.push((dep, range, message));
+        }
+    }
+
+    issues
+}
+
 /// Context for converting Typst diagnostics to LSP diagnostics.
 pub(crate) struct CheckDocWorker<'a> {
     /// The world surface for Typst compiler.
@@ -75,6 +169,201 @@ impl<'w> CheckDocWorker<'w> {
         self
     }
 
+    /// Checks the citations and the bibliography files of the document.
+    ///
+    /// The `errors` reported by the compiler are used to avoid reporting a
+    /// missing key twice.
+    pub fn check_bib(mut self, doc: Option<&TypstDocument>, errors: &[&TypstDiagnostic]) -> Self {
+        let Some(doc) = doc else {
+            return self;
+        };
+        let introspector = doc.introspector();
+        let Some((bib_elem, bib_info)) = find_bib(self.world, introspector) else {
+            return self;
+        };
+
+        let mut cited = HashSet::new();
+        for dep in self.world.depended_files() {
+            if WorkspaceResolver::is_package_file(dep) {
+                continue;
+            }
+            let Ok(source) = self.world.source(dep) else {
+                continue;
+            };
+
+            let error_ranges = errors
+                .iter()
+                .filter(|diag| diag.span.id() == Some(dep))
+                .filter_map(|diag| source.range(diag.span))
+                .collect::<Vec<_>>();
+
+            let index = get_index_info(&source);
+            for (key, span) in &index.citations {
+                cited.insert(key.clone());
+                let name: &str = key.as_ref();
+                if bib_info.entries.contains_key(name) {
+                    continue;
+                }
+                // A reference may also point to a label in the document.
+                let label = Label::construct(name.into());
+                if !introspector.query(&Selector::Label(label)).is_empty() {
+                    continue;
+                }
+                let Some(range) = source.range(*span) else {
+                    continue;
+                };
+                if error_ranges
+                    .iter()
+                    .any(|err| err.start <= range.start && range.end <= err.end)
+                {
+                    continue;
+                }
+
+                let message = format!("key `{key}` does not exist in the bibliography");
+                self.push_range(dep, range, DiagnosticSeverity::ERROR, message, vec![]);
+            }
+        }
+
+        for issue in &bib_info.issues {
+            let entry = &issue.entry;
+            let key = &issue.key;
+            let (message, related) = match &issue.kind {
+                BibIssueKind::Duplicate(first) => {
+                    let message = format!("duplicate bibliography key `{key}`");
+                    let related = self.location(first.file_id, first.name_range.clone());
+                    let related = related.map(|location| DiagnosticRelatedInformation {
+                        location,
+                        message: "first defined here".to_owned(),
+                    });
+                    (message, related.into_iter().collect())
+                }
+                BibIssueKind::MissingFields { ty, fields } => {
+                    let fields = fields.iter().map(|alts| alts.join(" or ")).join(", ");
+                    let message = if ty.is_empty() {
+                        format!("bibliography entry `{key}` is missing required fields: {fields}")
+                    } else {
+                        format!(
+                            "bibliography entry `{key}` of type `{ty}` is missing required fields: {fields}"
+                        )
+                    };
+                    (message, vec![])
+                }
+            };
+            let severity = DiagnosticSeverity::WARNING;
+            self.push_range(
+                entry.file_id,
+                entry.name_range.clone(),
+                severity,
+                message,
+                related,
+            );
+        }
+
+        // All entries are printed in a full bibliography.
+        if bib_elem.full(StyleChain::default()) {
+            return self;
+        }
+
+        for (key, entry) in &bib_info.entries {
+            if cited.contains(&Interned::from(key.as_str())) {
+                continue;
+            }
+            let message = format!("bibliography entry `{key}` is never cited");
+            let severity = DiagnosticSeverity::WARNING;
+            self.push_range(
+                entry.file_id,
+                entry.name_range.clone(),
+                severity,
+                message,
+                vec![],
+            );
+        }
+
+        self
+    }
+
+    /// Checks the keys read from `sys.inputs` against the inputs of the
+    /// document.
+    ///
+    /// The `errors` reported by the compiler are used to avoid reporting a
+    /// missing key twice.
+    pub fn check_inputs(mut self, errors: &[&TypstDiagnostic]) -> Self {
+        let inputs = self.world.inputs();
+        for dep in self.world.depended_files() {
+            if WorkspaceResolver::is_package_file(dep) {
+                continue;
+            }
+            let Ok(source) = self.world.source(dep) else {
+                continue;
+            };
+
+            let error_ranges = errors
+                .iter()
+                .filter(|diag| diag.span.id() == Some(dep))
+                .filter_map(|diag| source.range(diag.span))
+                .collect::<Vec<_>>();
+
+            for read in input_reads(&source) {
+                if read.is_optional || inputs.contains(&read.key) {
+                    continue;
+                }
+                let range = read.range;
+                if error_ranges
+                    .iter()
+                    .any(|err| err.start <= range.start && range.end <= err.end)
+                {
+                    continue;
+                }
+
+                let message = format!(
+                    "input `{}` is not supplied by the inputs and has no default value",
+                    read.key
+                );
+                self.push_range(dep, range, DiagnosticSeverity::WARNING, message, vec![]);
+            }
+        }
+
+        self
+    }
+
+    /// Pushes a diagnostic at the given range of a (possibly non-typst) file.
+    fn push_range(
+        &mut self,
+        fid: TypstFileId,
+        range: Range<usize>,
+        severity: DiagnosticSeverity,
+        message: String,
+        related: Vec<DiagnosticRelatedInformation>,
+    ) {
+        if WorkspaceResolver::is_package_file(fid) {
+            return;
+        }
+        let Some(location) = self.location(fid, range) else {
+            return;
+        };
+
+        let diagnostic = Diagnostic {
+            range: location.range,
+            severity: Some(severity),
+            message,
+            source: Some("typst".to_owned()),
+            related_information: Some(related),
+            ..Default::default()
+        };
+        self.results
+            .entry(location.uri)
+            .or_default()
+            .push(diagnostic);
+    }
+
+    /// Converts a range of a file to a LSP location.
+    fn location(&self, fid: TypstFileId, range: Range<usize>) -> Option<LspLocation> {
+        let uri = self.world.uri_for_id(fid).ok()?;
+        let source = self.world.source(fid).ok()?;
+        let range = to_lsp_range(range, &source, self.position_encoding);
+        Some(LspLocation { uri, range })
+    }
+
     /// Converts a list of Typst diagnostics to LSP diagnostics.
     pub fn convert_all<'a>(
         mut self,
@@ -285,3 +574,90 @@ impl DiagnosticRefiner for OutOfRootHintRefiner {
 fn lint_source(source: &Source) -> EcoVec<SourceDiagnostic> {
     tinymist_lint::lint_source(source)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use tinymist_world::ShadowApi;
+    use typst::foundations::Bytes;
+    use typst::layout::PagedDocument;
+
+    use super::*;
+    use crate::tests::*;
+
+    /// Compiles the entry of the `world` and checks it against the `doc`.
+    fn check_entry(
+        world: &LspWorld,
+        doc: Option<&TypstDocument>,
+        position_encoding: PositionEncoding,
+    ) -> BTreeMap<String, EcoVec<Diagnostic>> {
+        let mut world = world.clone();
+        world.set_is_compiling(true);
+        let compiled = typst::compile::<PagedDocument>(&world);
+        let errors = compiled.output.err().unwrap_or_default();
+        let diags = errors.iter().chain(compiled.warnings.iter());
+
+        let result = check_doc(&world, diags, doc, position_encoding);
+        result
+            .into_iter()
+            .map(|(k, v)| (file_path_(&k), v))
+            .collect()
+    }
+
+    #[test]
+    fn test() {
+        snapshot_testing("diagnostics", &|ctx, path| {
+            let source = ctx.source_by_path(&path).unwrap();
+            let docs = find_module_level_docs(&source).unwrap_or_default();
+            let properties = get_test_properties(&docs);
+            // The document may be compiled from another entry, which simulates
+            // a document kept from the last successful compilation.
+            let graph = compile_doc_for_test(ctx, &properties);
+            let doc = graph.snap.success_doc.as_ref();
+
+            let result = check_entry(&ctx.world, doc, ctx.position_encoding());
+            assert_snapshot!(JsonRepr::new_redacted(result, &REDACT_LOC));
+        });
+    }
+
+    #[test]
+    fn test_duplicate_bib_keys() {
+        let sources = r#"/// path: refs.bib
+@book{knuth, author = {Donald Knuth}, title = {TAOCP}, year = {1968}}
+-----
+@knuth
+
+#bibliography("refs.bib")
+"#;
+
+        run_with_sources(sources, |verse, path| {
+            let mut world = verse.snapshot();
+            world.set_is_compiling(true);
+            let doc = typst::compile::<PagedDocument>(&world).output.unwrap();
+            let doc = TypstDocument::Paged(Arc::new(doc));
+
+            // The compiler rejects duplicate keys, so they are only checked
+            // against the last successfully compiled document.
+            let bib = r#"@book{knuth, author = {Donald Knuth}, title = {TAOCP}, year = {1968}}
+@book{knuth, author = {Donald Knuth}, title = {TAOCP}, year = {1973}}
+"#;
+            let bib_path = path.with_file_name("refs.bib");
+            verse
+                .map_shadow(&bib_path, Bytes::from_string(bib.to_owned()))
+                .unwrap();
+
+            let result = check_entry(&verse.snapshot(), Some(&doc), PositionEncoding::Utf16);
+            let duplicate = result["refs.bib"]
+                .iter()
+                .find(|diag| diag.message == "duplicate bibliography key `knuth`")
+                .unwrap();
+            assert_eq!(duplicate.range.start, LspPosition::new(1, 6));
+            assert_eq!(duplicate.severity, Some(DiagnosticSeverity::WARNING));
+
+            let related = duplicate.related_information.as_deref().unwrap();
+            assert_eq!(related[0].message, "first defined here");
+            assert_eq!(related[0].location.range.start, LspPosition::new(0, 6));
+        });
+    }
+}
@@ -4,6 +4,7 @@ mod convert;
 mod def;
 mod module;
 mod package;
+mod site;
 
 use tinymist_std::path::unix_slash;
 use typst::syntax::FileId;
@@ -12,6 +13,7 @@ pub(crate) use convert::convert_docs;
 pub(crate) use def::*;
 pub use module::*;
 pub use package::*;
+pub use site::*;
 pub use tinymist_analysis::docs::*;
 
 fn file_id_repr(fid: FileId) -> String {
@@ -338,7 +338,7 @@ struct ConvertResult {
     errors: Vec<String>,
 }
 
-fn remove_list_annotations(s: &str) -> String {
+pub(super) fn remove_list_annotations(s: &str) -> String {
     let s = s.to_string();
     static REG: std::sync::LazyLock<regex::Regex> = std::sync::LazyLock::new(|| {
         regex::Regex::new(r"<!-- typlite:(?:begin|end):[\w\-]+ \d+ -->").unwrap()
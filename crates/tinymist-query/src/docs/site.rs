@@ -0,0 +1,654 @@
+//! Static documentation site of a package.
+
+use core::fmt::Write;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use ecow::EcoString;
+use pulldown_cmark::{CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd};
+use serde::Serialize;
+use typst::diag::{eco_format, StrResult};
+use typst::syntax::package::PackageManifest;
+use typst::syntax::FileId;
+
+use super::package::remove_list_annotations;
+use crate::analysis::SharedContext;
+use crate::docs::{
+    convert_docs, file_id_repr, module_docs, DefDocs, DefInfo, PackageDefInfo, ParamDocs,
+};
+use crate::package::{get_manifest_id, PackageInfo};
+use crate::syntax::DefKind;
+use crate::LocalContext;
+
+/// A static documentation site, which can be hosted by any static file server.
+#[derive(Debug, Default)]
+pub struct DocsSite {
+    /// The files of the site, mapped from their paths relative to the root of
+    /// the site.
+    pub files: BTreeMap<String, String>,
+}
+
+/// Generate a static documentation site for a package, which contains one
+/// page per module and definition and a search index.
+pub fn package_site(ctx: &mut LocalContext, spec: &PackageInfo) -> StrResult<DocsSite> {
+    log::info!("generate_site_docs {spec:?}");
+
+    let toml_id = get_manifest_id(spec)?;
+    let manifest = ctx.get_manifest(toml_id)?;
+
+    let for_spec = toml_id.package().unwrap();
+    let entry_point = toml_id.join(&manifest.package.entrypoint);
+
+    ctx.preload_package(entry_point);
+
+    let PackageDefInfo { root, module_uses } = module_docs(ctx, entry_point)?;
+
+    let mut primary_aka_cache = HashMap::<FileId, EcoString>::new();
+    let mut primary = |fid: FileId| {
+        primary_aka_cache
+            .entry(fid)
+            .or_insert_with(|| {
+                let aka = module_uses.get(&file_id_repr(fid));
+                aka.and_then(|aka| aka.first())
+                    .cloned()
+                    .unwrap_or_default()
+                    .into()
+            })
+            .clone()
+    };
+
+    // Collects the modules in the same order as the markdown docs.
+    let mut modules = vec![];
+    let mut modules_to_generate = vec![root];
+    let mut generated_modules = HashSet::new();
+    while !modules_to_generate.is_empty() {
+        for mut def in std::mem::take(&mut modules_to_generate) {
+            let fid = def.decl.as_ref().and_then(|decl| decl.file_id());
+            let module_primary = fid.map(&mut primary).unwrap_or_default();
+
+            let mut items = vec![];
+            for mut child in std::mem::take(&mut def.children) {
+                let child_fid = child.decl.as_ref().and_then(|decl| decl.file_id());
+                let children = std::mem::take(&mut child.children);
+
+                let page = if matches!(child.kind, DefKind::Module) {
+                    match child_fid {
+                        Some(fid) if fid.package() == Some(for_spec) => {
+                            if !children.is_empty() && generated_modules.insert(fid) {
+                                let mut module = child.clone();
+                                module.children = children;
+                                modules_to_generate.push(module);
+                            }
+                            Some(module_page(&primary(fid)))
+                        }
+                        _ => None,
+                    }
+                } else if child.is_external {
+                    match child_fid {
+                        Some(fid) if fid.package() == Some(for_spec) => {
+                            Some(def_page(&primary(fid), child.kind, &child.name))
+                        }
+                        Some(fid) => fid.package().map(|spec| {
+                            format!(
+                                "https://typst.app/universe/package/{}/{}",
+                                spec.name, spec.version
+                            )
+                        }),
+                        None => Some("https://typst.app/docs".into()),
+                    }
+                } else {
+                    Some(def_page(&module_primary, child.kind, &child.name))
+                };
+
+                items.push(SiteItem { def: child, page });
+            }
+
+            modules.push(SiteModule {
+                page: module_page(&module_primary),
+                primary: module_primary,
+                def,
+                items,
+            });
+        }
+    }
+
+    let mut site = SiteWriter {
+        ctx: ctx.shared_(),
+        title: eco_format!("{}/{}:{}", spec.namespace, spec.name, spec.version),
+        manifest,
+        links: HashMap::new(),
+        files: BTreeMap::new(),
+    };
+    site.collect_links(&modules);
+    for module in &modules {
+        site.module(module);
+    }
+    site.search_index(&modules);
+    site.files.insert("style.css".into(), STYLE.into());
+    site.files.insert("search.js".into(), SEARCH_SCRIPT.into());
+
+    Ok(DocsSite { files: site.files })
+}
+
+/// A module to be documented.
+struct SiteModule {
+    /// The primary path of the module, which is empty for the root module.
+    primary: EcoString,
+    /// The page of the module.
+    page: String,
+    /// The definition of the module.
+    def: DefInfo,
+    /// The items exported by the module.
+    items: Vec<SiteItem>,
+}
+
+/// An item exported by a module.
+struct SiteItem {
+    /// The definition of the item.
+    def: DefInfo,
+    /// The page documenting the item, which may be an external link.
+    page: Option<String>,
+}
+
+impl SiteItem {
+    /// Whether the item is documented by a page of its own.
+    fn has_own_page(&self) -> bool {
+        !self.def.is_external && !matches!(self.def.kind, DefKind::Module)
+    }
+
+    fn oneliner(&self) -> &str {
+        let docs = self.def.docs.as_deref();
+        let docs = docs.or(self.def.oneliner.as_deref()).unwrap_or_default();
+        docs.lines().next().unwrap_or_default()
+    }
+}
+
+struct SiteWriter {
+    /// The context to render examples.
+    ctx: Arc<SharedContext>,
+    title: EcoString,
+    manifest: PackageManifest,
+    /// The pages of the definitions, used to cross-link definitions.
+    links: HashMap<EcoString, String>,
+    files: BTreeMap<String, String>,
+}
+
+impl SiteWriter {
+    fn collect_links(&mut self, modules: &[SiteModule]) {
+        for item in modules.iter().flat_map(|module| &module.items) {
+            if let Some(page) = item.page.as_ref().filter(|_| item.has_own_page()) {
+                self.links
+                    .entry(item.def.name.clone())
+                    .or_insert_with(|| page.clone());
+            }
+        }
+    }
+
+    fn module(&mut self, module: &SiteModule) {
+        let mut body = String::new();
+
+        if module.primary.is_empty() {
+            let _ = writeln!(body, "<h1>{}</h1>", escape(&self.title));
+            let package = &self.manifest.package;
+            if let Some(description) = &package.description {
+                let _ = writeln!(body, "<p>{}</p>", escape(description));
+            }
+            if let Some(repository) = &package.repository {
+                let repository = escape(repository);
+                let _ = writeln!(body, "<p><a href=\"{repository}\">{repository}</a></p>");
+            }
+        } else {
+            let _ = writeln!(
+                body,
+                "<h1>Module <code>{}</code></h1>",
+                escape(&module.primary)
+            );
+        }
+
+        if let Some(docs) = module.def.docs.as_deref() {
+            body.push_str(&self.markdown(docs));
+        }
+
+        let sections: [(&str, fn(DefKind) -> bool); 4] = [
+            ("Modules", |kind| matches!(kind, DefKind::Module)),
+            ("Functions", |kind| matches!(kind, DefKind::Function)),
+            ("Variables", |kind| matches!(kind, DefKind::Variable)),
+            ("Constants", |kind| {
+                matches!(
+                    kind,
+                    DefKind::Constant | DefKind::Struct | DefKind::Reference
+                )
+            }),
+        ];
+        for (heading, is_kind) in sections {
+            let items = module.items.iter().filter(|item| is_kind(item.def.kind));
+            let mut items = items.peekable();
+            if items.peek().is_none() {
+                continue;
+            }
+
+            let _ = writeln!(body, "<h2>{heading}</h2>\n<table class=\"items\">");
+            for item in items {
+                let name = escape(&item.def.name);
+                let name = match &item.page {
+                    Some(page) => format!("<a href=\"{}\">{name}</a>", escape(page)),
+                    None => name,
+                };
+                let _ = writeln!(
+                    body,
+                    "<tr><td><code>{name}</code></td><td>{}</td></tr>",
+                    self.inline_markdown(item.oneliner())
+                );
+            }
+            body.push_str("</table>\n");
+        }
+
+        for item in module.items.iter().filter(|item| item.has_own_page()) {
+            if let Some(page) = &item.page {
+                let content = self.def(&module.primary, item);
+                self.files.insert(page.clone(), content);
+            }
+        }
+
+        let title = if module.primary.is_empty() {
+            self.title.to_string()
+        } else {
+            format!("{} - {}", module.primary, self.title)
+        };
+        let page = self.layout(&title, &body);
+        self.files.insert(module.page.clone(), page);
+    }
+
+    fn def(&self, primary: &str, item: &SiteItem) -> String {
+        let def = &item.def;
+        let path = if primary.is_empty() {
+            def.name.to_string()
+        } else {
+            format!("{primary}.{}", def.name)
+        };
+
+        let mut body = String::new();
+        let _ = writeln!(
+            body,
+            "<h1>{} <code>{}</code></h1>",
+            capitalize(&def.kind.to_string()),
+            escape(&path)
+        );
+
+        match &def.parsed_docs {
+            Some(DefDocs::Function(sig)) => {
+                let mut sig_repr = format!("let {}", def.name);
+                let _ = sig.print(&mut sig_repr);
+                let _ = writeln!(
+                    body,
+                    "<pre class=\"signature\"><code class=\"language-typc\">{};</code></pre>",
+                    escape(&sig_repr)
+                );
+                body.push_str(&self.markdown(&sig.docs));
+
+                let params = sig.pos.iter().chain(sig.named.values());
+                let params = params.chain(sig.rest.as_ref()).collect::<Vec<_>>();
+                if !params.is_empty() {
+                    body.push_str("<h2>Parameters</h2>\n<table class=\"params\">\n");
+                    body.push_str(
+                        "<tr><th>Name</th><th>Type</th><th>Default</th><th>Description</th></tr>\n",
+                    );
+                    for param in params {
+                        body.push_str(&self.param(param));
+                    }
+                    body.push_str("</table>\n");
+                }
+
+                if let Some((short, _, _)) = &sig.ret_ty {
+                    let _ = writeln!(body, "<h2>Returns</h2>\n<p>{}</p>", self.ty(short));
+                }
+            }
+            Some(DefDocs::Variable(var)) => {
+                if let Some((short, _, _)) = &var.return_ty {
+                    let _ = writeln!(body, "<p>Type: {}</p>", self.ty(short));
+                }
+                body.push_str(&self.markdown(&var.docs));
+            }
+            Some(docs) => body.push_str(&self.markdown(docs.docs())),
+            None => {
+                if let Some(docs) = def.docs.as_deref() {
+                    body.push_str(&self.markdown(docs));
+                }
+            }
+        }
+
+        if let Some(constant) = &def.constant {
+            let _ = writeln!(
+                body,
+                "<h2>Value</h2>\n<pre><code class=\"language-typc\">{}</code></pre>",
+                escape(constant)
+            );
+        }
+
+        self.layout(&format!("{path} - {}", self.title), &body)
+    }
+
+    fn param(&self, param: &ParamDocs) -> String {
+        let mut name = escape(&param.name);
+        if param.attrs.variadic {
+            name = format!("..{name}");
+        }
+        let ty = match &param.cano_type {
+            Some((short, _, _)) => self.ty(short),
+            None => "<code>any</code>".into(),
+        };
+        let default = match &param.default {
+            Some(default) => format!("<code>{}</code>", escape(default)),
+            None => String::new(),
+        };
+        let docs = self.markdown(&param.docs);
+
+        format!(
+            "<tr><td><code>{name}</code></td><td>{ty}</td><td>{default}</td><td>{docs}</td></tr>\n"
+        )
+    }
+
+    /// Renders a type, linking builtin types to the typst reference and
+    /// package definitions to their pages.
+    fn ty(&self, ty: &str) -> String {
+        ty.split(" | ")
+            .map(|part| {
+                let part = part.trim();
+                let link = builtin_type_link(part)
+                    .map(|path| format!("https://typst.app/docs/reference/{path}/{part}/"))
+                    .or_else(|| self.links.get(part).cloned());
+                match link {
+                    Some(link) => format!(
+                        "<a href=\"{}\"><code>{}</code></a>",
+                        escape(&link),
+                        escape(part)
+                    ),
+                    None => format!("<code>{}</code>", escape(part)),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+
+    /// Renders markdown documentation, linking code spans that name a
+    /// definition of the package to its page.
+    fn markdown(&self, docs: &str) -> String {
+        let docs = remove_list_annotations(docs);
+        let parser = Parser::new_ext(&docs, Options::ENABLE_TABLES);
+
+        let mut events = vec![];
+        let mut example: Option<String> = None;
+        for event in parser {
+            if let Some(code) = example.as_mut() {
+                match event {
+                    Event::Text(text) => code.push_str(&text),
+                    Event::End(TagEnd::CodeBlock) => {
+                        let html = self.example(&std::mem::take(code));
+                        events.push(Event::Html(CowStr::from(html)));
+                        example = None;
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            let event = match event {
+                Event::Code(code) => {
+                    let name = code.trim_end_matches("()");
+                    match self.links.get(name) {
+                        Some(page) => Event::InlineHtml(CowStr::from(format!(
+                            "<a href=\"{}\"><code>{}</code></a>",
+                            escape(page),
+                            escape(&code)
+                        ))),
+                        None => Event::Code(code),
+                    }
+                }
+                // Examples are rendered along with their code.
+                Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(lang)))
+                    if lang.as_ref() == "example" =>
+                {
+                    example = Some(String::new());
+                    continue;
+                }
+                event => event,
+            };
+            events.push(event);
+        }
+
+        let mut html = String::new();
+        pulldown_cmark::html::push_html(&mut html, events.into_iter());
+        html
+    }
+
+    /// Renders an example, i.e. its code and the document compiled from the
+    /// code, in the same way as the documentation shown in hover.
+    fn example(&self, code: &str) -> String {
+        let max_ticks = code
+            .split(|c| c != '`')
+            .map(str::len)
+            .max()
+            .unwrap_or_default();
+        let fence = "`".repeat(max_ticks.max(2) + 1);
+        let source = format!("#example({fence}typ\n{code}\n{fence})");
+
+        match convert_docs(&self.ctx, &source) {
+            Ok(converted) => {
+                let mut html = String::from("<div class=\"example\">\n");
+                let parser = Parser::new_ext(&converted, Options::ENABLE_TABLES);
+                pulldown_cmark::html::push_html(&mut html, parser);
+                html.push_str("</div>\n");
+                html
+            }
+            Err(err) => {
+                log::warn!("failed to render example: {err}");
+                format!(
+                    "<pre><code class=\"language-typ\">{}</code></pre>\n",
+                    escape(code)
+                )
+            }
+        }
+    }
+
+    /// Renders a single line of markdown documentation without the wrapping
+    /// paragraph.
+    fn inline_markdown(&self, docs: &str) -> String {
+        let html = self.markdown(docs);
+        let html = html.trim();
+        let html = html.strip_prefix("<p>").unwrap_or(html);
+        html.strip_suffix("</p>").unwrap_or(html).to_owned()
+    }
+
+    fn search_index(&mut self, modules: &[SiteModule]) {
+        #[derive(Serialize)]
+        struct SearchItem<'a> {
+            name: &'a str,
+            kind: DefKind,
+            module: &'a str,
+            page: &'a str,
+            desc: &'a str,
+        }
+
+        let items = modules
+            .iter()
+            .flat_map(|module| module.items.iter().map(move |item| (module, item)))
+            .filter(|(_, item)| item.has_own_page())
+            .filter_map(|(module, item)| {
+                Some(SearchItem {
+                    name: &item.def.name,
+                    kind: item.def.kind,
+                    module: &module.primary,
+                    page: item.page.as_deref()?,
+                    desc: item.oneliner(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let index = serde_json::to_string(&items).unwrap();
+        let index = format!("window.searchIndex = {index};\n");
+        self.files.insert("search-index.js".into(), index);
+    }
+
+    fn layout(&self, title: &str, body: &str) -> String {
+        let package = escape(&self.title);
+        let title = escape(title);
+        format!(
+            r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<link rel="stylesheet" href="style.css">
+<script defer src="search-index.js"></script>
+<script defer src="search.js"></script>
+</head>
+<body>
+<header><a href="index.html">{package}</a><input id="search" type="search" placeholder="Search..." autocomplete="off"></header>
+<ul id="search-results" hidden></ul>
+<main>
+{body}</main>
+</body>
+</html>
+"#
+        )
+    }
+}
+
+/// The page of a module, where the root module is the index page.
+fn module_page(primary: &str) -> String {
+    if primary.is_empty() {
+        "index.html".into()
+    } else {
+        format!("module.{}.html", page_name(primary))
+    }
+}
+
+/// The page of a definition, e.g. `function.utils.draw.html`.
+fn def_page(primary: &str, kind: DefKind, name: &str) -> String {
+    if primary.is_empty() {
+        format!("{kind}.{}.html", page_name(name))
+    } else {
+        format!("{kind}.{}.{}.html", page_name(primary), page_name(name))
+    }
+}
+
+fn page_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            c if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+/// The category of a builtin type in the typst reference.
+fn builtin_type_link(ty: &str) -> Option<&'static str> {
+    Some(match ty {
+        "none" | "auto" | "bool" | "int" | "float" | "str" | "content" | "array" | "dictionary"
+        | "function" | "bytes" | "label" | "regex" | "version" | "datetime" | "duration"
+        | "type" | "module" | "arguments" | "selector" | "decimal" => "foundations",
+        "length" | "ratio" | "relative" | "fraction" | "angle" | "alignment" | "direction" => {
+            "layout"
+        }
+        "color" | "gradient" | "stroke" | "tiling" => "visualize",
+        _ => return None,
+    })
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+const STYLE: &str = r#"body { margin: 0; font-family: sans-serif; line-height: 1.5; }
+header { display: flex; gap: 1em; align-items: center; padding: 0.5em 1em; border-bottom: 1px solid #ddd; }
+header a { font-weight: bold; color: inherit; text-decoration: none; }
+#search { flex: 1; max-width: 30em; padding: 0.3em; }
+main, #search-results { max-width: 60em; margin: 0 auto; padding: 0 1em; }
+pre { background: #f5f5f5; padding: 0.5em; overflow-x: auto; }
+table { border-collapse: collapse; }
+td, th { border-bottom: 1px solid #eee; padding: 0.3em 0.6em; text-align: left; vertical-align: top; }
+td p { margin: 0; }
+.example img { max-width: 100%; border: 1px solid #eee; }
+"#;
+
+const SEARCH_SCRIPT: &str = r#"(function () {
+  const input = document.getElementById("search");
+  const results = document.getElementById("search-results");
+  const main = document.querySelector("main");
+  input.addEventListener("input", function () {
+    const query = input.value.trim().toLowerCase();
+    results.innerHTML = "";
+    results.hidden = !query;
+    main.hidden = !!query;
+    if (!query) return;
+    for (const item of window.searchIndex || []) {
+      const path = item.module ? item.module + "." + item.name : item.name;
+      if (!path.toLowerCase().includes(query) && !item.desc.toLowerCase().includes(query)) continue;
+      const li = document.createElement("li");
+      const a = document.createElement("a");
+      a.href = item.page;
+      a.textContent = path;
+      li.append(a, " (" + item.kind + ") " + item.desc);
+      results.append(li);
+    }
+  });
+})();
+"#;
+
+#[cfg(test)]
+mod tests {
+    use tinymist_world::package::{PackageRegistry, PackageSpec};
+
+    use super::{package_site, PackageInfo};
+    use crate::tests::*;
+
+    #[test]
+    fn tidy() {
+        let pkg = PackageSpec {
+            namespace: "preview".into(),
+            name: "tidy".into(),
+            version: "0.3.0".parse().unwrap(),
+        };
+        run_with_sources("", |verse: &mut LspUniverse, path| {
+            let pkg_root = verse.registry.resolve(&pkg).unwrap();
+            let pi = PackageInfo {
+                path: pkg_root.as_ref().to_owned(),
+                namespace: pkg.namespace,
+                name: pkg.name,
+                version: pkg.version.to_string(),
+            };
+            run_with_ctx(verse, path, &|a, _p| {
+                let site = package_site(a, &pi).unwrap();
+                assert!(site.files.contains_key("index.html"));
+                assert!(site.files.contains_key("search-index.js"));
+
+                let dest = format!("../../target/{}-{}-{}", pi.namespace, pi.name, pi.version);
+                for (path, content) in site.files {
+                    let path = std::path::Path::new(&dest).join(path);
+                    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                    std::fs::write(path, content).unwrap();
+                }
+            })
+        })
+    }
+}
@@ -0,0 +1,17 @@
+/// path: refs.bib
+@book{knuth,
+  author = {Donald Knuth},
+  title = {The Art of Computer Programming},
+  year = {1968},
+}
+-----
+/// path: prev.typ
+@knuth
+
+#bibliography("refs.bib")
+-----
+/// compile: prev.typ
+
+@knuth @missing
+
+#bibliography("refs.bib")
@@ -0,0 +1,17 @@
+/// path: refs.bib
+@book{knuth,
+  author = {Donald Knuth},
+  title = {The Art of Computer Programming},
+  year = {1968},
+}
+@book{turing,
+  author = {Alan Turing},
+  title = {On Computable Numbers},
+  year = {1936},
+}
+-----
+/// compile: true
+
+@knuth
+
+#bibliography("refs.bib", full: true)
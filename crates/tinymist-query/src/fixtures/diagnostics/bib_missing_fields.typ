@@ -0,0 +1,11 @@
+/// path: refs.bib
+@article{knuth,
+  author = {Donald Knuth},
+  title = {Literate Programming},
+}
+-----
+/// compile: true
+
+@knuth
+
+#bibliography("refs.bib")
@@ -0,0 +1,14 @@
+/// path: refs.bib
+@book{knuth,
+  author = {Donald Knuth},
+  title = {The Art of Computer Programming},
+  year = {1968},
+}
+-----
+/// compile: true
+
+#let unused() = [@missing]
+
+@knuth
+
+#bibliography("refs.bib")
@@ -0,0 +1,13 @@
+/// path: references.bib
+@article{Russell:1908,
+Author = {Bertand Russell},
+Journal = {American Journal of Mathematics},
+Title = {Mathematical logic based on the theory of types},
+Year = 1908}
+
+-----
+/// compile: true
+
+/* position after */@Russell:1908
+
+#bibliography("references.bib")
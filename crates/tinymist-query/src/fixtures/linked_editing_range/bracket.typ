@@ -0,0 +1 @@
+#let x = /* position after */[a *b*]
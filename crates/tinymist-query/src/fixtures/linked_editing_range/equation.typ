@@ -0,0 +1 @@
+/* position after */$x + y$
@@ -0,0 +1,4 @@
+#let /* ident after */f(x) = x + 1
+#f(1) #f(2)
+#let g = f
+#let h(f) = f
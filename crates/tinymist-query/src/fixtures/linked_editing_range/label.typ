@@ -0,0 +1,4 @@
+#figure([A], caption: [B]) <fig:a> /* position */
+
+See @fig:a and @fig:a[Figure].
+<fig:b> @fig:b
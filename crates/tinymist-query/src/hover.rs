@@ -4,7 +4,7 @@ use tinymist_std::typst::TypstDocument;
 use typst::foundations::repr::separated_list;
 use typst_shim::syntax::LinkedNodeExt;
 
-use crate::analysis::get_link_exprs_in;
+use crate::analysis::{bib_style, find_bib, get_link_exprs_in, input_key_at};
 use crate::jump_from_cursor;
 use crate::prelude::*;
 use crate::upstream::{route_of_value, truncated_repr, Tooltip};
@@ -99,7 +99,8 @@ impl HoverWorker<'_> {
         let source = self.source.clone();
         let leaf = LinkedNode::new(source.root()).leaf_at_compat(self.cursor)?;
 
-        self.definition()
+        self.sys_input(&leaf)
+            .or_else(|| self.definition())
             .or_else(|| self.star(&leaf))
             .or_else(|| self.link(&leaf))
     }
@@ -133,6 +134,9 @@ impl HoverWorker<'_> {
             }
             BibEntry(..) => {
                 self.def.push(format!("Bibliography: @{}", def.name()));
+                if let Some(reference) = self.bib_reference(def.name()) {
+                    self.docs.push(reference);
+                }
             }
             _ => {
                 let sym_docs = self.ctx.def_docs(&def);
@@ -179,6 +183,31 @@ impl HoverWorker<'_> {
         Some(())
     }
 
+    /// Renders a bibliography entry in the citation style of the document.
+    fn bib_reference(&self, key: &str) -> Option<String> {
+        let doc = self.doc.as_ref()?;
+        let (bib_elem, bib_info) = find_bib(&self.ctx.world, doc.introspector())?;
+        let style = bib_style(&bib_elem);
+        bib_info.render(key, &style)
+    }
+
+    /// Shows the current value of a key read from `sys.inputs`.
+    fn sys_input(&mut self, leaf: &LinkedNode) -> Option<()> {
+        let key = input_key_at(leaf)?;
+        self.def.push(format!("Input: `{key}`"));
+
+        let inputs = self.ctx.world().inputs();
+        match inputs.get(&key) {
+            Ok(value) => {
+                let value = truncated_repr(value);
+                self.value.push(format!("```typc\n{value}\n```"));
+            }
+            Err(_) => self.value.push("Not supplied by the inputs.".into()),
+        }
+
+        Some(())
+    }
+
     fn star(&mut self, mut node: &LinkedNode) -> Option<()> {
         if !matches!(node.kind(), SyntaxKind::Star) {
             return None;
@@ -401,8 +430,10 @@ mod tests {
                 position: find_test_position(&source),
             };
 
-            let snap = WorldComputeGraph::from_world(ctx.world.clone());
-            let result = request.request(ctx, snap);
+            let docs = find_module_level_docs(&source).unwrap_or_default();
+            let properties = get_test_properties(&docs);
+            let graph = compile_doc_for_test(ctx, &properties);
+            let result = request.request(ctx, graph);
             assert_snapshot!(JsonRepr::new_redacted(result, &REDACT_LOC));
         });
     }
@@ -8,7 +8,7 @@
 //! + language queries defined by the [Language Server Protocol](https://microsoft.github.io/language-server-protocol/).
 
 pub use analysis::{CompletionFeat, LocalContext, LocalContextGuard, LspWorldExt};
-pub use completion::{CompletionRequest, PostfixSnippet};
+pub use completion::{CompletionRequest, PostfixSnippet, SnippetLibrary};
 pub use typlite::ColorTheme;
 pub use upstream::with_vm;
 
@@ -28,6 +28,7 @@ pub use goto_definition::*;
 pub use hover::*;
 pub use inlay_hint::*;
 pub use jump::*;
+pub use linked_editing_range::*;
 pub use lsp_typst_boundary::*;
 pub use on_enter::*;
 pub use prepare_rename::*;
@@ -72,6 +73,7 @@ mod goto_definition;
 mod hover;
 mod inlay_hint;
 mod jump;
+mod linked_editing_range;
 mod on_enter;
 mod prepare_rename;
 mod references;
@@ -127,7 +129,7 @@ pub trait StatefulRequest {
 #[allow(missing_docs)]
 mod polymorphic {
     use completion::CompletionList;
-    use lsp_types::TextEdit;
+    use lsp_types::{LinkedEditingRanges, TextEdit};
     use serde::{Deserialize, Serialize};
     use tinymist_project::ProjectTask;
     use typst::foundations::Dict;
@@ -182,6 +184,7 @@ mod polymorphic {
         DocumentColor(DocumentColorRequest),
         DocumentLink(DocumentLinkRequest),
         DocumentHighlight(DocumentHighlightRequest),
+        LinkedEditingRange(LinkedEditingRangeRequest),
         ColorPresentation(ColorPresentationRequest),
         CodeAction(CodeActionRequest),
         CodeLens(CodeLensRequest),
@@ -219,6 +222,7 @@ mod polymorphic {
                 Self::DocumentColor(..) => PinnedFirst,
                 Self::DocumentLink(..) => PinnedFirst,
                 Self::DocumentHighlight(..) => PinnedFirst,
+                Self::LinkedEditingRange(..) => PinnedFirst,
                 Self::ColorPresentation(..) => ContextFreeUnique,
                 Self::CodeAction(..) => Unique,
                 Self::CodeLens(..) => Unique,
@@ -255,6 +259,7 @@ mod polymorphic {
                 Self::DocumentColor(req) => &req.path,
                 Self::DocumentLink(req) => &req.path,
                 Self::DocumentHighlight(req) => &req.path,
+                Self::LinkedEditingRange(req) => &req.path,
                 Self::ColorPresentation(req) => &req.path,
                 Self::CodeAction(req) => &req.path,
                 Self::CodeLens(req) => &req.path,
@@ -293,6 +298,7 @@ mod polymorphic {
         DocumentColor(Option<Vec<ColorInformation>>),
         DocumentLink(Option<Vec<DocumentLink>>),
         DocumentHighlight(Option<Vec<DocumentHighlight>>),
+        LinkedEditingRange(Option<LinkedEditingRanges>),
         ColorPresentation(Option<Vec<ColorPresentation>>),
         CodeAction(Option<Vec<CodeActionOrCommand>>),
         CodeLens(Option<Vec<CodeLens>>),
@@ -0,0 +1,78 @@
+use lsp_types::LinkedEditingRanges;
+
+use crate::{analysis::doc_highlight::DocumentHighlightWorker, prelude::*, SemanticRequest};
+
+/// The [`textDocument/linkedEditingRange`] request is sent from the client to
+/// the server to return for a given position in a document the range of the
+/// symbol at the position and all ranges that have the same content.
+///
+/// The ranges are edited together, i.e. a label and its references in the
+/// same file, a local definition and its uses, or the pair of dollars of an
+/// equation.
+///
+/// [`textDocument/linkedEditingRange`]: https://microsoft.github.io/language-server-protocol/specification#textDocument_linkedEditingRange
+#[derive(Debug, Clone)]
+pub struct LinkedEditingRangeRequest {
+    /// The path of the document to request linked editing ranges for.
+    pub path: PathBuf,
+    /// The position of the document to request linked editing ranges for.
+    pub position: LspPosition,
+}
+
+impl SemanticRequest for LinkedEditingRangeRequest {
+    type Response = LinkedEditingRanges;
+
+    fn request(self, ctx: &mut LocalContext) -> Option<Self::Response> {
+        let source = ctx.source_by_path(&self.path).ok()?;
+        let cursor = ctx.to_typst_pos(self.position, &source)?;
+
+        // Prefers the node after the cursor, e.g. `|name`, and falls back to the
+        // node before the cursor, e.g. `name|`.
+        let root = LinkedNode::new(source.root());
+        let nodes = [root.leaf_at_compat(cursor + 1), root.leaf_at_compat(cursor)];
+
+        let mut worker = DocumentHighlightWorker::new(ctx, &source);
+        for node in nodes.iter().flatten() {
+            worker.annotated.clear();
+            if worker.work_linked(node).is_some() && worker.annotated.len() > 1 {
+                let ranges = worker.annotated.iter().map(|it| it.range).collect();
+                return Some(LinkedEditingRanges {
+                    ranges,
+                    word_pattern: None,
+                });
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::*;
+
+    #[test]
+    fn test() {
+        snapshot_testing("linked_editing_range", &|ctx, path| {
+            let source = ctx.source_by_path(&path).unwrap();
+
+            let request = LinkedEditingRangeRequest {
+                path: path.clone(),
+                position: find_test_position(&source),
+            };
+
+            let result = request.request(ctx).map(|res| {
+                let range = |range: LspRange| {
+                    let (start, end) = (range.start, range.end);
+                    format!(
+                        "{}:{}:{}:{}",
+                        start.line, start.character, end.line, end.character
+                    )
+                };
+                res.ranges.into_iter().map(range).collect::<Vec<_>>()
+            });
+            assert_snapshot!(JsonRepr::new_pure(result));
+        });
+    }
+}
@@ -37,6 +37,16 @@ impl StatefulRequest for PrepareRenameRequest {
     fn request(self, ctx: &mut LocalContext, graph: LspComputeGraph) -> Option<Self::Response> {
         let doc = graph.snap.success_doc.as_ref();
         let source = ctx.source_by_path(&self.path).ok()?;
+        if let Some(def) = ctx.def_of_bib_entry(&source, doc, self.position) {
+            let (name, _) = prepare_bib_renaming(&def)?;
+            let range = ctx.to_lsp_range_(def.name_range(ctx.shared())?, def.file_id()?)?;
+
+            return Some(PrepareRenameResponse::RangeWithPlaceholder {
+                range,
+                placeholder: name,
+            });
+        }
+
         let syntax = ctx.classify_for_decl(&source, self.position)?;
         if matches!(syntax.node().kind(), SyntaxKind::FieldAccess) {
             // todo: rename field access
@@ -61,15 +71,7 @@ pub(crate) fn prepare_renaming(
     def: &Definition,
 ) -> Option<(String, Option<LspRange>)> {
     let name = def.name().clone();
-    let def_fid = def.file_id()?;
-
-    if WorkspaceResolver::is_package_file(def_fid) {
-        crate::log_debug_ct!(
-            "prepare_rename: {name} is in a package {pkg:?}",
-            pkg = def_fid.package(),
-        );
-        return None;
-    }
+    check_renaming_file(def)?;
 
     let var_rename = || Some((name.to_string(), None));
 
@@ -91,8 +93,9 @@ pub(crate) fn prepare_renaming(
             let name = path.get().to_string();
             Some((name, None))
         }
-        // todo: label renaming, bibkey renaming
-        BibEntry(..) | Label(..) | ContentRef(..) => None,
+        BibEntry(..) => var_rename(),
+        // todo: label renaming
+        Label(..) | ContentRef(..) => None,
         ImportAlias(..) | Constant(..) | IdentRef(..) | Import(..) | StrName(..) | Spread(..) => {
             None
         }
@@ -100,6 +103,27 @@ pub(crate) fn prepare_renaming(
     }
 }
 
+/// Prepares renaming a bibliography entry from the bibliography file.
+pub(crate) fn prepare_bib_renaming(def: &Definition) -> Option<(String, Option<LspRange>)> {
+    check_renaming_file(def)?;
+    matches!(def.decl.as_ref(), Decl::BibEntry(..)).then(|| (def.name().to_string(), None))
+}
+
+fn check_renaming_file(def: &Definition) -> Option<()> {
+    let def_fid = def.file_id()?;
+
+    if WorkspaceResolver::is_package_file(def_fid) {
+        crate::log_debug_ct!(
+            "prepare_rename: {name} is in a package {pkg:?}",
+            name = def.name(),
+            pkg = def_fid.package(),
+        );
+        return None;
+    }
+
+    Some(())
+}
+
 fn validate_fn_renaming(def: &Definition) -> Option<()> {
     use typst::foundations::func::Repr;
     let value = def.value();
@@ -30,9 +30,13 @@ impl StatefulRequest for ReferencesRequest {
     fn request(self, ctx: &mut LocalContext, graph: LspComputeGraph) -> Option<Self::Response> {
         let doc = graph.snap.success_doc.as_ref();
         let source = ctx.source_by_path(&self.path).ok()?;
-        let syntax = ctx.classify_for_decl(&source, self.position)?;
-
-        let locations = find_references(ctx, &source, doc, syntax)?;
+        let locations = match ctx.def_of_bib_entry(&source, doc, self.position) {
+            Some(def) => find_bib_references(ctx, def)?,
+            None => {
+                let syntax = ctx.classify_for_decl(&source, self.position)?;
+                find_references(ctx, &source, doc, syntax)?
+            }
+        };
 
         crate::log_debug_ct!("references: {locations:?}");
         Some(locations)
@@ -54,14 +58,11 @@ pub(crate) fn find_references(
     };
 
     let def = ctx.def_of_syntax(source, doc, syntax)?;
+    if matches!(def.decl.as_ref(), Decl::BibEntry(..)) {
+        return find_bib_references(ctx, def);
+    }
 
-    let worker = ReferencesWorker {
-        ctx: ctx.fork_for_search(),
-        references: vec![],
-        def,
-        module_path: OnceLock::new(),
-    };
-
+    let worker = ReferencesWorker::new(ctx, def);
     if finding_label {
         worker.label_root()
     } else {
@@ -70,6 +71,15 @@ pub(crate) fn find_references(
     }
 }
 
+/// Finds the references of a bibliography entry, which includes the key in
+/// the bibliography file and the citations in the document.
+pub(crate) fn find_bib_references(
+    ctx: &mut LocalContext,
+    def: Definition,
+) -> Option<Vec<LspLocation>> {
+    ReferencesWorker::new(ctx, def).bib_root()
+}
+
 struct ReferencesWorker<'a> {
     ctx: SearchCtx<'a>,
     references: Vec<LspLocation>,
@@ -77,9 +87,40 @@ struct ReferencesWorker<'a> {
     module_path: OnceLock<StrRef>,
 }
 
-impl ReferencesWorker<'_> {
+impl<'a> ReferencesWorker<'a> {
+    fn new(ctx: &'a mut LocalContext, def: Definition) -> Self {
+        Self {
+            ctx: ctx.fork_for_search(),
+            references: vec![],
+            def,
+            module_path: OnceLock::new(),
+        }
+    }
+
+    fn bib_root(mut self) -> Option<Vec<LspLocation>> {
+        let Decl::BibEntry(entry) = self.def.decl.as_ref() else {
+            return None;
+        };
+        let (bib_fid, name_range, _) = entry.at.as_ref();
+        let uri = self.ctx.ctx.uri_for_id(*bib_fid).ok()?;
+        let range = self.ctx.ctx.to_lsp_range_(name_range.clone(), *bib_fid)?;
+        self.references.push(LspLocation { uri, range });
+
+        for ref_fid in self.ctx.ctx.depended_source_files() {
+            if self.ctx.ctx.is_cancelled() {
+                return None;
+            }
+            self.citations(ref_fid);
+        }
+
+        Some(self.references)
+    }
+
     fn label_root(mut self) -> Option<Vec<LspLocation>> {
         for ref_fid in self.ctx.ctx.depended_files() {
+            if self.ctx.ctx.is_cancelled() {
+                return None;
+            }
             self.file(ref_fid)?;
         }
 
@@ -89,6 +130,9 @@ impl ReferencesWorker<'_> {
     fn ident_root(mut self) -> Option<Vec<LspLocation>> {
         self.file(self.def.decl.file_id()?);
         while let Some(ref_fid) = self.ctx.worklist.pop() {
+            if self.ctx.ctx.is_cancelled() {
+                return None;
+            }
             self.file(ref_fid);
         }
 
@@ -128,6 +172,34 @@ impl ReferencesWorker<'_> {
         Some(())
     }
 
+    fn citations(&mut self, ref_fid: TypstFileId) -> Option<()> {
+        let src = self.ctx.ctx.source_by_id(ref_fid).ok()?;
+        let index = get_index_info(&src);
+        let name = self.def.decl.name();
+        let mut citations = index
+            .citations
+            .iter()
+            .filter(|(key, _)| key == name)
+            .peekable();
+        if citations.peek().is_none() {
+            return Some(());
+        }
+
+        let uri = self.ctx.ctx.uri_for_id(ref_fid).ok()?;
+        for (_, span) in citations {
+            let Some(range) = citation_key_range(&src, *span) else {
+                continue;
+            };
+            let range = self.ctx.ctx.to_lsp_range(range, &src);
+            self.references.push(LspLocation {
+                uri: uri.clone(),
+                range,
+            });
+        }
+
+        Some(())
+    }
+
     fn push_idents<'b>(
         &mut self,
         src: &Source,
@@ -166,6 +238,24 @@ impl ReferencesWorker<'_> {
     }
 }
 
+/// Gets the range of the key in a citation node, i.e. `@key`, `<key>` or
+/// `"key"`.
+fn citation_key_range(src: &Source, span: Span) -> Option<Range<usize>> {
+    let range = src.range(span)?;
+    let text = src.get(range.clone())?;
+    match text.as_bytes().first()? {
+        b'@' => {
+            let ref_node = LinkedNode::new(src.root()).find(span)?;
+            let target = ref_node.cast::<ast::Ref>()?.target();
+            Some(range.start + 1..range.start + 1 + target.len())
+        }
+        // Skips strings containing escape sequences.
+        b'"' if text.contains('\\') => None,
+        b'<' | b'"' => Some(range.start + 1..range.end - 1),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -12,9 +12,9 @@ use typst::{
 use crate::adt::interner::Interned;
 use crate::{
     analysis::{get_link_exprs, LinkObject, LinkTarget},
-    find_references,
+    find_bib_references, find_references,
     prelude::*,
-    prepare_renaming,
+    prepare_bib_renaming, prepare_renaming,
     syntax::{first_ancestor_expr, get_index_info, node_ancestors, Decl, RefExpr, SyntaxClass},
 };
 
@@ -40,11 +40,26 @@ impl StatefulRequest for RenameRequest {
         let doc = graph.snap.success_doc.as_ref();
 
         let source = ctx.source_by_path(&self.path).ok()?;
+        if let Some(def) = ctx.def_of_bib_entry(&source, doc, self.position) {
+            prepare_bib_renaming(&def)?;
+            if !is_valid_cite_key(&self.new_name) {
+                log::info!("bad rename: invalid citation key {:?}", self.new_name);
+                return None;
+            }
+
+            let references = find_bib_references(ctx, def)?;
+            return Some(rename_references(references, self.new_name));
+        }
+
         let syntax = ctx.classify_for_decl(&source, self.position)?;
 
         let def = ctx.def_of_syntax(&source, doc, syntax.clone())?;
 
         prepare_renaming(&syntax, &def)?;
+        if matches!(def.decl.as_ref(), Decl::BibEntry(..)) && !is_valid_cite_key(&self.new_name) {
+            log::info!("bad rename: invalid citation key {:?}", self.new_name);
+            return None;
+        }
 
         match syntax {
             // todo: abs path
@@ -95,30 +110,42 @@ impl StatefulRequest for RenameRequest {
             }
             _ => {
                 let references = find_references(ctx, &source, doc, syntax)?;
+                Some(rename_references(references, self.new_name))
+            }
+        }
+    }
+}
 
-                let mut edits = HashMap::new();
+fn rename_references(references: Vec<LspLocation>, new_name: String) -> WorkspaceEdit {
+    let mut edits = HashMap::new();
 
-                for loc in references {
-                    let uri = loc.uri;
-                    let range = loc.range;
-                    let edits = edits.entry(uri).or_insert_with(Vec::new);
-                    edits.push(TextEdit {
-                        range,
-                        new_text: self.new_name.clone(),
-                    });
-                }
+    for loc in references {
+        let uri = loc.uri;
+        let range = loc.range;
+        let edits = edits.entry(uri).or_insert_with(Vec::new);
+        edits.push(TextEdit {
+            range,
+            new_text: new_name.clone(),
+        });
+    }
 
-                log::info!("rename edits: {edits:?}");
+    log::info!("rename edits: {edits:?}");
 
-                Some(WorkspaceEdit {
-                    changes: Some(edits),
-                    ..Default::default()
-                })
-            }
-        }
+    WorkspaceEdit {
+        changes: Some(edits),
+        ..Default::default()
     }
 }
 
+/// Checks whether the name can be used in a citation, i.e. `@name`.
+fn is_valid_cite_key(name: &str) -> bool {
+    !name.is_empty()
+        && !name.ends_with(['.', ':'])
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'))
+}
+
 pub(crate) fn do_rename_file(
     ctx: &mut LocalContext,
     def_fid: TypstFileId,
@@ -156,11 +183,17 @@ impl RenameFileWorker<'_> {
         let dep = self.ctx.module_dependencies().get(&self.def_fid).cloned();
         if let Some(dep) = dep {
             for ref_fid in dep.dependents.iter() {
+                if self.ctx.is_cancelled() {
+                    return None;
+                }
                 self.refs_in_file(*ref_fid, edits);
             }
         }
 
         for ref_fid in self.ctx.source_files().clone() {
+            if self.ctx.is_cancelled() {
+                return None;
+            }
             self.links_in_file(ref_fid, edits);
         }
 
@@ -37,6 +37,9 @@ impl SemanticRequest for SymbolRequest {
         let mut symbols = vec![];
 
         for id in ctx.depended_files() {
+            if ctx.is_cancelled() {
+                return None;
+            }
             let Ok(source) = ctx.source_by_id(id) else {
                 continue;
             };
@@ -2,6 +2,7 @@ use std::str::FromStr;
 
 use rustc_hash::FxHashSet;
 use tinymist_world::package::PackageSpec;
+use typst::syntax::Span;
 
 use crate::{adt::interner::Interned, prelude::*};
 
@@ -10,6 +11,9 @@ pub struct IndexInfo {
     pub(crate) paths: FxHashSet<Interned<str>>,
     pub(crate) packages: FxHashSet<PackageSpec>,
     pub(crate) identifiers: FxHashSet<Interned<str>>,
+    /// The keys referenced by `@key` or `cite(..)`, with the span of the node
+    /// holding the key.
+    pub(crate) citations: Vec<(Interned<str>, Span)>,
 }
 
 #[comemo::memoize]
@@ -56,6 +60,15 @@ impl IndexWorker {
             Some(ast::Expr::Ident(ident)) => {
                 self.info.identifiers.insert(ident.get().into());
             }
+            Some(ast::Expr::Ref(ref_node)) => {
+                let key = ref_node.target().into();
+                self.info.citations.push((key, ref_node.span()));
+            }
+            Some(ast::Expr::FuncCall(call)) => {
+                if let Some(cite) = cite_key(call) {
+                    self.info.citations.push(cite);
+                }
+            }
             _ => {}
         }
 
@@ -64,3 +77,32 @@ impl IndexWorker {
         }
     }
 }
+
+/// Gets the key cited by a `cite(<key>)` or `cite(label("key"))` call.
+pub(crate) fn cite_key(call: ast::FuncCall) -> Option<(Interned<str>, Span)> {
+    let ast::Expr::Ident(callee) = call.callee() else {
+        return None;
+    };
+    if callee.get() != "cite" {
+        return None;
+    }
+
+    let key = call.args().items().find_map(|arg| match arg {
+        ast::Arg::Pos(expr) => Some(expr),
+        _ => None,
+    })?;
+    match key {
+        ast::Expr::Label(label) => Some((label.get().into(), label.span())),
+        ast::Expr::FuncCall(call) => match call.callee() {
+            ast::Expr::Ident(callee) if callee.get() == "label" => {
+                let arg = call.args().items().next()?;
+                let ast::Arg::Pos(ast::Expr::Str(key)) = arg else {
+                    return None;
+                };
+                Some((key.get().as_str().into(), key.span()))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
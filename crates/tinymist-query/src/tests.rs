@@ -78,6 +78,11 @@ pub fn run_with_ctx<T>(
         .get("html")
         .map(|v| v.trim() == "true")
         .unwrap_or(true);
+    // Auto-import completions are opted in, since they would otherwise mix into
+    // the completions of the fixtures having multiple files.
+    let auto_import = properties
+        .get("auto_import")
+        .is_some_and(|v| v.trim() == "true");
 
     let mut ctx = Arc::new(Analysis {
         remove_html: !supports_html,
@@ -86,6 +91,7 @@ pub fn run_with_ctx<T>(
             trigger_suggest: true,
             trigger_parameter_hints: true,
             trigger_suggest_and_parameter_hints: true,
+            auto_import: Some(auto_import),
             ..Default::default()
         },
         ..Analysis::default()
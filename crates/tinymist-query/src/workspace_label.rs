@@ -22,6 +22,9 @@ impl SemanticRequest for WorkspaceLabelRequest {
         let mut symbols = vec![];
 
         for fid in ctx.source_files().clone() {
+            if ctx.is_cancelled() {
+                return None;
+            }
             let Ok(source) = ctx.source_by_id(fid) else {
                 continue;
             };
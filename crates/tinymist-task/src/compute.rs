@@ -9,7 +9,7 @@ use tinymist_std::error::prelude::*;
 use tinymist_std::typst::{TypstDocument, TypstHtmlDocument, TypstPagedDocument};
 use tinymist_world::{CompileSnapshot, CompilerFeat, ExportComputation, WorldComputeGraph};
 use typst::diag::{SourceResult, StrResult};
-use typst::foundations::{Bytes, Content, IntoValue, LocatableSelector, Scope, Value};
+use typst::foundations::{Bytes, Content, Dict, IntoValue, LocatableSelector, Scope, Value};
 use typst::layout::Abs;
 use typst::routines::EvalMode;
 use typst::syntax::{ast, Span, SyntaxNode};
@@ -52,6 +52,9 @@ impl ExportTimings {
             TaskWhen::Never => Some(false),
             TaskWhen::OnType => Some(s.by_mem_events),
             TaskWhen::OnSave => Some(s.by_fs_events),
+            // The delay and the changed files are checked by the caller.
+            TaskWhen::OnIdle | TaskWhen::OnChange => Some(s.by_mem_events || s.by_fs_events),
+            TaskWhen::OnSuccess => Some((s.by_mem_events || s.by_fs_events) && docs.is_some()),
             TaskWhen::OnDocumentHasTitle if s.by_fs_events => {
                 docs.map(|doc| doc.info().title.is_some())
             }
@@ -333,6 +336,59 @@ pub fn get_page_selection(task: &crate::ExportTask) -> Result<(bool, Abs)> {
     Ok((is_first, gap_res))
 }
 
+/// Runs the [`ExportTransform::Script`] transforms of the task on the exported
+/// data in order, before it is written to the `path`.
+pub fn run_transform_scripts(
+    world: &dyn World,
+    task: &crate::ExportTask,
+    path: &str,
+    mut data: Bytes,
+) -> Result<Bytes> {
+    for trans in &task.transform {
+        let ExportTransform::Script {
+            script: Some(script),
+        } = trans
+        else {
+            continue;
+        };
+
+        let mut output = Dict::new();
+        output.insert("path".into(), path.into_value());
+        output.insert("bytes".into(), data.clone().into_value());
+        let mut scope = Scope::default();
+        scope.define("output", output);
+
+        let result = eval_string(
+            &typst::ROUTINES,
+            world.track(),
+            script,
+            Span::detached(),
+            EvalMode::Code,
+            scope,
+        )
+        .map_err(|errors| {
+            let message = errors.iter().map(|error| error.message.as_str());
+            format!(
+                "failed to evaluate script: {}",
+                message.collect::<Vec<_>>().join(", ")
+            )
+        })
+        .context("transform script")?;
+
+        data = match result {
+            Value::None => data,
+            Value::Bytes(bytes) => bytes,
+            Value::Str(s) => Bytes::from_string(s.as_str().to_owned()),
+            value => bail!(
+                "expected the script to return bytes, str or none, got {}",
+                value.ty()
+            ),
+        };
+    }
+
+    Ok(data)
+}
+
 fn parse_length(gap: &str) -> Result<Abs> {
     let length = typst::syntax::parse_code(gap);
     if length.erroneous() {
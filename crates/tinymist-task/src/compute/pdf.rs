@@ -2,6 +2,13 @@ use tinymist_std::time::ToUtcDateTime;
 pub use typst_pdf::pdf;
 pub use typst_pdf::PdfStandard as TypstPdfStandard;
 
+use tinymist_world::{ShadowApi, TaskInputs};
+use typst::foundations::{Repr, Smart, Str};
+use typst::introspection::{Introspector, Location, Tag};
+use typst::layout::{Frame, FrameItem, PageRanges};
+use typst::model::Destination;
+use typst::syntax::{FileId, VirtualPath};
+use typst::utils::hash128;
 use typst_pdf::{PdfOptions, PdfStandards, Timestamp};
 
 use super::*;
@@ -14,10 +21,13 @@ impl<F: CompilerFeat> ExportComputation<F, TypstPagedDocument> for PdfExport {
     type Config = ExportPdfTask;
 
     fn run(
-        _graph: &Arc<WorldComputeGraph<F>>,
+        graph: &Arc<WorldComputeGraph<F>>,
         doc: &Arc<TypstPagedDocument>,
         config: &ExportPdfTask,
     ) -> Result<Bytes> {
+        let doc = with_attachments(graph, doc, config)?;
+        let doc = &with_metadata(&doc, config);
+
         let creation_timestamp = config
             .creation_timestamp
             .map(|ts| ts.to_utc_datetime().context("timestamp is out of range"))
@@ -40,15 +50,169 @@ impl<F: CompilerFeat> ExportComputation<F, TypstPagedDocument> for PdfExport {
         )
         .context_ut("prepare pdf standards")?;
 
+        let ranges = config.export.transform.iter().flat_map(|t| match t {
+            ExportTransform::Pages { ranges } => ranges.as_slice(),
+            _ => &[],
+        });
+        let ranges = ranges.map(|pages| pages.0.clone()).collect::<Vec<_>>();
+        let page_ranges = (!ranges.is_empty()).then(|| PageRanges::new(ranges));
+
         // todo: Some(pdf_uri.as_str())
-        // todo: ident option
         Ok(Bytes::new(typst_pdf::pdf(
             doc,
             &PdfOptions {
+                ident: match &config.ident {
+                    Some(ident) => Smart::Custom(ident.as_str()),
+                    None => Smart::Auto,
+                },
                 timestamp: Some(timestamp),
                 standards,
+                page_ranges,
                 ..Default::default()
             },
         )?))
     }
 }
+
+/// Overrides the metadata of the document.
+fn with_metadata(doc: &Arc<TypstPagedDocument>, config: &ExportPdfTask) -> Arc<TypstPagedDocument> {
+    let overrides_info =
+        config.title.is_some() || !config.author.is_empty() || !config.keywords.is_empty();
+    if !overrides_info {
+        return doc.clone();
+    }
+
+    let mut doc = doc.as_ref().clone();
+    if let Some(title) = &config.title {
+        doc.info.title = Some(title.into());
+    }
+    if !config.author.is_empty() {
+        doc.info.author = config.author.iter().map(Into::into).collect();
+    }
+    if !config.keywords.is_empty() {
+        doc.info.keywords = config.keywords.iter().map(Into::into).collect();
+    }
+
+    Arc::new(doc)
+}
+
+/// Compiles the document again with the files attached by `pdf.embed`, which
+/// are appended to the main source.
+pub fn with_attachments<F: CompilerFeat>(
+    graph: &Arc<WorldComputeGraph<F>>,
+    doc: &Arc<TypstPagedDocument>,
+    config: &ExportPdfTask,
+) -> Result<Arc<TypstPagedDocument>> {
+    if config.attachments.is_empty() {
+        return Ok(doc.clone());
+    }
+
+    let world = &graph.snap.world;
+    let entry = world.entry_state();
+    let root = entry.root().context("cannot attach files without a root")?;
+    let main = world.main();
+    let source = world
+        .source(main)
+        .context_ut("cannot read the main source")?;
+    let mut text = source.text().to_owned();
+
+    let mut world = world.task(TaskInputs::default());
+    for (idx, pattern) in config.attachments.iter().enumerate() {
+        let path = pattern
+            .substitute(&entry)
+            .context("cannot resolve attachment path")?;
+        let path = root.join(path);
+        let data = std::fs::read(&path).context("read attachment")?;
+        let name = path.file_name().context("attachment without a file name")?;
+        let name = Str::from(name.to_string_lossy().as_ref()).repr();
+
+        // The data is read from a shadow file, as the attachment may be outside
+        // of the root.
+        let vpath = format!("/__tinymist_attachment_{idx}");
+        let id = FileId::new(None, VirtualPath::new(&vpath));
+        world
+            .map_shadow_by_id(id, Bytes::new(data))
+            .context_ut("attach file")?;
+        text.push_str(&format!(
+            "\n#pdf.embed({name}, read({vpath:?}, encoding: none))"
+        ));
+    }
+    world
+        .map_shadow_by_id(main, Bytes::from_string(text))
+        .context_ut("attach file")?;
+
+    let doc = typst::compile::<TypstPagedDocument>(&world)
+        .output
+        .map_err(|errors| {
+            let message = errors.iter().map(|error| error.message.as_str());
+            format!(
+                "failed to attach files: {}",
+                message.collect::<Vec<_>>().join(", ")
+            )
+        })
+        .context("attach file")?;
+
+    Ok(Arc::new(doc))
+}
+
+/// Concatenates the pages of the documents in order. The document information
+/// is taken from the first document.
+pub fn concat_documents<'a>(
+    docs: impl IntoIterator<Item = &'a TypstPagedDocument>,
+) -> TypstPagedDocument {
+    let mut pages = vec![];
+    let mut info = None;
+    for (idx, doc) in docs.into_iter().enumerate() {
+        info.get_or_insert_with(|| doc.info.clone());
+        // The locations are only unique within a compilation, so those of the
+        // appended documents are derived anew to not collide with the others.
+        pages.extend(doc.pages.iter().map(|page| {
+            let mut page = page.clone();
+            if idx > 0 {
+                page.frame = relocate(&page.frame, idx);
+            }
+            page
+        }));
+    }
+
+    // Rebuilds the introspector so that the outline refers to the concatenated
+    // pages.
+    let introspector = Introspector::paged(&pages);
+    TypstPagedDocument {
+        pages,
+        info: info.unwrap_or_default(),
+        introspector,
+    }
+}
+
+/// Derives new locations for the elements and the links in a frame.
+fn relocate(frame: &Frame, idx: usize) -> Frame {
+    let derive = |loc: &Location| Location::new(hash128(&("tinymist-concat", idx, loc)));
+
+    let mut relocated = frame.clone();
+    relocated.clear();
+    for (pos, item) in frame.items() {
+        let item = match item {
+            FrameItem::Group(group) => {
+                let mut group = group.clone();
+                group.frame = relocate(&group.frame, idx);
+                FrameItem::Group(group)
+            }
+            FrameItem::Tag(Tag::Start(content)) => {
+                let mut content = content.clone();
+                if let Some(loc) = content.location() {
+                    content.set_location(derive(&loc));
+                }
+                FrameItem::Tag(Tag::Start(content))
+            }
+            FrameItem::Tag(Tag::End(loc, key)) => FrameItem::Tag(Tag::End(derive(loc), *key)),
+            FrameItem::Link(Destination::Location(loc), size) => {
+                FrameItem::Link(Destination::Location(derive(loc)), *size)
+            }
+            item => item.clone(),
+        };
+        relocated.push(*pos, item);
+    }
+
+    relocated
+}
@@ -1,10 +1,12 @@
 //! Project task models.
 
 use std::hash::Hash;
+use std::str::FromStr;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
-use super::{Id, Pages, PathPattern, PdfStandard, Scalar, TaskWhen};
+use super::{Id, Pages, PathPattern, PdfStandard, ResourcePath, Scalar, TaskWhen};
 
 /// A project task application specifier. This is used for specifying tasks to
 /// run in a project. When the language service notifies an update event of the
@@ -25,11 +27,42 @@ use super::{Id, Pages, PathPattern, PdfStandard, Scalar, TaskWhen};
 /// tinymist project query main.typ --format json --selector "<pdfpc-notes>" --field value --one
 /// ```
 ///
-/// Export a PDF file and then runs a ghostscript command to compress it:
+/// Export a PDF file and then post-process it by a typst script, which
+/// receives the output path and the exported bytes:
 ///
-/// ```bash
-/// tinymist project compile main.typ --pipe 'import "@local/postprocess:0.0.1": ghostscript; ghostscript(output.path)'
+/// ```toml
+/// [[task]]
+/// id = "main"
+/// document = "main.typ"
+/// type = "export-pdf"
+/// when = "never"
+/// transform = [{ script = { script = 'import "@local/postprocess:0.0.1": strip; strip(output.bytes)' } }]
+/// ```
+///
+/// Export the glossary of a document as JSON, and then feed it into the main
+/// document. The tasks are run in order by `tinymist build`:
+///
+/// ```toml
+/// [[task]]
+/// id = "glossary"
+/// document = "glossary.typ"
+/// type = "query"
+/// when = "never"
+/// output = "$root/target/glossary"
+/// format = "json"
+/// selector = "<term>"
+/// one = false
+///
+/// [[task]]
+/// id = "main"
+/// document = "main.typ"
+/// type = "export-pdf"
+/// when = "never"
+/// depends-on = ["glossary"]
 /// ```
+///
+/// The main document then reads the output of the glossary task by
+/// `json(sys.inputs.at("tinymist.task.glossary"))`.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case", tag = "type")]
 pub struct ApplyProjectTask {
@@ -37,6 +70,19 @@ pub struct ApplyProjectTask {
     pub id: Id,
     /// The document's ID.
     pub document: Id,
+    /// The tasks that must be run before this task. The output of each
+    /// dependency is visible through `sys.inputs` under the key
+    /// `tinymist.task.<id>`, or `tinymist.task.<id>.<variant>` for each variant
+    /// of a dependency with a matrix. The value is the path to the output
+    /// file, not its content. The path is absolute in the project, i.e.
+    /// starting with `/` relative to the root, if the output is inside the
+    /// root, so it can be passed to `read`, `json`, etc. directly. Otherwise,
+    /// it is the absolute path of the file on the filesystem.
+    ///
+    /// Dependencies are only run by `tinymist build`. Other ways of running a
+    /// task ignore them with a warning.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<Id>,
     /// The task to run.
     #[serde(flatten)]
     pub task: ProjectTask,
@@ -112,6 +158,42 @@ impl ProjectTask {
         })
     }
 
+    /// Returns the mutable export configuration of a task.
+    pub fn as_export_mut(&mut self) -> Option<&mut ExportTask> {
+        Some(match self {
+            Self::Preview(..) => return None,
+            Self::ExportPdf(task) => &mut task.export,
+            Self::ExportPng(task) => &mut task.export,
+            Self::ExportSvg(task) => &mut task.export,
+            Self::ExportHtml(task) => &mut task.export,
+            Self::ExportSvgHtml(task) => &mut task.export,
+            Self::ExportMd(task) => &mut task.export,
+            Self::ExportText(task) => &mut task.export,
+            Self::Query(task) => &mut task.export,
+        })
+    }
+
+    /// Expands the task into the tasks of its variants, whose output paths
+    /// are substituted with the variants. A task without a matrix is expanded
+    /// to itself.
+    pub fn expand(&self) -> Vec<(Option<TaskVariant>, ProjectTask)> {
+        let Some(export) = self.as_export().filter(|export| !export.matrix.is_empty()) else {
+            return vec![(None, self.clone())];
+        };
+
+        export
+            .matrix
+            .iter()
+            .map(|variant| {
+                let mut task = self.clone();
+                let export = task.as_export_mut().unwrap();
+                export.output = Some(variant.substitute(export.output.as_ref()));
+                export.matrix = Vec::new();
+                (Some(variant.clone()), task)
+            })
+            .collect()
+    }
+
     /// Returns extension of the artifact.
     pub fn extension(&self) -> &str {
         match self {
@@ -130,6 +212,101 @@ impl ProjectTask {
     }
 }
 
+/// A variant of an export task. The inputs of the variant are added to the
+/// `sys.inputs` of the document, and its font paths are added to the
+/// document's font paths.
+///
+/// The output path pattern of the task can be substituted with `$variant` and
+/// `$inputs.<key>`. If the task has no output path pattern, the variants are
+/// exported to `$root/$dir/$variant/$name`.
+///
+/// ## Examples
+///
+/// Export a report in English, German, and as a draft:
+///
+/// ```toml
+/// [[task]]
+/// id = "report"
+/// document = "report.typ"
+/// type = "export-pdf"
+/// when = "never"
+/// output = "$root/target/report-$variant"
+///
+/// [[task.matrix]]
+/// name = "en"
+/// inputs = [["lang", "en"]]
+///
+/// [[task.matrix]]
+/// name = "de"
+/// inputs = [["lang", "de"]]
+/// font-paths = ["file:fonts/de"]
+///
+/// [[task.matrix]]
+/// name = "draft"
+/// inputs = [["lang", "en"], ["draft", "true"]]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TaskVariant {
+    /// The name of the variant.
+    pub name: String,
+    /// The key-value pairs added to `sys.inputs`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<(String, String)>,
+    /// The font paths added to the document's font paths.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub font_paths: Vec<ResourcePath>,
+}
+
+impl TaskVariant {
+    /// Substitutes `$variant` and `$inputs.<key>` in an output path pattern.
+    pub fn substitute(&self, pattern: Option<&PathPattern>) -> PathPattern {
+        let pattern = match pattern {
+            Some(pattern) if !pattern.0.is_empty() => pattern.0.as_str(),
+            _ => "$root/$dir/$variant/$name",
+        };
+
+        let mut path = pattern.replace("$variant", &self.name);
+        // Substitutes longer keys first, in case that a key is a prefix of another.
+        let mut inputs = self.inputs.iter().collect::<Vec<_>>();
+        inputs.sort_by_key(|(key, _)| std::cmp::Reverse(key.len()));
+        for (key, value) in inputs {
+            path = path.replace(&format!("$inputs.{key}"), value);
+        }
+
+        PathPattern::new(&path)
+    }
+}
+
+/// Parses a variant from the command line, e.g. `draft:lang=en,draft=true`.
+impl FromStr for TaskVariant {
+    type Err = &'static str;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (name, inputs) = value.split_once(':').unwrap_or((value, ""));
+        if name.is_empty() {
+            return Err("variant name must not be empty");
+        }
+
+        let inputs = inputs
+            .split(',')
+            .filter(|input| !input.is_empty())
+            .map(|input| {
+                let (key, value) = input
+                    .split_once('=')
+                    .ok_or("variant input must be in the form of `key=value`")?;
+                Ok((key.to_owned(), value.to_owned()))
+            })
+            .collect::<Result<_, Self::Err>>()?;
+
+        Ok(TaskVariant {
+            name: name.to_owned(),
+            inputs,
+            font_paths: Vec::new(),
+        })
+    }
+}
+
 /// A preview task specifier.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -145,20 +322,40 @@ pub struct PreviewTask {
 pub struct ExportTask {
     /// When to run the task
     pub when: TaskWhen,
+    /// The idle time in milliseconds before running an
+    /// [`TaskWhen::OnIdle`] task. Defaults to
+    /// [`ExportTask::DEFAULT_DEBOUNCE`].
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub debounce: Option<u64>,
+    /// The glob patterns, relative to the root, of the files whose changes run
+    /// an [`TaskWhen::OnChange`] task, e.g. `chapters/**/*.typ`.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub watch: Vec<String>,
     /// The output path pattern.
     pub output: Option<PathPattern>,
     /// The task's transforms.
     #[serde(skip_serializing_if = "Vec::is_empty", default)]
     pub transform: Vec<ExportTransform>,
+    /// The variants of the task. If not empty, the task is run once per
+    /// variant instead. See [`TaskVariant`] for more information.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub matrix: Vec<TaskVariant>,
 }
 
 impl ExportTask {
+    /// The default idle time in milliseconds before running an
+    /// [`TaskWhen::OnIdle`] task.
+    pub const DEFAULT_DEBOUNCE: u64 = 500;
+
     /// Creates a new unmounted export task.
     pub fn new(when: TaskWhen) -> Self {
         Self {
             when,
+            debounce: None,
+            watch: Vec::new(),
             output: None,
             transform: Vec::new(),
+            matrix: Vec::new(),
         }
     }
 
@@ -167,6 +364,27 @@ impl ExportTask {
         self.transform
             .push(ExportTransform::Pretty { script: None });
     }
+
+    /// Gets the idle time before running an [`TaskWhen::OnIdle`] task.
+    pub fn debounce(&self) -> Duration {
+        let debounce = self.debounce.unwrap_or(Self::DEFAULT_DEBOUNCE);
+        Duration::from_millis(debounce)
+    }
+
+    /// Checks whether a file, given by its path relative to the root, is
+    /// watched by an [`TaskWhen::OnChange`] task.
+    pub fn is_watched(&self, path: &str) -> bool {
+        let path = path.trim_start_matches('/');
+        self.watch.iter().any(|pattern| glob_match(pattern, path))
+    }
+
+    /// Gets the documents to append to the output, in order.
+    pub fn concat_documents(&self) -> impl Iterator<Item = &Id> {
+        self.transform.iter().flat_map(|transform| match transform {
+            ExportTransform::Concat { documents } => documents.as_slice(),
+            _ => &[],
+        })
+    }
 }
 
 /// The legacy page selection specifier.
@@ -197,7 +415,22 @@ pub enum ExportTransform {
         /// The gap between pages (typst code expression, e.g. `1pt`).
         gap: Option<String>,
     },
+    /// Appends the pages of other documents in the project, in order. The
+    /// outlines of the documents are merged as well.
+    ///
+    /// Only effective for PDF exports. The page ranges select the pages of the
+    /// concatenated document. The documents are only concatenated by
+    /// `tinymist build`, and other exports of the task fail.
+    Concat {
+        /// The IDs of the documents to append.
+        documents: Vec<Id>,
+    },
     /// Execute a transform script.
+    ///
+    /// The script is evaluated in code mode with `output` bound to a
+    /// dictionary of the output `path` and the exported `bytes`. The result
+    /// (`bytes` or `str`) replaces the output, or it is kept if the script
+    /// returns `none`.
     Script {
         /// The postprocess script (typst script) to run.
         #[serde(skip_serializing_if = "Option::is_none", default)]
@@ -230,6 +463,23 @@ pub struct ExportPdfTask {
     /// For more information, see <https://reproducible-builds.org/specs/source-date-epoch/>.
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub creation_timestamp: Option<i64>,
+    /// A stable identifier of the document, which is kept across the exports
+    /// of the document. If not provided, it is derived from the title and the
+    /// authors of the document.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub ident: Option<String>,
+    /// Overrides the title of the document.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub title: Option<String>,
+    /// Overrides the authors of the document.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub author: Vec<String>,
+    /// Overrides the keywords of the document.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub keywords: Vec<String>,
+    /// The files to attach to the PDF, e.g. `$root/data.csv`.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub attachments: Vec<PathPattern>,
 }
 
 /// An export png task specifier.
@@ -305,3 +555,86 @@ pub struct QueryTask {
     /// Expects and retrieves exactly one element.
     pub one: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_matrix() {
+        let task = ProjectTask::ExportPdf(ExportPdfTask {
+            export: ExportTask {
+                output: Some(PathPattern::new("$root/report-$variant-$inputs.lang")),
+                matrix: vec![
+                    "en:lang=en".parse().unwrap(),
+                    "draft:lang=en,language=English".parse().unwrap(),
+                ],
+                ..ExportTask::default()
+            },
+            pdf_standards: vec![],
+            creation_timestamp: None,
+            ident: None,
+            title: None,
+            author: vec![],
+            keywords: vec![],
+            attachments: vec![],
+        });
+
+        let outputs = task
+            .expand()
+            .into_iter()
+            .map(|(variant, task)| {
+                let export = task.as_export().unwrap();
+                assert!(export.matrix.is_empty());
+                (variant.unwrap().name, export.output.clone().unwrap().0)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            outputs,
+            vec![
+                ("en".into(), "$root/report-en-en".into()),
+                ("draft".into(), "$root/report-draft-en".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_concat_documents() {
+        let export: ExportTask = toml::from_str(
+            r#"
+            when = "never"
+            transform = [
+                { pages = { ranges = ["2-3"] } },
+                { concat = { documents = ["ch1", "ch2"] } },
+                { concat = { documents = ["appendix"] } },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        let documents = export.concat_documents().cloned().collect::<Vec<_>>();
+        assert_eq!(
+            documents,
+            vec![
+                Id::new("ch1".into()),
+                Id::new("ch2".into()),
+                Id::new("appendix".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_variant() {
+        let variant: TaskVariant = "draft:lang=en,draft=true".parse().unwrap();
+        assert_eq!(variant.name, "draft");
+        assert_eq!(
+            variant.inputs,
+            vec![
+                ("lang".to_owned(), "en".to_owned()),
+                ("draft".to_owned(), "true".to_owned())
+            ]
+        );
+        assert!("".parse::<TaskVariant>().is_err());
+        assert!("draft:lang".parse::<TaskVariant>().is_err());
+    }
+}
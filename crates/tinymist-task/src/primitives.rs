@@ -169,6 +169,57 @@ impl PathPattern {
     }
 }
 
+/// Matches a unix-style path against a glob pattern, where `*` matches any
+/// characters except `/`, `**` matches any number of path segments, and `?`
+/// matches a single character except `/`.
+pub(crate) fn glob_match(pattern: &str, path: &str) -> bool {
+    fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+        match pattern.split_first() {
+            None => path.is_empty(),
+            Some((&"**", rest)) => (0..=path.len()).any(|i| match_segments(rest, &path[i..])),
+            Some((segment, rest)) => match path.split_first() {
+                Some((name, path)) => match_segment(segment, name) && match_segments(rest, path),
+                None => false,
+            },
+        }
+    }
+
+    fn match_segment(pattern: &str, name: &str) -> bool {
+        let pattern = pattern.chars().collect::<Vec<_>>();
+        let name = name.chars().collect::<Vec<_>>();
+
+        // Classic wildcard matching with backtracking to the last `*`.
+        let (mut p, mut n) = (0, 0);
+        let mut star = None;
+        while n < name.len() {
+            match pattern.get(p) {
+                Some('*') => {
+                    star = Some((p, n));
+                    p += 1;
+                }
+                Some(c) if *c == '?' || *c == name[n] => {
+                    p += 1;
+                    n += 1;
+                }
+                _ => match star {
+                    Some((star_p, star_n)) => {
+                        p = star_p + 1;
+                        n = star_n + 1;
+                        star = Some((star_p, star_n + 1));
+                    }
+                    None => return false,
+                },
+            }
+        }
+
+        pattern[p..].iter().all(|c| *c == '*')
+    }
+
+    let pattern = pattern.split('/').collect::<Vec<_>>();
+    let path = path.split('/').collect::<Vec<_>>();
+    match_segments(&pattern, &path)
+}
+
 /// Implements parsing of page ranges (`1-3`, `4`, `5-`, `-2`), used by the
 /// `CompileCommand.pages` argument, through the `FromStr` trait instead of a
 /// value parser, in order to generate better errors.
@@ -362,6 +413,21 @@ mod tests {
     use super::*;
     use typst::syntax::VirtualPath;
 
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("main.typ", "main.typ"));
+        assert!(glob_match("*.typ", "main.typ"));
+        assert!(!glob_match("*.typ", "chapters/intro.typ"));
+        assert!(glob_match("chapters/*.typ", "chapters/intro.typ"));
+        assert!(glob_match("**/*.typ", "main.typ"));
+        assert!(glob_match("**/*.typ", "chapters/part1/intro.typ"));
+        assert!(glob_match("chapters/**", "chapters/part1/intro.typ"));
+        assert!(glob_match("data/?.csv", "data/a.csv"));
+        assert!(!glob_match("data/?.csv", "data/ab.csv"));
+        assert!(glob_match("*-*.csv", "sales-2024.csv"));
+        assert!(!glob_match("*.csv", "sales.csv.bak"));
+    }
+
     #[test]
     fn test_substitute_path() {
         let root = Path::new("/root");
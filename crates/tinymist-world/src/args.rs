@@ -10,7 +10,7 @@ use tinymist_std::{bail, error::prelude::*};
 use tinymist_vfs::ImmutDict;
 use typst::{foundations::IntoValue, utils::LazyHash};
 
-use crate::EntryOpts;
+use crate::{ColumnEncoding, DiagnosticFormat, EntryOpts};
 
 const ENV_PATH_SEP: char = if cfg!(windows) { ';' } else { ':' };
 
@@ -50,6 +50,19 @@ pub struct CompilePackageArgs {
     pub package_cache_path: Option<PathBuf>,
 }
 
+/// Arguments related to how diagnostics are reported.
+#[derive(Debug, Clone, Copy, Parser, Default, PartialEq, Eq)]
+pub struct DiagnosticArgs {
+    /// The format to print the diagnostics in. The machine-readable formats
+    /// are printed to stdout.
+    #[clap(long, value_enum, default_value_t)]
+    pub diagnostic_format: DiagnosticFormat,
+
+    /// The encoding to count the columns in machine-readable diagnostics.
+    #[clap(long, value_enum, default_value_t)]
+    pub diagnostic_encoding: ColumnEncoding,
+}
+
 /// Common arguments of compile, watch, and query.
 #[derive(Debug, Clone, Parser, Default)]
 pub struct CompileOnceArgs {
@@ -247,6 +260,16 @@ pub enum TaskWhen {
     OnSave,
     /// Run task on typing, i.e. on `textDocument/didChange` events.
     OnType,
+    /// Run task when typing pauses, i.e. after no `textDocument/didChange`
+    /// events for a while. The delay is configured by the `debounce` of the
+    /// task.
+    OnIdle,
+    /// Run task on typing or saving, but only when the document compiles
+    /// without errors.
+    OnSuccess,
+    /// Run task when the files matching the `watch` globs of the task are
+    /// changed, by either typing or saving.
+    OnChange,
     /// *DEPRECATED* Run task when a document has a title and on saved, which is
     /// useful to filter out template files.
     ///
@@ -26,7 +26,10 @@ mod snapshot;
 #[cfg(feature = "system")]
 pub mod system;
 #[cfg(feature = "system")]
-pub use system::{print_diagnostics, SystemCompilerFeat, TypstSystemUniverse, TypstSystemWorld};
+pub use system::{
+    print_diagnostics, print_diagnostics_with, DiagnosticPrinter, SystemCompilerFeat,
+    TypstSystemUniverse, TypstSystemWorld,
+};
 
 /// Run the compiler in the browser environment.
 #[cfg(feature = "browser")]
@@ -145,11 +148,35 @@ pub trait CompilerFeat: Send + Sync + 'static {
 }
 
 /// Which format to use for diagnostics.
-#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Ord, PartialOrd, clap::ValueEnum)]
 pub enum DiagnosticFormat {
+    /// Human-readable diagnostics with source snippets.
     #[default]
     Human,
+    /// One line per diagnostic.
     Short,
+    /// One JSON object per diagnostic, in the JSON Lines format.
+    Json,
+    /// A SARIF log, which can be uploaded to code scanning services.
+    Sarif,
+    /// GitHub Actions workflow commands, which annotate the files in pull
+    /// requests.
+    Github,
+}
+
+/// How to count the columns in machine-readable diagnostics.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, clap::ValueEnum)]
+pub enum ColumnEncoding {
+    /// Counts the columns in UTF-8 code units (bytes).
+    #[value(name = "utf-8")]
+    Utf8,
+    /// Counts the columns in UTF-16 code units.
+    #[default]
+    #[value(name = "utf-16")]
+    Utf16,
+    /// Counts the columns in Unicode code points.
+    #[value(name = "utf-32")]
+    Utf32,
 }
 
 pub mod build_info {
@@ -65,6 +65,12 @@ impl HttpRegistry {
         }
     }
 
+    /// Sets the notifier that receives the progress of package downloads.
+    pub fn with_notifier(mut self, notifier: Arc<Mutex<dyn Notifier + Send>>) -> Self {
+        self.notifier = notifier;
+        self
+    }
+
     /// Get `typst-kit` implementing package storage
     pub fn storage(&self) -> &PackageStorage {
         self.storage.get_or_init(|| {
@@ -286,7 +292,7 @@ impl PackageStorage {
         );
 
         self.notifier.lock().downloading(spec);
-        threaded_http(&url, self.cert_path.as_deref(), |resp| {
+        let result = threaded_http(&url, self.cert_path.as_deref(), |resp| {
             let reader = match resp.and_then(|r| r.error_for_status()) {
                 Ok(response) => response,
                 Err(err) if matches!(err.status().map(|s| s.as_u16()), Some(404)) => {
@@ -303,7 +309,10 @@ impl PackageStorage {
                     PackageError::MalformedArchive(Some(eco_format!("{err}")))
                 })
         })
-        .ok_or_else(|| PackageError::Other(Some(eco_format!("cannot spawn http thread"))))?
+        .ok_or_else(|| PackageError::Other(Some(eco_format!("cannot spawn http thread"))));
+        self.notifier.lock().downloaded(spec);
+
+        result?
     }
 }
 
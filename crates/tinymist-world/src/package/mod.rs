@@ -52,8 +52,13 @@ impl<T: PackageRegistry> tinymist_vfs::RootResolver for RegistryPathMapper<T> {
     }
 }
 
+/// Receives the progress of package operations.
 pub trait Notifier {
+    /// Called before a package starts downloading.
     fn downloading(&self, _spec: &PackageSpec) {}
+    /// Called after a package finished downloading, whether it succeeded or
+    /// not.
+    fn downloaded(&self, _spec: &PackageSpec) {}
 }
 
 #[derive(Debug, Default, Clone, Copy, Hash)]
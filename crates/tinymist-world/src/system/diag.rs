@@ -1,4 +1,5 @@
-use std::io::IsTerminal;
+use std::io::{IsTerminal, Write};
+use std::path::Path;
 
 use codespan_reporting::{
     diagnostic::{Diagnostic, Label},
@@ -7,13 +8,16 @@ use codespan_reporting::{
         termcolor::{ColorChoice, StandardStream},
     },
 };
+use ecow::EcoString;
+use parking_lot::Mutex;
+use serde::Serialize;
 use tinymist_std::Result;
 use tinymist_vfs::FileId;
 use typst::diag::{eco_format, Severity, SourceDiagnostic};
-use typst::syntax::Span;
+use typst::syntax::{Source, Span};
 use typst::WorldExt;
 
-use crate::{CodeSpanReportWorld, DiagnosticFormat, SourceWorld};
+use crate::{CodeSpanReportWorld, ColumnEncoding, DiagnosticFormat, SourceWorld};
 
 /// Get stderr with color support if desirable.
 fn color_stream() -> StandardStream {
@@ -30,11 +34,36 @@ pub fn print_diagnostics<'d, 'files>(
     errors: impl Iterator<Item = &'d SourceDiagnostic>,
     diagnostic_format: DiagnosticFormat,
 ) -> Result<(), codespan_reporting::files::Error> {
+    print_diagnostics_with(world, errors, diagnostic_format, ColumnEncoding::default())
+}
+
+/// Print diagnostic messages to the terminal. The columns of the
+/// machine-readable formats are counted in the given encoding.
+pub fn print_diagnostics_with<'d, 'files>(
+    world: &'files dyn SourceWorld,
+    errors: impl Iterator<Item = &'d SourceDiagnostic>,
+    diagnostic_format: DiagnosticFormat,
+    encoding: ColumnEncoding,
+) -> Result<(), codespan_reporting::files::Error> {
+    let (DiagnosticFormat::Human | DiagnosticFormat::Short) = diagnostic_format else {
+        let encoding = machine_encoding(diagnostic_format, encoding);
+        let records = errors
+            .map(|diag| DiagnosticRecord::new(world, diag, encoding))
+            .collect::<Vec<_>>();
+        let mut w = std::io::stdout().lock();
+        return match diagnostic_format {
+            DiagnosticFormat::Sarif => write_sarif(&mut w, &records, encoding),
+            DiagnosticFormat::Github => write_github(&mut w, &records),
+            _ => write_json(&mut w, &records),
+        }
+        .map_err(codespan_reporting::files::Error::Io);
+    };
+
     let world = CodeSpanReportWorld::new(world);
 
     let mut w = match diagnostic_format {
-        DiagnosticFormat::Human => color_stream(),
         DiagnosticFormat::Short => StandardStream::stderr(ColorChoice::Never),
+        _ => color_stream(),
     };
 
     let mut config = term::Config {
@@ -76,7 +105,360 @@ pub fn print_diagnostics<'d, 'files>(
     Ok(())
 }
 
+/// Prints the diagnostics of several compilations in one invocation.
+///
+/// A SARIF log cannot be concatenated, so the SARIF results are collected
+/// instead and written as a single run by [`DiagnosticPrinter::finish`]. The
+/// other formats are printed at once.
+pub struct DiagnosticPrinter {
+    format: DiagnosticFormat,
+    encoding: ColumnEncoding,
+    sarif: Mutex<Vec<DiagnosticRecord>>,
+}
+
+impl DiagnosticPrinter {
+    /// Creates a printer for the given format and column encoding.
+    pub fn new(format: DiagnosticFormat, encoding: ColumnEncoding) -> Self {
+        Self {
+            format,
+            encoding,
+            sarif: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Prints the diagnostics, or collects them if the format is SARIF.
+    pub fn print<'d>(
+        &self,
+        world: &dyn SourceWorld,
+        errors: impl Iterator<Item = &'d SourceDiagnostic>,
+    ) -> Result<(), codespan_reporting::files::Error> {
+        if self.format != DiagnosticFormat::Sarif {
+            return print_diagnostics_with(world, errors, self.format, self.encoding);
+        }
+
+        let encoding = machine_encoding(self.format, self.encoding);
+        let records = errors.map(|diag| DiagnosticRecord::new(world, diag, encoding));
+        self.sarif.lock().extend(records);
+        Ok(())
+    }
+
+    /// Writes the collected SARIF log, if any.
+    pub fn finish(&self) -> Result<(), codespan_reporting::files::Error> {
+        if self.format != DiagnosticFormat::Sarif {
+            return Ok(());
+        }
+
+        let encoding = machine_encoding(self.format, self.encoding);
+        let records = std::mem::take(&mut *self.sarif.lock());
+        write_sarif(&mut std::io::stdout().lock(), &records, encoding)
+            .map_err(codespan_reporting::files::Error::Io)
+    }
+}
+
+/// The column encoding used by a machine-readable format.
+fn machine_encoding(format: DiagnosticFormat, encoding: ColumnEncoding) -> ColumnEncoding {
+    match (format, encoding) {
+        // SARIF has no kind for UTF-8 columns.
+        (DiagnosticFormat::Sarif, ColumnEncoding::Utf8) => ColumnEncoding::Utf32,
+        _ => encoding,
+    }
+}
+
 /// Create a label for a span.
 fn label(world: &dyn SourceWorld, span: Span) -> Option<Label<FileId>> {
     Some(Label::primary(span.id()?, world.range(span)?))
 }
+
+/// A machine-readable diagnostic.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticRecord {
+    /// The severity of the diagnostic, either `error` or `warning`.
+    pub severity: &'static str,
+    /// The message of the diagnostic.
+    pub message: EcoString,
+    /// The location of the diagnostic.
+    #[serde(flatten)]
+    pub location: Option<DiagnosticLocation>,
+    /// The hints to fix the diagnostic.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hints: Vec<EcoString>,
+    /// The trace of the diagnostic, from the innermost point.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub trace: Vec<DiagnosticTracepoint>,
+}
+
+/// A point in the trace of a diagnostic.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticTracepoint {
+    /// The message of the tracepoint.
+    pub message: String,
+    /// The location of the tracepoint.
+    #[serde(flatten)]
+    pub location: Option<DiagnosticLocation>,
+}
+
+/// The location of a diagnostic. The lines and columns are one-based, and the
+/// end column is exclusive.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticLocation {
+    /// The resolved path to the file.
+    pub path: String,
+    /// The start line.
+    pub line: usize,
+    /// The start column.
+    pub column: usize,
+    /// The end line.
+    pub end_line: usize,
+    /// The end column.
+    pub end_column: usize,
+}
+
+impl DiagnosticRecord {
+    /// Creates a machine-readable diagnostic.
+    pub fn new(world: &dyn SourceWorld, diag: &SourceDiagnostic, encoding: ColumnEncoding) -> Self {
+        Self {
+            severity: match diag.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            },
+            message: diag.message.clone(),
+            location: DiagnosticLocation::new(world, diag.span, encoding),
+            hints: diag.hints.to_vec(),
+            trace: diag
+                .trace
+                .iter()
+                .map(|point| DiagnosticTracepoint {
+                    message: point.v.to_string(),
+                    location: DiagnosticLocation::new(world, point.span, encoding),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl DiagnosticLocation {
+    /// Locates a span in the world.
+    pub fn new(world: &dyn SourceWorld, span: Span, encoding: ColumnEncoding) -> Option<Self> {
+        let id = span.id()?;
+        let range = world.range(span)?;
+        let source = world.source(id).ok()?;
+        let path = world.path_for_id(id).ok()?;
+
+        let (line, column) = line_column(&source, range.start, encoding)?;
+        let (end_line, end_column) = line_column(&source, range.end, encoding)?;
+        Some(Self {
+            path: path.as_path().display().to_string(),
+            line,
+            column,
+            end_line,
+            end_column,
+        })
+    }
+}
+
+/// Gets the one-based line and column of a byte offset.
+fn line_column(source: &Source, offset: usize, encoding: ColumnEncoding) -> Option<(usize, usize)> {
+    let line = source.byte_to_line(offset)?;
+    let line_start = source.line_to_byte(line)?;
+    let column = match encoding {
+        ColumnEncoding::Utf8 => offset - line_start,
+        ColumnEncoding::Utf16 => {
+            source.byte_to_utf16(offset)? - source.byte_to_utf16(line_start)?
+        }
+        ColumnEncoding::Utf32 => source.byte_to_column(offset)?,
+    };
+
+    Some((line + 1, column + 1))
+}
+
+/// Writes the diagnostics in the JSON Lines format.
+fn write_json(w: &mut impl Write, records: &[DiagnosticRecord]) -> std::io::Result<()> {
+    for record in records {
+        serde_json::to_writer(&mut *w, record)?;
+        writeln!(w)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the diagnostics as a SARIF log.
+fn write_sarif(
+    w: &mut impl Write,
+    records: &[DiagnosticRecord],
+    encoding: ColumnEncoding,
+) -> std::io::Result<()> {
+    let column_kind = match encoding {
+        ColumnEncoding::Utf16 => "utf16CodeUnits",
+        ColumnEncoding::Utf8 | ColumnEncoding::Utf32 => "unicodeCodePoints",
+    };
+
+    let location = |location: &DiagnosticLocation| {
+        serde_json::json!({
+            "physicalLocation": {
+                "artifactLocation": { "uri": file_uri(Path::new(&location.path)) },
+                "region": {
+                    "startLine": location.line,
+                    "startColumn": location.column,
+                    "endLine": location.end_line,
+                    "endColumn": location.end_column,
+                },
+            },
+        })
+    };
+
+    let results = records
+        .iter()
+        .map(|record| {
+            let mut message = record.message.to_string();
+            for hint in &record.hints {
+                message.push_str(&format!("\nhint: {hint}"));
+            }
+
+            let trace = record.trace.iter().map(|point| {
+                serde_json::json!({
+                    "message": { "text": point.message },
+                    "locations": point.location.iter().map(location).collect::<Vec<_>>(),
+                })
+            });
+
+            serde_json::json!({
+                "ruleId": "typst",
+                "level": record.severity,
+                "message": { "text": message },
+                "locations": record.location.iter().map(location).collect::<Vec<_>>(),
+                "relatedLocations": trace.collect::<Vec<_>>(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let log = serde_json::json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "tinymist",
+                    "informationUri": "https://github.com/Myriad-Dreamin/tinymist",
+                    "version": crate::build_info::VERSION,
+                },
+            },
+            "columnKind": column_kind,
+            "results": results,
+        }],
+    });
+
+    serde_json::to_writer_pretty(&mut *w, &log)?;
+    writeln!(w)
+}
+
+/// Writes the diagnostics as GitHub Actions workflow commands.
+fn write_github(w: &mut impl Write, records: &[DiagnosticRecord]) -> std::io::Result<()> {
+    let escape_data = |s: &str| {
+        s.replace('%', "%25")
+            .replace('\r', "%0D")
+            .replace('\n', "%0A")
+    };
+    let escape_property = |s: &str| escape_data(s).replace(':', "%3A").replace(',', "%2C");
+
+    // GitHub resolves the files relative to the workspace.
+    let cwd = std::env::current_dir().ok();
+    let relative = |path: &str| match &cwd {
+        Some(cwd) => Path::new(path)
+            .strip_prefix(cwd)
+            .map_or_else(|_| path.to_owned(), |path| path.display().to_string()),
+        None => path.to_owned(),
+    };
+
+    for record in records {
+        let mut message = record.message.to_string();
+        for hint in &record.hints {
+            message.push_str(&format!("\nhint: {hint}"));
+        }
+        for point in &record.trace {
+            match &point.location {
+                Some(loc) => message.push_str(&format!(
+                    "\n{}:{}:{}: {}",
+                    relative(&loc.path),
+                    loc.line,
+                    loc.column,
+                    point.message
+                )),
+                None => message.push_str(&format!("\n{}", point.message)),
+            }
+        }
+
+        let mut properties = vec![];
+        if let Some(loc) = &record.location {
+            properties.push(format!("file={}", escape_property(&relative(&loc.path))));
+            properties.push(format!("line={}", loc.line));
+            properties.push(format!("col={}", loc.column));
+            properties.push(format!("endLine={}", loc.end_line));
+            properties.push(format!("endColumn={}", loc.end_column));
+        }
+        properties.push("title=typst".to_owned());
+
+        writeln!(
+            w,
+            "::{} {}::{}",
+            record.severity,
+            properties.join(","),
+            escape_data(&message)
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Converts a path to a file URI.
+fn file_uri(path: &Path) -> String {
+    let path = path.display().to_string().replace('\\', "/");
+    let path = path.replace('%', "%25").replace(' ', "%20");
+    if path.starts_with('/') {
+        format!("file://{path}")
+    } else {
+        format!("file:///{path}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_column() {
+        let source = Source::detached("a\n😀bc");
+        let offset = "a\n😀b".len();
+
+        let column = |encoding| line_column(&source, offset, encoding);
+        assert_eq!(column(ColumnEncoding::Utf8), Some((2, 6)));
+        assert_eq!(column(ColumnEncoding::Utf16), Some((2, 4)));
+        assert_eq!(column(ColumnEncoding::Utf32), Some((2, 3)));
+    }
+
+    #[test]
+    fn test_github() {
+        let records = [DiagnosticRecord {
+            severity: "error",
+            message: "unknown variable: x".into(),
+            location: Some(DiagnosticLocation {
+                path: "main,1.typ".to_owned(),
+                line: 1,
+                column: 2,
+                end_line: 1,
+                end_column: 3,
+            }),
+            hints: vec!["if you meant to display multiple letters as is, try adding spaces".into()],
+            trace: vec![],
+        }];
+
+        let mut out = vec![];
+        write_github(&mut out, &records).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "::error file=main%2C1.typ,line=1,col=2,endLine=1,endColumn=3,title=typst::unknown variable: x%0Ahint: if you meant to display multiple letters as is, try adding spaces\n"
+        );
+    }
+}
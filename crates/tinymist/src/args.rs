@@ -1,8 +1,12 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use sync_ls::transport::MirrorArgs;
-use tinymist::project::DocCommands;
+use sync_ls::transport::{ListenAddr, MirrorArgs};
+use tinymist::project::{DocBuildArgs, DocCommands};
+use tinymist::tool::build::BuildArgs;
+use tinymist::tool::deps::DepsArgs;
+use tinymist::tool::lint::LintArgs;
 use tinymist::tool::project::{CompileArgs, GenerateScriptArgs, TaskCommands};
+use tinymist::tool::query::FilePosition;
 use tinymist::tool::testing::TestArgs;
 use tinymist::{CompileFontArgs, CompileOnceArgs};
 use tinymist_core::LONG_VERSION;
@@ -41,6 +45,12 @@ pub enum Commands {
     Test(TestArgs),
     /// Runs compile command like `typst-cli compile`
     Compile(CompileArgs),
+    /// Runs the tasks in the lock file, skipping the up-to-date ones
+    Build(BuildArgs),
+    /// Prints the dependency graph of a document
+    Deps(DepsArgs),
+    /// Lints the source files of a document
+    Lint(LintArgs),
     /// Generates build script for compilation
     #[clap(hide(true))] // still in development
     GenerateScript(GenerateScriptArgs),
@@ -159,6 +169,11 @@ pub struct TraceLspArgs {
 pub struct LspArgs {
     #[clap(flatten)]
     pub mirror: MirrorArgs,
+    /// Listens on the address to serve multiple editors in a single server
+    /// instead of the stdio, e.g. `127.0.0.1:9257`, `unix:///tmp/tinymist.sock`
+    /// or `ws://127.0.0.1:9257`.
+    #[clap(long, value_name = "ADDR", conflicts_with_all = ["mirror", "replay"])]
+    pub listen: Option<ListenAddr>,
     #[clap(flatten)]
     pub font: CompileFontArgs,
 }
@@ -172,6 +187,84 @@ pub enum QueryCommands {
     PackageDocs(PackageDocsArgs),
     /// Check a specific package.
     CheckPackage(PackageDocsArgs),
+    /// Count words of a document per section.
+    WordCount(WordCountArgs),
+    /// Build a static documentation site for a package, which is invoked by
+    /// `tinymist doc build`.
+    #[clap(hide(true))]
+    DocBuild(DocBuildArgs),
+    /// Find the definition of the symbol at a position.
+    Definition(PositionQueryArgs),
+    /// Find the references to the symbol at a position.
+    References(PositionQueryArgs),
+    /// Get the hover content at a position.
+    Hover(PositionQueryArgs),
+    /// List the symbols in a document.
+    Symbols(FileQueryArgs),
+    /// List the labels in the workspace.
+    Labels(QueryOutputArgs),
+    /// Check a document and list the diagnostics.
+    Diagnostics(FileQueryArgs),
+    /// Rename the symbol at a position, which rewrites the files in place
+    /// unless `--dry-run` is given.
+    Rename(RenameQueryArgs),
+}
+
+impl QueryCommands {
+    /// Gets the output arguments of the language queries.
+    pub fn output(&self) -> Option<&QueryOutputArgs> {
+        match self {
+            Self::Definition(args) | Self::References(args) | Self::Hover(args) => Some(&args.out),
+            Self::Symbols(args) | Self::Diagnostics(args) => Some(&args.out),
+            Self::Labels(args) => Some(args),
+            Self::Rename(args) => Some(&args.out),
+            Self::PackageDocs(..) | Self::CheckPackage(..) | Self::WordCount(..) => None,
+            Self::DocBuild(..) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, clap::Parser)]
+pub struct QueryOutputArgs {
+    /// The root directory of the workspace, which defaults to the current
+    /// directory.
+    #[clap(long)]
+    pub root: Option<PathBuf>,
+    /// The output path for the result in JSON, which defaults to the stdout.
+    #[clap(short, long)]
+    pub output: Option<String>,
+}
+
+#[derive(Debug, Clone, clap::Parser)]
+pub struct PositionQueryArgs {
+    /// The position to query, in the form of `file:line:column`. The line and
+    /// the column are 1-based.
+    pub position: FilePosition,
+    #[clap(flatten)]
+    pub out: QueryOutputArgs,
+}
+
+#[derive(Debug, Clone, clap::Parser)]
+pub struct FileQueryArgs {
+    /// The path of the document to query.
+    pub input: String,
+    #[clap(flatten)]
+    pub out: QueryOutputArgs,
+}
+
+#[derive(Debug, Clone, clap::Parser)]
+pub struct RenameQueryArgs {
+    /// The position of the symbol to rename, in the form of
+    /// `file:line:column`. The line and the column are 1-based.
+    pub position: FilePosition,
+    /// The new name of the symbol.
+    pub new_name: String,
+    /// Prints the edits without applying them to the files. By default, the
+    /// edits are applied to the files in place and then printed.
+    #[clap(long)]
+    pub dry_run: bool,
+    #[clap(flatten)]
+    pub out: QueryOutputArgs,
 }
 
 #[derive(Debug, Clone, clap::Parser)]
@@ -190,6 +283,15 @@ pub struct PackageDocsArgs {
     // pub format: Option<QueryDocsFormat>,
 }
 
+#[derive(Debug, Clone, clap::Parser)]
+pub struct WordCountArgs {
+    /// The path of the document to count words.
+    pub input: String,
+    /// The output path for the statistics in JSON.
+    #[clap(short, long)]
+    pub output: String,
+}
+
 #[derive(Debug, Clone, Default, clap::ValueEnum)]
 #[clap(rename_all = "camelCase")]
 pub enum QueryDocsFormat {
@@ -10,11 +10,13 @@ use sync_ls::RequestId;
 use task::TraceParams;
 use tinymist_assets::TYPST_PREVIEW_HTML;
 use tinymist_project::{
-    ExportHtmlTask, ExportMarkdownTask, ExportPdfTask, ExportPngTask, ExportSvgTask, ExportTask,
-    ExportTextTask, ExportTransform, PageSelection, Pages, ProjectTask, QueryTask,
+    CompiledArtifact, ExportHtmlTask, ExportMarkdownTask, ExportPdfTask, ExportPngTask,
+    ExportSvgTask, ExportTask, ExportTextTask, ExportTransform, PageSelection, Pages, ProjectTask,
+    QueryTask,
 };
+use tinymist_query::docs::DocsSite;
 use tinymist_query::package::PackageInfo;
-use tinymist_query::{LocalContextGuard, LspRange};
+use tinymist_query::{check_doc_with_analysis, DiagnosticsMap, LocalContextGuard, LspRange};
 use tinymist_std::error::prelude::*;
 use typst::diag::{eco_format, EcoString, StrResult};
 use typst::syntax::package::{PackageSpec, VersionlessPackageSpec};
@@ -24,7 +26,9 @@ use world::TaskInputs;
 use super::*;
 use crate::lsp::query::{run_query, LspClientExt};
 use crate::tool::ast::AstRepr;
+use crate::tool::deps::DependencyGraph;
 use crate::tool::package::InitTask;
+use crate::tool::word_count::{section_word_count, SectionWordsCount};
 
 /// See [`ProjectTask`].
 #[derive(Debug, Clone, Default, Deserialize)]
@@ -85,6 +89,11 @@ impl ServerState {
                 export,
                 pdf_standards: pdf_standards.unwrap_or_default(),
                 creation_timestamp,
+                ident: None,
+                title: None,
+                author: vec![],
+                keywords: vec![],
+                attachments: vec![],
             }),
             opts.open.unwrap_or_default(),
             args,
@@ -548,6 +557,65 @@ impl ServerState {
         run_query!(req_id, self.DocumentMetrics(path))
     }
 
+    /// Get the word count and writing statistics of the document per section.
+    pub fn get_word_count(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        let path = get_arg!(args[0] as PathBuf);
+
+        let fut = self.get_word_count_(path)?;
+        just_future(async move { serde_json::to_value(fut.await?).map_err(internal_error) })
+    }
+
+    /// Get the word count and writing statistics of the document per section.
+    pub fn get_word_count_(
+        &mut self,
+        path: PathBuf,
+    ) -> LspResult<impl Future<Output = LspResult<Vec<SectionWordsCount>>>> {
+        let input = self.resolve_task(path.as_path().into());
+        let encoding = self.const_config().position_encoding;
+        let snap = self.snapshot().map_err(internal_error)?;
+
+        Ok(async move {
+            let artifact = CompiledArtifact::from_graph(snap.task(input), false);
+            let doc = artifact
+                .doc
+                .as_ref()
+                .ok_or_else(|| internal_error("cannot compile the document"))?;
+
+            Ok(section_word_count(artifact.world(), doc, encoding))
+        })
+    }
+
+    /// Checks the document and gets the diagnostics of the files.
+    pub fn get_diagnostics_(
+        &mut self,
+        path: PathBuf,
+    ) -> LspResult<impl Future<Output = LspResult<DiagnosticsMap>>> {
+        let input = self.resolve_task(path.as_path().into());
+        let analysis = self.project.analysis.clone();
+        let snap = self.snapshot().map_err(internal_error)?;
+
+        Ok(async move {
+            let artifact = CompiledArtifact::from_graph(snap.task(input), false);
+            let mut ctx = analysis.snapshot(artifact.world().clone());
+            let (diags, doc) = (artifact.diagnostics(), artifact.doc.as_ref());
+
+            Ok(check_doc_with_analysis(&mut ctx, diags, doc))
+        })
+    }
+
+    /// Get the dependency graph of the document.
+    pub fn get_document_deps(&mut self, mut args: Vec<JsonValue>) -> AnySchedulableResponse {
+        let path = get_arg!(args[0] as PathBuf);
+        let input = self.resolve_task(path.as_path().into());
+        let snap = self.snapshot().map_err(internal_error)?;
+
+        just_future(async move {
+            let artifact = CompiledArtifact::from_graph(snap.task(input), false);
+            let deps = DependencyGraph::new(artifact.world(), artifact.doc.as_ref());
+            serde_json::to_value(deps).map_err(internal_error)
+        })
+    }
+
     /// Get all syntactic labels in workspace.
     pub fn get_workspace_labels(
         &mut self,
@@ -677,6 +745,18 @@ impl ServerState {
         })
     }
 
+    /// Get the static documentation site of a package
+    pub fn resource_package_site_(
+        &mut self,
+        info: PackageInfo,
+    ) -> LspResult<impl Future<Output = LspResult<DocsSite>>> {
+        self.within_package(info.clone(), move |a| {
+            tinymist_query::docs::package_site(a, &info)
+                .map_err(map_string_err("failed to generate docs site"))
+                .map_err(internal_error)
+        })
+    }
+
     /// Check package
     pub fn check_package(
         &mut self,
@@ -5,6 +5,7 @@ use std::sync::{Arc, LazyLock, OnceLock};
 use clap::Parser;
 use itertools::Itertools;
 use lsp_types::*;
+use parking_lot::Mutex;
 use reflexo::error::IgnoreLogging;
 use reflexo::CowStr;
 use reflexo_typst::{ImmutPath, TypstDict};
@@ -26,7 +27,7 @@ use super::*;
 use crate::project::font::TinymistFontResolver;
 use crate::project::{
     EntryResolver, ExportPdfTask, ExportTask, ImmutDict, PathPattern, ProjectResolutionKind,
-    ProjectTask, TaskWhen,
+    ProjectTask, TaskVariant, TaskWhen,
 };
 
 // region Configuration Items
@@ -35,8 +36,12 @@ const CONFIG_ITEMS: &[&str] = &[
     "colorTheme",
     "compileStatus",
     "completion",
+    "docsIndex",
+    "exportDebounce",
+    "exportMatrix",
     "exportPdf",
     "exportTarget",
+    "exportWatch",
     "fontPaths",
     "formatterMode",
     "formatterPrintWidth",
@@ -75,6 +80,9 @@ pub struct Config {
 
     /// The preferred color theme for rendering.
     pub color_theme: Option<String>,
+    /// Whether to persist the documentation of package definitions on the
+    /// disk.
+    pub docs_index: Option<bool>,
     /// The entry resolver.
     pub entry_resolver: EntryResolver,
     /// The `sys.inputs` passed to the typst compiler.
@@ -104,8 +112,14 @@ pub struct Config {
     pub export_target: ExportTarget,
     /// The mode of PDF export.
     pub export_pdf: TaskWhen,
+    /// The idle time in milliseconds before exporting PDF on idle.
+    pub export_debounce: Option<u64>,
+    /// The glob patterns of the files whose changes export PDF.
+    pub export_watch: Vec<String>,
     /// The output directory for PDF export.
     pub output_path: PathPattern,
+    /// The variants of the document to export.
+    pub export_matrix: Vec<TaskVariant>,
 
     /// Dynamic configuration for the experimental formatter.
     pub formatter_mode: FormatterMode,
@@ -119,6 +133,47 @@ pub struct Config {
     pub warnings: Vec<CowStr>,
 }
 
+/// The recently used font resolvers, keyed by the font options. They are
+/// shared by the connections served by the process and by the exported
+/// variants, so fonts are scanned only once for the same font options.
+static SHARED_FONTS: Mutex<Vec<(CompileFontArgs, Arc<TinymistFontResolver>)>> =
+    Mutex::new(Vec::new());
+const MAX_SHARED_FONTS: usize = 4;
+
+/// Gets a recently used font resolver, which is marked as the most recently
+/// used.
+fn recent_fonts(opts: &CompileFontArgs) -> Option<Arc<TinymistFontResolver>> {
+    let mut shared = SHARED_FONTS.lock();
+    let idx = shared.iter().position(|(args, _)| args == opts)?;
+    let entry = shared.remove(idx);
+    let fonts = entry.1.clone();
+    shared.push(entry);
+    Some(fonts)
+}
+
+/// Gets the font resolver of the font options, which is created and cached if
+/// it is not recently used.
+pub(crate) fn shared_fonts(opts: CompileFontArgs) -> Result<Arc<TinymistFontResolver>> {
+    if let Some(fonts) = recent_fonts(&opts) {
+        return Ok(fonts);
+    }
+
+    // The fonts are scanned without holding the lock, which would block the
+    // other connections.
+    log::info!("creating SharedFontResolver with {opts:?}");
+    let fonts = Arc::new(crate::project::LspUniverseBuilder::resolve_fonts(
+        opts.clone(),
+    )?);
+
+    let mut shared = SHARED_FONTS.lock();
+    shared.retain(|(args, _)| *args != opts);
+    if shared.len() >= MAX_SHARED_FONTS {
+        shared.remove(0);
+    }
+    shared.push((opts, fonts.clone()));
+    Ok(fonts)
+}
+
 impl Config {
     /// Creates a new configuration with system defaults.
     pub fn new(
@@ -304,14 +359,18 @@ impl Config {
         }
 
         assign_config!(color_theme := "colorTheme"?: Option<String>);
+        assign_config!(docs_index := "docsIndex"?: Option<bool>);
         assign_config!(completion := "completion"?: CompletionFeat);
         assign_config!(completion.trigger_suggest := "triggerSuggest"?: bool);
         assign_config!(completion.trigger_parameter_hints := "triggerParameterHints"?: bool);
         assign_config!(completion.trigger_suggest_and_parameter_hints := "triggerSuggestAndParameterHints"?: bool);
         assign_config!(customized_show_document := "customizedShowDocument"?: bool);
         assign_config!(entry_resolver.project_resolution := "projectResolution"?: ProjectResolutionKind);
+        assign_config!(export_debounce := "exportDebounce"?: Option<u64>);
+        assign_config!(export_matrix := "exportMatrix"?: Vec<TaskVariant>);
         assign_config!(export_pdf := "exportPdf"?: TaskWhen);
         assign_config!(export_target := "exportTarget"?: ExportTarget);
+        assign_config!(export_watch := "exportWatch"?: Vec<String>);
         assign_config!(font_paths := "fontPaths"?: Vec<_>);
         assign_config!(formatter_mode := "formatterMode"?: FormatterMode);
         assign_config!(formatter_print_width := "formatterPrintWidth"?: Option<u32>);
@@ -485,8 +544,11 @@ impl Config {
     pub(crate) fn export_task(&self) -> ExportTask {
         ExportTask {
             when: self.export_pdf,
+            debounce: self.export_debounce,
+            watch: self.export_watch.clone(),
             output: Some(self.output_path.clone()),
             transform: vec![],
+            matrix: self.export_matrix.clone(),
         }
     }
 
@@ -508,8 +570,14 @@ impl Config {
                 export,
                 pdf_standards: self.pdf_standards().unwrap_or_default(),
                 creation_timestamp: self.creation_timestamp(),
+                ident: None,
+                title: None,
+                author: vec![],
+                keywords: vec![],
+                attachments: vec![],
             }),
             count_words: self.notify_status,
+            font: self.font_opts(),
         }
     }
 
@@ -554,21 +622,24 @@ impl Config {
     }
 
     /// Determines the font resolver.
+    ///
+    /// The font resolvers are shared by all the connections served by the
+    /// process, so fonts are scanned only once for the same font options.
     pub fn fonts(&self) -> Arc<TinymistFontResolver> {
         // todo: on font resolving failure, downgrade to a fake font book
         let font = || {
-            let opts = self.font_opts();
-
-            log::info!("creating SharedFontResolver with {opts:?}");
-            Derived(
-                crate::project::LspUniverseBuilder::resolve_fonts(opts)
-                    .map(Arc::new)
-                    .expect("failed to create font book"),
-            )
+            let fonts = shared_fonts(self.font_opts()).expect("failed to create font book");
+            Derived(fonts)
         };
         self.fonts.get_or_init(font).clone().0
     }
 
+    /// Whether the font resolver is determined without scanning fonts.
+    pub fn has_fonts(&self) -> bool {
+        let opts = self.font_opts();
+        self.fonts.get().is_some() || SHARED_FONTS.lock().iter().any(|(args, _)| *args == opts)
+    }
+
     /// Determines the `sys.inputs` for the entry file.
     pub fn inputs(&self) -> ImmutDict {
         #[comemo::memoize]
@@ -656,6 +727,8 @@ pub struct ConstConfig {
     pub doc_line_folding_only: bool,
     /// Allow dynamic registration of document formatting.
     pub doc_fmt_dynamic_registration: bool,
+    /// Allow server-initiated work done progress.
+    pub work_done_progress: bool,
     /// The locale of the editor.
     pub locale: Option<String>,
 }
@@ -688,6 +761,7 @@ impl From<&InitializeParams> for ConstConfig {
         let sema = try_(|| doc?.semantic_tokens.as_ref());
         let fold = try_(|| doc?.folding_range.as_ref());
         let format = try_(|| doc?.formatting.as_ref());
+        let window = params.capabilities.window.as_ref();
 
         let locale = params
             .initialization_options
@@ -704,6 +778,7 @@ impl From<&InitializeParams> for ConstConfig {
             tokens_multiline_token_support: try_or(|| sema?.multiline_token_support, false),
             doc_line_folding_only: try_or(|| fold?.line_folding_only, true),
             doc_fmt_dynamic_registration: try_or(|| format?.dynamic_registration, false),
+            work_done_progress: try_or(|| window?.work_done_progress, false),
             locale: locale.map(ToOwned::to_owned),
         }
     }
@@ -876,6 +951,9 @@ mod tests {
         let update = json!({
             "outputPath": "out",
             "exportPdf": "onSave",
+            "exportMatrix": [{ "name": "de", "inputs": [["lang", "de"]] }],
+            "exportDebounce": 1000,
+            "exportWatch": ["data/*.csv"],
             "rootPath": root_path,
             "semanticTokens": "enable",
             "formatterMode": "typstyle",
@@ -894,6 +972,12 @@ mod tests {
 
         assert_eq!(config.output_path, PathPattern::new("out"));
         assert_eq!(config.export_pdf, TaskWhen::OnSave);
+        assert_eq!(
+            config.export_matrix,
+            vec!["de:lang=de".parse::<TaskVariant>().unwrap()]
+        );
+        assert_eq!(config.export_debounce, Some(1000));
+        assert_eq!(config.export_watch, vec!["data/*.csv".to_owned()]);
         assert_eq!(
             config.entry_resolver.root_path,
             Some(ImmutPath::from(root_path))
@@ -1,9 +1,11 @@
+use std::sync::Arc;
+
 use lsp_types::*;
-use reflexo_typst::Bytes;
+use reflexo_typst::{Bytes, LazyHash};
 use tinymist_query::{to_typst_range, PositionEncoding};
 use tinymist_std::error::prelude::*;
 use tinymist_std::ImmutPath;
-use typst::{diag::FileResult, syntax::Source};
+use typst::{diag::FileResult, foundations::Value, syntax::Source};
 
 use crate::project::{Interrupt, ProjectResolutionKind};
 use crate::route::ProjectResolution;
@@ -200,7 +202,8 @@ impl ServerState {
     fn resolve_task_without_lock(&self, path: Option<ImmutPath>) -> TaskInputs {
         TaskInputs {
             entry: Some(self.entry_resolver().resolve(path)),
-            ..TaskInputs::default()
+            // Resets the inputs that could be injected by a locked document.
+            inputs: Some(self.config.inputs()),
         }
     }
 
@@ -240,9 +243,19 @@ impl ServerState {
                 .resolve_with_root(Some(root), Some(main));
             log::info!("resolved task with state: {path:?} -> {project_id:?} -> {entry:?}");
 
+            // The inputs of the document are visible through `sys.inputs`, along with
+            // the inputs of the editor, and the inputs of the server take precedence.
+            let mut inputs = (**self.config.inputs()).clone();
+            for (key, value) in &input.inputs {
+                inputs.insert(key.as_str().into(), Value::Str(value.as_str().into()));
+            }
+            for (key, value) in self.config.lsp_inputs.iter() {
+                inputs.insert(key.clone(), value.clone());
+            }
+
             Some(TaskInputs {
                 entry: Some(entry),
-                ..TaskInputs::default()
+                inputs: Some(Arc::new(LazyHash::new(inputs))),
             })
         });
 
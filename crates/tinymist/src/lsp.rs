@@ -150,7 +150,8 @@ impl ServerState {
         }
 
         if old_config.primary_opts() != self.config.primary_opts() {
-            self.config.fonts = OnceLock::new(); // todo: don't reload fonts if not changed
+            // Fonts are reused if the font options are not changed.
+            self.config.fonts = OnceLock::new();
             self.reload_projects()
                 .log_error("could not restart primary");
         }
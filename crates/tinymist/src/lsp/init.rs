@@ -48,8 +48,19 @@ impl Initializer for RegularInit {
     /// # Errors
     /// Errors if the configuration could not be updated.
     fn initialize(self, params: InitializeParams) -> (ServerState, AnySchedulableResponse) {
+        let init_progress = params.work_done_progress_params.work_done_token.clone();
         let (config, err) = Config::extract_lsp_params(params, self.font_opts);
 
+        // Scans fonts eagerly to report the progress with the token of the
+        // initialize request, since the server cannot create progresses before
+        // it is initialized.
+        if let Some(token) = init_progress.filter(|_| !config.has_fonts()) {
+            let _progress = self
+                .client
+                .begin_progress_with(token, "Scanning fonts", false);
+            config.fonts();
+        }
+
         let super_init = SuperInit {
             client: self.client,
             exec_cmds: self.exec_cmds,
@@ -136,7 +147,11 @@ impl Initializer for SuperInit {
                     },
                 }),
                 definition_provider: Some(OneOf::Left(true)),
-                references_provider: Some(OneOf::Left(true)),
+                references_provider: Some(OneOf::Right(ReferencesOptions {
+                    work_done_progress_options: WorkDoneProgressOptions {
+                        work_done_progress: Some(true),
+                    },
+                })),
                 completion_provider: Some(CompletionOptions {
                     // Please update the language-configuration.json if you are changing this
                     // setting.
@@ -170,13 +185,21 @@ impl Initializer for SuperInit {
                 }),
                 color_provider: Some(ColorProviderCapability::Simple(true)),
                 document_highlight_provider: Some(OneOf::Left(true)),
+                linked_editing_range_provider: Some(LinkedEditingRangeServerCapabilities::Simple(
+                    true,
+                )),
                 document_symbol_provider: Some(OneOf::Left(true)),
-                workspace_symbol_provider: Some(OneOf::Left(true)),
+                workspace_symbol_provider: Some(OneOf::Right(WorkspaceSymbolOptions {
+                    resolve_provider: None,
+                    work_done_progress_options: WorkDoneProgressOptions {
+                        work_done_progress: Some(true),
+                    },
+                })),
                 selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
                 rename_provider: Some(OneOf::Right(RenameOptions {
                     prepare_provider: Some(true),
                     work_done_progress_options: WorkDoneProgressOptions {
-                        work_done_progress: None,
+                        work_done_progress: Some(true),
                     },
                 })),
                 document_link_provider: Some(DocumentLinkOptions {
@@ -42,12 +42,41 @@ macro_rules! run_query {
     ($req_id: ident, $self: ident.$query: ident ($($arg_key:ident),* $(,)?)) => {{
         use tinymist_query::*;
         let req = paste::paste! { [<$query Request>] { $($arg_key),* } };
-        let query_fut = $self.query(CompilerQueryRequest::$query(req.clone()));
+        let cancel = $self.client.untyped().cancellation_token(&$req_id);
+        let query_fut = $self.query(CompilerQueryRequest::$query(req.clone()), cancel);
+        $self.client.untyped().schedule_query($req_id, query_fut)
+    }};
+    ($req_id: ident, $self: ident.$query: ident ($($arg_key:ident),* $(,)?), $progress: expr) => {{
+        use tinymist_query::*;
+        let req = paste::paste! { [<$query Request>] { $($arg_key),* } };
+        let progress: Option<ProgressHandle> = $progress;
+        let cancel = match &progress {
+            Some(progress) => Some(progress.cancellation_token()),
+            None => $self.client.untyped().cancellation_token(&$req_id),
+        };
+        let query_fut = $self.query(CompilerQueryRequest::$query(req.clone()), cancel);
+        let query_fut = with_progress(query_fut, progress);
         $self.client.untyped().schedule_query($req_id, query_fut)
     }};
 }
 pub(crate) use run_query;
 
+/// Keeps the progress of a query until the query is finished.
+pub(crate) fn with_progress(fut: QueryFuture, progress: Option<ProgressHandle>) -> QueryFuture {
+    let Some(progress) = progress else {
+        return fut;
+    };
+
+    Ok(match fut? {
+        MaybeDone::Future(fut) => MaybeDone::Future(Box::pin(async move {
+            let res = fut.await;
+            drop(progress);
+            res
+        })),
+        done => done,
+    })
+}
+
 /// LSP Standard Language Features
 impl ServerState {
     pub(crate) fn goto_definition(
@@ -74,7 +103,12 @@ impl ServerState {
         params: ReferenceParams,
     ) -> ScheduledResult {
         let (path, position) = as_path_pos(params.text_document_position);
-        run_query!(req_id, self.References(path, position))
+        let progress = self.request_progress(
+            &req_id,
+            params.work_done_progress_params,
+            "Finding references",
+        );
+        run_query!(req_id, self.References(path, position), progress)
     }
 
     pub(crate) fn hover(&mut self, req_id: RequestId, params: HoverParams) -> ScheduledResult {
@@ -115,6 +149,15 @@ impl ServerState {
         run_query!(req_id, self.DocumentHighlight(path, position))
     }
 
+    pub(crate) fn linked_editing_range(
+        &mut self,
+        req_id: RequestId,
+        params: LinkedEditingRangeParams,
+    ) -> ScheduledResult {
+        let (path, position) = as_path_pos(params.text_document_position_params);
+        run_query!(req_id, self.LinkedEditingRange(path, position))
+    }
+
     pub(crate) fn document_symbol(
         &mut self,
         req_id: RequestId,
@@ -253,7 +296,8 @@ impl ServerState {
     pub(crate) fn rename(&mut self, req_id: RequestId, params: RenameParams) -> ScheduledResult {
         let (path, position) = as_path_pos(params.text_document_position);
         let new_name = params.new_name;
-        run_query!(req_id, self.Rename(path, position, new_name))
+        let progress = self.request_progress(&req_id, params.work_done_progress_params, "Renaming");
+        run_query!(req_id, self.Rename(path, position, new_name), progress)
     }
 
     pub(crate) fn prepare_rename(
@@ -271,7 +315,12 @@ impl ServerState {
         params: WorkspaceSymbolParams,
     ) -> ScheduledResult {
         let pattern = (!params.query.is_empty()).then_some(params.query);
-        run_query!(req_id, self.Symbol(pattern))
+        let progress = self.request_progress(
+            &req_id,
+            params.work_done_progress_params,
+            "Searching symbols",
+        );
+        run_query!(req_id, self.Symbol(pattern), progress)
     }
 
     pub(crate) fn on_enter(&mut self, req_id: RequestId, params: OnEnterParams) -> ScheduledResult {
@@ -315,8 +364,24 @@ macro_rules! query_source {
 }
 
 impl ServerState {
+    /// Begins a work done progress for a request if the client provides a
+    /// token. The query is cancelled if the user cancels the progress.
+    fn request_progress(
+        &self,
+        req_id: &RequestId,
+        params: WorkDoneProgressParams,
+        title: &str,
+    ) -> Option<ProgressHandle> {
+        let token = params.work_done_token?;
+        Some(self.client.begin_request_progress(req_id, token, title))
+    }
+
     /// Perform a language query.
-    pub fn query(&mut self, query: CompilerQueryRequest) -> QueryFuture {
+    pub fn query(
+        &mut self,
+        query: CompilerQueryRequest,
+        cancel: Option<CancellationToken>,
+    ) -> QueryFuture {
         use CompilerQueryRequest::*;
 
         just_ok(match query {
@@ -328,11 +393,15 @@ impl ServerState {
             OnExport(req) => return self.on_export(req),
             ServerInfo(_) => return self.collect_server_info(),
             // todo: query on dedicate projects
-            _ => return self.query_on(query),
+            _ => return self.query_on(query, cancel),
         })
     }
 
-    fn query_on(&mut self, query: CompilerQueryRequest) -> QueryFuture {
+    fn query_on(
+        &mut self,
+        query: CompilerQueryRequest,
+        cancel: Option<CancellationToken>,
+    ) -> QueryFuture {
         use CompilerQueryRequest::*;
         type R = CompilerQueryResponse;
         assert!(query.fold_feature() != FoldRequestFeature::ContextFreeUnique);
@@ -355,6 +424,9 @@ impl ServerState {
                 snap = snap.task(input);
             }
         }
+        if let Some(cancel) = cancel {
+            snap = snap.with_cancel(cancel);
+        }
 
         just_future(async move {
             stat.snap();
@@ -379,6 +451,7 @@ impl ServerState {
                 References(req) => snap.run_stateful(req, R::References),
                 InlayHint(req) => snap.run_semantic(req, R::InlayHint),
                 DocumentHighlight(req) => snap.run_semantic(req, R::DocumentHighlight),
+                LinkedEditingRange(req) => snap.run_semantic(req, R::LinkedEditingRange),
                 DocumentColor(req) => snap.run_semantic(req, R::DocumentColor),
                 DocumentLink(req) => snap.run_semantic(req, R::DocumentLink),
                 CodeAction(req) => snap.run_semantic(req, R::CodeAction),
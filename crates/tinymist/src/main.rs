@@ -13,18 +13,27 @@ use clap_complete::generate;
 use futures::future::MaybeDone;
 use reflexo::ImmutPath;
 use reflexo_typst::package::PackageSpec;
-use sync_ls::transport::{with_stdio_transport, MirrorArgs};
+use sync_ls::transport::{with_listen_transport, with_stdio_transport};
 use sync_ls::{
-    internal_error, DapBuilder, DapMessage, LspBuilder, LspClientRoot, LspMessage, LspResult,
-    RequestId,
+    internal_error, invalid_params, Connection, DapBuilder, DapMessage, LspBuilder, LspClientRoot,
+    LspMessage, LspResult, RequestId,
 };
+use tinymist::project::DocCommands;
+use tinymist::tool::build::build_main;
+use tinymist::tool::deps::deps_main;
+use tinymist::tool::lint::lint_main;
 use tinymist::tool::project::{compile_main, generate_script_main, project_main, task_main};
+use tinymist::tool::query::{apply_workspace_edit, FilePosition};
 use tinymist::tool::testing::{coverage_main, test_main};
 use tinymist::world::TaskInputs;
 use tinymist::{Config, DapRegularInit, RegularInit, ServerState, SuperInit, UserActionTask};
 use tinymist_core::LONG_VERSION;
 use tinymist_project::EntryResolver;
 use tinymist_query::package::PackageInfo;
+use tinymist_query::{
+    CompilerQueryRequest, CompilerQueryResponse, DocumentSymbolRequest, GotoDefinitionRequest,
+    HoverRequest, LspPosition, ReferencesRequest, RenameRequest, WorkspaceLabelRequest,
+};
 use tinymist_std::{bail, error::prelude::*};
 
 #[cfg(feature = "l10n")]
@@ -99,6 +108,9 @@ fn main() -> Result<()> {
         Commands::Cov(args) => coverage_main(args),
         Commands::Test(args) => RUNTIMES.tokio_runtime.block_on(test_main(args)),
         Commands::Compile(args) => RUNTIMES.tokio_runtime.block_on(compile_main(args)),
+        Commands::Build(args) => RUNTIMES.tokio_runtime.block_on(build_main(args)),
+        Commands::Deps(args) => deps_main(args),
+        Commands::Lint(args) => lint_main(args),
         Commands::GenerateScript(args) => generate_script_main(args),
         Commands::Query(query_cmds) => query_main(query_cmds),
         Commands::Lsp(args) => lsp_main(args),
@@ -111,6 +123,7 @@ fn main() -> Result<()> {
 
             RUNTIMES.tokio_runtime.block_on(preview_main(args))
         }
+        Commands::Doc(DocCommands::Build(args)) => query_main(QueryCommands::DocBuild(args)),
         Commands::Doc(args) => project_main(args),
         Commands::Task(args) => task_main(args),
         Commands::Probe => Ok(()),
@@ -139,19 +152,25 @@ pub fn lsp_main(args: LspArgs) -> Result<()> {
     log::info!("starting language server: {args:?}");
 
     let is_replay = !args.mirror.replay.is_empty();
-    with_stdio_transport::<LspMessage>(args.mirror.clone(), |conn| {
+    let font_opts = args.font;
+    let serve = move |conn: Connection<LspMessage>| {
         let client = LspClientRoot::new(RUNTIMES.tokio_runtime.handle().clone(), conn.sender);
         ServerState::install_lsp(LspBuilder::new(
             RegularInit {
                 client: client.weak().to_typed(),
-                font_opts: args.font,
+                font_opts: font_opts.clone(),
                 exec_cmds: Vec::new(),
             },
             client.weak(),
         ))
         .build()
         .start(conn.receiver, is_replay)
-    })?;
+    };
+
+    match args.listen {
+        Some(addr) => with_listen_transport(addr, serve)?,
+        None => with_stdio_transport(args.mirror, serve)?,
+    }
 
     log::info!("language server did shut down");
     Ok(())
@@ -167,18 +186,24 @@ pub fn dap_main(args: DapArgs) -> Result<()> {
     log::info!("starting debug adaptor: {args:?}");
 
     let is_replay = !args.mirror.replay.is_empty();
-    with_stdio_transport::<DapMessage>(args.mirror.clone(), |conn| {
+    let font_opts = args.font;
+    let serve = move |conn: Connection<DapMessage>| {
         let client = LspClientRoot::new(RUNTIMES.tokio_runtime.handle().clone(), conn.sender);
         ServerState::install_dap(DapBuilder::new(
             DapRegularInit {
                 client: client.weak().to_typed(),
-                font_opts: args.font,
+                font_opts: font_opts.clone(),
             },
             client.weak(),
         ))
         .build()
         .start(conn.receiver, is_replay)
-    })?;
+    };
+
+    match args.listen {
+        Some(addr) => with_listen_transport(addr, serve)?,
+        None => with_stdio_transport(args.mirror, serve)?,
+    }
 
     log::info!("language server did shut down");
     Ok(())
@@ -260,79 +285,221 @@ pub fn trace_lsp_main(args: TraceLspArgs) -> Result<()> {
     Ok(())
 }
 
-/// The main entry point for language server queries.
-pub fn query_main(cmds: QueryCommands) -> Result<()> {
-    use tinymist_project::package::PackageRegistry;
+/// Resolves the position of a headless query.
+fn query_position(pos: &FilePosition) -> LspResult<(PathBuf, LspPosition)> {
+    let path = std::path::absolute(&pos.path).map_err(internal_error)?;
+    Ok((path, pos.to_lsp()))
+}
 
-    with_stdio_transport::<LspMessage>(MirrorArgs::default(), |conn| {
-        let client_root = LspClientRoot::new(RUNTIMES.tokio_runtime.handle().clone(), conn.sender);
-        let client = client_root.weak();
+/// Runs a language query without a client.
+async fn query_headless(
+    state: &mut ServerState,
+    query: CompilerQueryRequest,
+) -> LspResult<CompilerQueryResponse> {
+    let res = match state.query(query, None).map_err(internal_error)? {
+        MaybeDone::Done(res) => res,
+        MaybeDone::Future(fut) => fut.await,
+        MaybeDone::Gone => return Err(internal_error("query is gone")),
+    };
 
-        // todo: roots, inputs, font_opts
-        let config = Config::default();
+    res.map_err(internal_error)
+}
 
-        let mut service = ServerState::install_lsp(LspBuilder::new(
-            SuperInit {
-                client: client.to_typed(),
-                exec_cmds: Vec::new(),
-                config,
-                err: None,
-            },
-            client.clone(),
-        ))
-        .build();
+/// Writes the result of a headless query in JSON.
+fn write_query_output(args: &QueryOutputArgs, res: &impl serde::Serialize) -> LspResult<()> {
+    let res = serde_json::to_string_pretty(res).map_err(internal_error)?;
+    match &args.output {
+        Some(output) => std::fs::write(output, res).map_err(internal_error),
+        None => {
+            println!("{res}");
+            Ok(())
+        }
+    }
+}
 
-        let resp = service.ready(()).unwrap();
-        let MaybeDone::Done(resp) = resp else {
-            anyhow::bail!("internal error: not sync init")
-        };
-        resp.unwrap();
+/// The main entry point for language server queries.
+pub fn query_main(cmds: QueryCommands) -> Result<()> {
+    use tinymist_project::package::PackageRegistry;
 
-        let state = service.state_mut().unwrap();
+    // The language queries are resolved in the workspace of the root.
+    let root = match cmds.output().map(|out| out.root.as_ref()) {
+        Some(Some(root)) => Some(std::path::absolute(root).context("root")?),
+        Some(None) => Some(std::env::current_dir().context("cwd")?),
+        None => None,
+    };
+    let root = root.map(ImmutPath::from);
+
+    // The queries are run in process, so the server is not attached to a
+    // client, and the stdio is left for the output.
+    let conn = Connection::<LspMessage>::detached();
+    let client_root =
+        LspClientRoot::new(RUNTIMES.tokio_runtime.handle().clone(), conn.sender.clone());
+    let client = client_root.weak();
+
+    // todo: inputs, font_opts
+    let mut config = Config::default();
+    if let Some(root) = root {
+        config.entry_resolver.roots = vec![root];
+    }
 
-        let snap = state.snapshot().unwrap();
-        let res = RUNTIMES.tokio_runtime.block_on(async move {
-            match cmds {
-                QueryCommands::PackageDocs(args) => {
-                    let pkg = PackageSpec::from_str(&args.id).unwrap();
-                    let path = args.path.map(PathBuf::from);
-                    let path = path
-                        .unwrap_or_else(|| snap.registry().resolve(&pkg).unwrap().as_ref().into());
-
-                    let res = state
-                        .resource_package_docs_(PackageInfo {
-                            path,
-                            namespace: pkg.namespace,
-                            name: pkg.name,
-                            version: pkg.version.to_string(),
-                        })?
-                        .await?;
-
-                    let output_path = Path::new(&args.output);
-                    std::fs::write(output_path, res).map_err(internal_error)?;
+    let mut service = ServerState::install_lsp(LspBuilder::new(
+        SuperInit {
+            client: client.to_typed(),
+            exec_cmds: Vec::new(),
+            config,
+            err: None,
+        },
+        client.clone(),
+    ))
+    .build();
+
+    let resp = service.ready(()).unwrap();
+    let MaybeDone::Done(resp) = resp else {
+        bail!("internal error: not sync init")
+    };
+    resp.unwrap();
+
+    let state = service.state_mut().unwrap();
+
+    let snap = state.snapshot().unwrap();
+    let res = RUNTIMES.tokio_runtime.block_on(async move {
+        match cmds {
+            QueryCommands::PackageDocs(args) => {
+                let pkg = PackageSpec::from_str(&args.id).unwrap();
+                let path = match args.path {
+                    Some(path) => PathBuf::from(path),
+                    None => {
+                        let path = snap.registry().resolve(&pkg).map_err(internal_error)?;
+                        path.as_ref().into()
+                    }
+                };
+
+                let res = state
+                    .resource_package_docs_(PackageInfo {
+                        path,
+                        namespace: pkg.namespace,
+                        name: pkg.name,
+                        version: pkg.version.to_string(),
+                    })?
+                    .await?;
+
+                let output_path = Path::new(&args.output);
+                std::fs::write(output_path, res).map_err(internal_error)?;
+            }
+            QueryCommands::CheckPackage(args) => {
+                let pkg = PackageSpec::from_str(&args.id).unwrap();
+                let path = match args.path {
+                    Some(path) => PathBuf::from(path),
+                    None => {
+                        let path = snap.registry().resolve(&pkg).map_err(internal_error)?;
+                        path.as_ref().into()
+                    }
+                };
+
+                state
+                    .check_package(PackageInfo {
+                        path,
+                        namespace: pkg.namespace,
+                        name: pkg.name,
+                        version: pkg.version.to_string(),
+                    })?
+                    .await?;
+            }
+            QueryCommands::DocBuild(args) => {
+                let pkg = PackageSpec::from_str(&args.id).map_err(internal_error)?;
+                let path = match args.path {
+                    Some(path) => PathBuf::from(path),
+                    None => {
+                        let path = snap.registry().resolve(&pkg).map_err(internal_error)?;
+                        path.as_ref().into()
+                    }
+                };
+
+                let site = state
+                    .resource_package_site_(PackageInfo {
+                        path,
+                        namespace: pkg.namespace,
+                        name: pkg.name,
+                        version: pkg.version.to_string(),
+                    })?
+                    .await?;
+
+                let output_dir = Path::new(&args.output);
+                for (path, content) in site.files {
+                    let path = output_dir.join(path);
+                    if let Some(parent) = path.parent() {
+                        std::fs::create_dir_all(parent).map_err(internal_error)?;
+                    }
+                    std::fs::write(path, content).map_err(internal_error)?;
                 }
-                QueryCommands::CheckPackage(args) => {
-                    let pkg = PackageSpec::from_str(&args.id).unwrap();
-                    let path = args.path.map(PathBuf::from);
-                    let path = path
-                        .unwrap_or_else(|| snap.registry().resolve(&pkg).unwrap().as_ref().into());
-
-                    state
-                        .check_package(PackageInfo {
-                            path,
-                            namespace: pkg.namespace,
-                            name: pkg.name,
-                            version: pkg.version.to_string(),
-                        })?
-                        .await?;
+            }
+            QueryCommands::WordCount(args) => {
+                let input = std::path::absolute(&args.input).map_err(internal_error)?;
+                let res = state.get_word_count_(input)?.await?;
+                let res = serde_json::to_string_pretty(&res).map_err(internal_error)?;
+
+                let output_path = Path::new(&args.output);
+                std::fs::write(output_path, res).map_err(internal_error)?;
+            }
+            QueryCommands::Definition(args) => {
+                let (path, position) = query_position(&args.position)?;
+                let req = GotoDefinitionRequest { path, position };
+                let res = query_headless(state, CompilerQueryRequest::GotoDefinition(req));
+                write_query_output(&args.out, &res.await?)?;
+            }
+            QueryCommands::References(args) => {
+                let (path, position) = query_position(&args.position)?;
+                let req = ReferencesRequest { path, position };
+                let res = query_headless(state, CompilerQueryRequest::References(req));
+                write_query_output(&args.out, &res.await?)?;
+            }
+            QueryCommands::Hover(args) => {
+                let (path, position) = query_position(&args.position)?;
+                let req = HoverRequest { path, position };
+                let res = query_headless(state, CompilerQueryRequest::Hover(req));
+                write_query_output(&args.out, &res.await?)?;
+            }
+            QueryCommands::Symbols(args) => {
+                let path = std::path::absolute(&args.input).map_err(internal_error)?;
+                let req = DocumentSymbolRequest { path };
+                let res = query_headless(state, CompilerQueryRequest::DocumentSymbol(req));
+                write_query_output(&args.out, &res.await?)?;
+            }
+            QueryCommands::Labels(args) => {
+                let req = WorkspaceLabelRequest {};
+                let res = query_headless(state, CompilerQueryRequest::WorkspaceLabel(req));
+                write_query_output(&args, &res.await?)?;
+            }
+            QueryCommands::Diagnostics(args) => {
+                let input = std::path::absolute(&args.input).map_err(internal_error)?;
+                let res = state.get_diagnostics_(input)?.await?;
+                write_query_output(&args.out, &res)?;
+            }
+            QueryCommands::Rename(args) => {
+                let (path, position) = query_position(&args.position)?;
+                let new_name = args.new_name;
+                let req = RenameRequest {
+                    path,
+                    position,
+                    new_name,
+                };
+                let res = query_headless(state, CompilerQueryRequest::Rename(req)).await?;
+                let CompilerQueryResponse::Rename(Some(edit)) = &res else {
+                    return Err(invalid_params("cannot rename the symbol at the position"));
+                };
+
+                if !args.dry_run {
+                    let encoding = state.const_config().position_encoding;
+                    apply_workspace_edit(edit, encoding).map_err(internal_error)?;
                 }
-            };
+                write_query_output(&args.out, &res)?;
+            }
+        };
 
-            LspResult::Ok(())
-        });
+        LspResult::Ok(())
+    });
 
-        res.map_err(|e| anyhow::anyhow!("{e:?}"))
-    })?;
+    res.map_err(|e| anyhow::anyhow!("{e:?}"))?;
 
     Ok(())
 }
@@ -22,16 +22,21 @@
 use reflexo_typst::{diag::print_diagnostics, TypstDocument};
 pub use tinymist_project::*;
 
-use std::{num::NonZeroUsize, sync::Arc};
+use std::num::NonZeroUsize;
+use std::sync::{Arc, LazyLock};
 
 use parking_lot::Mutex;
 use reflexo::{hash::FxHashMap, path::unix_slash};
-use sync_ls::{LspClient, TypedLspClient};
+use sync_ls::{CancellationToken, LspClient, ProgressHandle, TypedLspClient};
+use tinymist_project::package::{Notifier, PackageSpec};
 use tinymist_project::vfs::{FileChangeSet, MemoryEvent};
 use tinymist_query::{
-    analysis::{Analysis, AnalysisRevLock, LocalContextGuard, PeriscopeProvider},
+    analysis::{
+        Analysis, AnalysisGlobalCaches, AnalysisGlobalWorkers, AnalysisRevLock, DocsIndex,
+        LocalContextGuard, PeriscopeProvider,
+    },
     CompilerQueryRequest, CompilerQueryResponse, DiagnosticsMap, LocalContext, SemanticRequest,
-    StatefulRequest,
+    SnippetLibrary, StatefulRequest,
 };
 use tinymist_render::PeriscopeRenderer;
 use tinymist_std::{error::prelude::*, ImmutPath};
@@ -85,6 +90,11 @@ impl ServerState {
         self.preview.stop_all();
         let editor_tx = self.editor_tx.clone();
 
+        // Fonts are rescanned if the font configuration has changed, unless
+        // the resolver is shared with another connection.
+        let rescan_fonts = !self.config.has_fonts();
+        let _progress = rescan_fonts.then(|| self.begin_progress("Scanning fonts", false));
+
         let new_project = Self::project(
             &self.config,
             editor_tx,
@@ -167,9 +177,15 @@ impl ServerState {
                     Arc::new(r) as Arc<dyn PeriscopeProvider + Send + Sync>
                 }),
                 tokens_caches: Arc::default(),
-                workers: Default::default(),
-                caches: Default::default(),
+                workers: SHARED_ANALYSIS.workers.clone(),
+                caches: SHARED_ANALYSIS.caches.clone(),
                 analysis_rev_cache: Arc::default(),
+                docs_index: SHARED_ANALYSIS
+                    .docs_index
+                    .clone()
+                    .filter(|_| config.docs_index != Some(false)),
+                snippets: SHARED_ANALYSIS.snippets.clone(),
+                snippet_files: SnippetLibrary::default_files(),
                 stats: Arc::default(),
             }),
 
@@ -188,7 +204,11 @@ impl ServerState {
         log::info!("ServerState: creating ProjectState, entry: {entry:?}, inputs: {inputs:?}");
 
         let fonts = config.fonts();
-        let packages = LspUniverseBuilder::resolve_package(cert_path.clone(), Some(&package));
+        let mut packages = LspUniverseBuilder::resolve_package(cert_path.clone(), Some(&package));
+        if const_config.work_done_progress {
+            let notifier = PackageProgress::new(client.clone().to_untyped());
+            packages = packages.with_notifier(Arc::new(Mutex::new(notifier)));
+        }
         let verse =
             LspUniverseBuilder::build(entry, export_target, features, inputs, packages, fonts);
 
@@ -222,6 +242,50 @@ impl ServerState {
     }
 }
 
+/// The analysis resources shared by all the connections served by the
+/// process, while the documents are still overlaid per connection.
+struct SharedAnalysis {
+    workers: Arc<AnalysisGlobalWorkers>,
+    caches: AnalysisGlobalCaches,
+    docs_index: Option<Arc<DocsIndex>>,
+    snippets: Arc<SnippetLibrary>,
+}
+
+static SHARED_ANALYSIS: LazyLock<SharedAnalysis> = LazyLock::new(|| SharedAnalysis {
+    workers: Arc::default(),
+    caches: AnalysisGlobalCaches::default(),
+    docs_index: DocsIndex::in_cache_dir().map(Arc::new),
+    snippets: Arc::default(),
+});
+
+/// Reports package downloads to the client as work done progresses.
+struct PackageProgress {
+    client: LspClient,
+    downloading: Mutex<FxHashMap<PackageSpec, ProgressHandle>>,
+}
+
+impl PackageProgress {
+    fn new(client: LspClient) -> Self {
+        Self {
+            client,
+            downloading: Mutex::default(),
+        }
+    }
+}
+
+impl Notifier for PackageProgress {
+    fn downloading(&self, spec: &PackageSpec) {
+        let progress = self
+            .client
+            .begin_progress(format!("Downloading {spec}"), false);
+        self.downloading.lock().insert(spec.clone(), progress);
+    }
+
+    fn downloaded(&self, spec: &PackageSpec) {
+        self.downloading.lock().remove(spec);
+    }
+}
+
 #[derive(Default)]
 pub struct ProjectInsStateExt {
     pub notified_revision: usize,
@@ -310,6 +374,7 @@ impl ProjectState {
             snap,
             analysis,
             rev_lock,
+            cancel: CancellationToken::new(),
         })
     }
 
@@ -451,12 +516,15 @@ impl CompileHandlerImpl {
 
         let snap = snap.clone();
         let editor_tx = self.editor_tx.clone();
-        let enc = self.analysis.position_encoding;
+        let analysis = self.analysis.clone();
         rayon::spawn(move || {
             let world = snap.world();
+            let mut ctx = analysis.snapshot(world.clone());
 
             // todo: check all errors in this file
-            let diagnostics = tinymist_query::check_doc(world, snap.diagnostics(), enc);
+            let doc = snap.success_doc();
+            let diagnostics =
+                tinymist_query::check_doc_with_analysis(&mut ctx, snap.diagnostics(), doc.as_ref());
 
             log::trace!("notify diagnostics({dv:?}): {diagnostics:#?}");
 
@@ -645,6 +713,7 @@ pub struct LspQuerySnapshot {
     pub snap: LspComputeGraph,
     analysis: Arc<Analysis>,
     rev_lock: AnalysisRevLock,
+    cancel: CancellationToken,
 }
 
 impl std::ops::Deref for LspQuerySnapshot {
@@ -661,6 +730,12 @@ impl LspQuerySnapshot {
         self
     }
 
+    /// Sets the token to cancel the analysis on the snapshot.
+    pub fn with_cancel(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
     pub fn run_stateful<T: StatefulRequest>(
         self,
         query: T,
@@ -686,7 +761,13 @@ impl LspQuerySnapshot {
             bail!("main file is not set");
         };
 
+        // The request may be cancelled while it is waiting in the queue.
+        if self.cancel.is_cancelled() {
+            bail!("request is cancelled");
+        }
+
         let mut analysis = self.analysis.snapshot_(world, self.rev_lock);
+        analysis.cancel = self.cancel;
         Ok(f(&mut analysis))
     }
 }
@@ -152,6 +152,13 @@ impl ServerState {
         &self.config.entry_resolver
     }
 
+    /// Begins a server-initiated work done progress if the client supports
+    /// it. The progress ends when the returned handle is dropped.
+    pub fn begin_progress(&self, title: &str, cancellable: bool) -> Option<ProgressHandle> {
+        let supported = self.const_config().work_done_progress;
+        supported.then(|| self.client.begin_progress(title, cancellable))
+    }
+
     /// Whether the main file is pinning.
     pub fn is_pinning(&self) -> bool {
         self.pinning_by_user
@@ -213,7 +220,6 @@ impl ServerState {
             .with_command("tinymist.doStartBrowsingPreview", State::browse_preview)
             .with_command("tinymist.doKillPreview", State::kill_preview);
 
-        // todo: .on_sync_mut::<notifs::Cancel>(handlers::handle_cancel)?
         let mut provider = provider
             .with_request::<Shutdown>(State::shutdown)
             // customized event
@@ -230,6 +236,7 @@ impl ServerState {
             .with_request_::<SemanticTokensFullRequest>(State::semantic_tokens_full)
             .with_request_::<SemanticTokensFullDeltaRequest>(State::semantic_tokens_full_delta)
             .with_request_::<DocumentHighlightRequest>(State::document_highlight)
+            .with_request_::<LinkedEditingRange>(State::linked_editing_range)
             .with_request_::<DocumentSymbolRequest>(State::document_symbol)
             // Sync for low latency
             .with_request_::<Formatting>(State::formatting)
@@ -278,6 +285,8 @@ impl ServerState {
             .with_command_("tinymist.interactCodeContext", State::interact_code_context)
             .with_command("tinymist.getDocumentTrace", State::get_document_trace)
             .with_command_("tinymist.getDocumentMetrics", State::get_document_metrics)
+            .with_command("tinymist.getWordCount", State::get_word_count)
+            .with_command("tinymist.getDocumentDependencies", State::get_document_deps)
             .with_command_("tinymist.getWorkspaceLabels", State::get_workspace_labels)
             .with_command_("tinymist.getServerInfo", State::get_server_info)
             // resources
@@ -309,7 +318,6 @@ impl ServerState {
     ) -> DapBuilder<T> {
         use dapts::request;
 
-        // todo: .on_sync_mut::<notifs::Cancel>(handlers::handle_cancel)?
         provider
             .with_request::<request::ConfigurationDone>(Self::configuration_done)
             .with_request::<request::Disconnect>(Self::disconnect)
@@ -462,6 +470,7 @@ impl ServerState {
         });
 
         let snap = self.snapshot()?;
+        let progress = self.begin_progress("Exporting document", true);
         just_future(async move {
             let snap = snap.task(TaskInputs {
                 entry: Some(entry),
@@ -470,7 +479,18 @@ impl ServerState {
 
             let is_html = matches!(task, ProjectTask::ExportHtml { .. });
             let artifact = CompiledArtifact::from_graph(snap.clone(), is_html);
-            let res = ExportTask::do_export(task, artifact, lock_dir).await?;
+            let export = ExportTask::do_export(task, artifact, lock_dir);
+            // The cancellation is best-effort. Dropping the export skips writing
+            // the output, but a running compilation or export computation is not
+            // interrupted and finishes in the background.
+            let res = match progress.as_ref().map(ProgressHandle::cancellation_token) {
+                Some(cancel) => tokio::select! {
+                    res = export => res?,
+                    _ = cancel.cancelled() => bail!("export is cancelled"),
+                },
+                None => export.await?,
+            };
+            drop(progress);
             if let Some(update_dep) = update_dep {
                 tokio::spawn(update_dep(snap));
             }
@@ -1,28 +1,40 @@
 //! The actor that handles various document export, like PDF and SVG export.
 
+use std::ops::Deref;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::atomic::AtomicUsize;
 use std::sync::{Arc, OnceLock};
 
-use reflexo::ImmutPath;
-use reflexo_typst::{Bytes, CompilationTask, ExportComputation};
+use parking_lot::Mutex;
+use reflexo::{path::unix_slash, ImmutPath};
+use reflexo_typst::{Bytes, CompilationTask, ExportComputation, WorldComputeGraph};
 use tinymist_project::LspWorld;
+use tinymist_query::LspWorldExt;
 use tinymist_std::error::prelude::*;
 use tinymist_std::fs::paths::write_atomic;
-use tinymist_std::typst::TypstDocument;
-use tinymist_task::{get_page_selection, ExportTarget, PdfExport, TextExport};
+use tinymist_std::hash::hash128;
+use tinymist_std::typst::{TypstDocument, TypstPagedDocument};
+use tinymist_task::{
+    concat_documents, get_page_selection, run_transform_scripts, with_attachments, ExportTarget,
+    PdfExport, TextExport,
+};
 use tokio::sync::mpsc;
 use typlite::Typlite;
-use typst::foundations::IntoValue;
+use typst::foundations::{IntoValue, Str, Value};
 use typst::visualize::Color;
+use typst::World;
+use typst_shim::utils::LazyHash;
 
 use super::{FutureFolder, SyncTaskFactory};
+use crate::config::shared_fonts;
 use crate::project::{
     ApplyProjectTask, CompiledArtifact, EntryReader, ExportHtmlTask, ExportMarkdownTask,
     ExportPdfTask, ExportPngTask, ExportSvgTask, ExportTask as ProjectExportTask, ExportTextTask,
-    LspCompiledArtifact, ProjectTask, QueryTask, TaskWhen,
+    LspCompiledArtifact, ProjectTask, QueryTask, TaskVariant, TaskWhen,
 };
+use crate::world::TaskInputs;
+use crate::CompileFontArgs;
 use crate::{actor::editor::EditorRequest, tool::word_count};
 
 #[derive(Clone)]
@@ -32,6 +44,8 @@ pub struct ExportTask {
     pub factory: SyncTaskFactory<ExportUserConfig>,
     export_folder: FutureFolder,
     count_word_folder: FutureFolder,
+    /// The fingerprint of the watched files in the last check.
+    watched: Arc<Mutex<Option<u128>>>,
 }
 
 impl ExportTask {
@@ -46,6 +60,7 @@ impl ExportTask {
             factory: SyncTaskFactory::new(export_config),
             export_folder: FutureFolder::default(),
             count_word_folder: FutureFolder::default(),
+            watched: Arc::default(),
         }
     }
 
@@ -68,12 +83,16 @@ impl ExportTask {
         let doc = artifact.doc.as_ref()?;
         let s = artifact.snap.signal;
 
-        let when = config.task.when().unwrap_or_default();
+        let export = config.task.as_export()?;
+        let when = export.when;
+        let by_edits = s.by_mem_events || s.by_fs_events;
         let need_export = (!matches!(when, TaskWhen::Never) && s.by_entry_update)
             || match when {
                 TaskWhen::Never => false,
-                TaskWhen::OnType => s.by_mem_events,
+                TaskWhen::OnType | TaskWhen::OnIdle => s.by_mem_events,
                 TaskWhen::OnSave => s.by_fs_events,
+                TaskWhen::OnSuccess => by_edits && !artifact.has_errors(),
+                TaskWhen::OnChange => by_edits && self.watched_changed(artifact, export),
                 TaskWhen::OnDocumentHasTitle => s.by_fs_events && doc.info().title.is_some(),
             };
 
@@ -81,12 +100,31 @@ impl ExportTask {
             return None;
         }
 
+        let debounce = matches!(when, TaskWhen::OnIdle).then(|| export.debounce());
         let rev = artifact.world().revision().get();
         let fut = self.export_folder.spawn(rev, || {
             let task = config.task.clone();
+            let font = config.font.clone();
             let artifact = artifact.clone();
+            let folder = self.export_folder.clone();
             Box::pin(async move {
-                log_err(Self::do_export(task, artifact, None).await);
+                if let Some(debounce) = debounce {
+                    tokio::time::sleep(debounce).await;
+                    // A newer revision is exported instead if the typing continues.
+                    if folder.has_pending() {
+                        return Some(());
+                    }
+                }
+
+                for (variant, task) in task.expand() {
+                    let artifact = match variant {
+                        Some(variant) => Self::compile_variant(&artifact, variant, &font).await,
+                        None => Ok(artifact.clone()),
+                    };
+                    if let Some(artifact) = log_err(artifact) {
+                        log_err(Self::do_export(task, artifact, None).await);
+                    }
+                }
                 Some(())
             })
         })?;
@@ -96,6 +134,27 @@ impl ExportTask {
         Some(())
     }
 
+    /// Checks whether the files watched by the task are changed since the last
+    /// check.
+    fn watched_changed(&self, artifact: &LspCompiledArtifact, task: &ProjectExportTask) -> bool {
+        let world = artifact.world();
+        let mut files = world
+            .depended_files()
+            .into_iter()
+            .filter(|id| {
+                id.package().is_none() && task.is_watched(&unix_slash(id.vpath().as_rooted_path()))
+            })
+            .map(|id| (id, world.file(id).ok().map(|content| hash128(&content))))
+            .collect::<Vec<_>>();
+        if files.is_empty() {
+            return false;
+        }
+        files.sort();
+
+        let fingerprint = hash128(&(world.main(), files));
+        self.watched.lock().replace(fingerprint) != Some(fingerprint)
+    }
+
     fn signal_count_word(
         &self,
         artifact: &LspCompiledArtifact,
@@ -129,10 +188,56 @@ impl ExportTask {
         Some(())
     }
 
+    /// Compiles a variant of the document, whose inputs are added to
+    /// `sys.inputs` and whose font paths, relative to the root, are added to
+    /// the font paths of the server.
+    async fn compile_variant(
+        artifact: &LspCompiledArtifact,
+        variant: TaskVariant,
+        font: &CompileFontArgs,
+    ) -> Result<LspCompiledArtifact> {
+        let mut inputs = artifact.world().inputs().as_ref().deref().clone();
+        for (key, value) in variant.inputs {
+            inputs.insert(Str::from(key), Value::Str(Str::from(value)));
+        }
+
+        let mut snap = artifact.graph.snap.clone().task(TaskInputs {
+            entry: None,
+            inputs: Some(Arc::new(LazyHash::new(inputs))),
+        });
+        if !variant.font_paths.is_empty() {
+            let root = artifact.world().entry_state().root();
+            let root = root.context("cannot resolve font paths without a root")?;
+
+            let mut font = font.clone();
+            let paths = variant.font_paths.iter();
+            font.font_paths
+                .extend(paths.flat_map(|p| p.to_abs_path(&root)));
+            snap.world.font_resolver = FutureFolder::compute(move |_| shared_fonts(font)).await??;
+        }
+
+        let graph = WorldComputeGraph::new(snap);
+        let is_html = matches!(artifact.doc, Some(TypstDocument::Html(..)));
+        FutureFolder::compute(move |_| CompiledArtifact::from_graph(graph, is_html)).await
+    }
+
     pub async fn do_export(
         task: ProjectTask,
         artifact: LspCompiledArtifact,
         lock_dir: Option<ImmutPath>,
+    ) -> Result<Option<PathBuf>> {
+        Self::do_export_with(task, artifact, lock_dir, vec![]).await
+    }
+
+    /// Exports the artifact, appending the pages of the `appendix` documents
+    /// to PDF outputs, which are resolved from the
+    /// [`ExportTransform::Concat`](crate::project::ExportTransform::Concat)
+    /// transform by the caller.
+    pub async fn do_export_with(
+        task: ProjectTask,
+        artifact: LspCompiledArtifact,
+        lock_dir: Option<ImmutPath>,
+        appendix: Vec<Arc<TypstPagedDocument>>,
     ) -> Result<Option<PathBuf>> {
         use reflexo_vec2svg::DefaultExportFeature;
         use ProjectTask::*;
@@ -158,6 +263,9 @@ impl ExportTask {
         let export_id = EXPORT_ID.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
 
         log::info!("ExportTask({export_id}): exporting {entry:?} to {write_to:?}");
+        if appendix.is_empty() && config.concat_documents().next().is_some() {
+            bail!("ExportTask({export_id}): documents are only concatenated by `tinymist build`");
+        }
         if let Some(e) = write_to.parent() {
             if !e.exists() {
                 std::fs::create_dir_all(e).context("failed to create directory")?;
@@ -168,10 +276,12 @@ impl ExportTask {
             let mut updater = crate::project::update_lock(lock_dir);
 
             let doc_id = updater.compiled(graph.world())?;
+            updater.update_materials(doc_id.clone(), graph.world().depended_fs_paths());
 
             updater.task(ApplyProjectTask {
                 id: doc_id.clone(),
                 document: doc_id,
+                depends_on: vec![],
                 task: task.clone(),
             });
             updater.commit();
@@ -184,6 +294,8 @@ impl ExportTask {
 
         // Prepare data.
         let kind2 = task.clone();
+        let export_task = config.clone();
+        let path = unix_slash(&write_to);
         let data = FutureFolder::compute(move |_| -> Result<Bytes> {
             let doc = &doc;
 
@@ -219,10 +331,24 @@ impl ExportTask {
                     .first()
                     .context("no first page to export")
             };
-            Ok(match kind2 {
+            let data = match kind2 {
                 Preview(..) => Bytes::new([]),
                 // todo: more pdf flags
-                ExportPdf(config) => PdfExport::run(&graph, paged_doc()?, &config)?,
+                ExportPdf(config) if appendix.is_empty() => {
+                    PdfExport::run(&graph, paged_doc()?, &config)?
+                }
+                ExportPdf(config) => {
+                    // The files are attached to the main document, which is
+                    // compiled again with them.
+                    let main = with_attachments(&graph, paged_doc()?, &config)?;
+                    let docs = std::iter::once(&main).chain(&appendix);
+                    let doc = Arc::new(concat_documents(docs.map(Deref::deref)));
+                    let config = ExportPdfTask {
+                        attachments: vec![],
+                        ..config
+                    };
+                    PdfExport::run(&graph, &doc, &config)?
+                }
                 Query(QueryTask {
                     export: _,
                     output_extension: _,
@@ -308,7 +434,9 @@ impl ExportTask {
                             .map_err(|err| anyhow::anyhow!("failed to encode PNG ({err})"))?,
                     )
                 }
-            })
+            };
+
+            run_transform_scripts(graph.world(), &export_task, &path, data)
         })
         .await??;
 
@@ -329,6 +457,9 @@ pub struct ExportUserConfig {
     pub export_target: ExportTarget,
     pub task: ProjectTask,
     pub count_words: bool,
+    /// The font options of the server, which are extended by the font paths of
+    /// the variants.
+    pub font: CompileFontArgs,
 }
 
 impl Default for ExportUserConfig {
@@ -338,13 +469,22 @@ impl Default for ExportUserConfig {
             task: ProjectTask::ExportPdf(ExportPdfTask {
                 export: ProjectExportTask {
                     when: TaskWhen::Never,
+                    debounce: None,
+                    watch: vec![],
                     output: None,
                     transform: vec![],
+                    matrix: vec![],
                 },
                 pdf_standards: vec![],
                 creation_timestamp: None,
+                ident: None,
+                title: None,
+                author: vec![],
+                keywords: vec![],
+                attachments: vec![],
             }),
             count_words: false,
+            font: CompileFontArgs::default(),
         }
     }
 }
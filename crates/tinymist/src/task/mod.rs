@@ -64,6 +64,11 @@ impl FutureFolder {
             .context_ut("compute error")
     }
 
+    /// Whether a future of a newer revision is waiting to run.
+    fn has_pending(&self) -> bool {
+        self.state.lock().task.is_some()
+    }
+
     #[must_use]
     fn spawn(
         &self,
@@ -0,0 +1,444 @@
+//! Incremental builds of the tasks declared in the lock file.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use reflexo::{path::unix_slash, ImmutPath};
+use reflexo_typst::WorldComputeGraph;
+use serde::{Deserialize, Serialize};
+use tinymist_query::LspWorldExt;
+use tinymist_std::hash::hash128;
+use tinymist_std::typst::TypstDocument;
+use tinymist_std::{bail, error::prelude::*};
+use typst::syntax::package::PackageVersion;
+
+use crate::{project::*, task::ExportTask, world::system::DiagnosticPrinter};
+
+/// The path to the build cache, relative to the directory of the lock file.
+const BUILD_CACHE_PATH: &str = ".tinymist/build-cache.json";
+
+/// Arguments for building the tasks in the lock file.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct BuildArgs {
+    /// The IDs of the tasks to build. If not provided, all tasks in the lock
+    /// file will be built.
+    pub tasks: Vec<String>,
+
+    /// Specifies the path to the lock file.
+    #[clap(long)]
+    pub lockfile: Option<PathBuf>,
+
+    /// Rebuilds the tasks even if they are up to date.
+    #[clap(long)]
+    pub force: bool,
+
+    /// The arguments to print diagnostics.
+    #[clap(flatten)]
+    pub diag: DiagnosticArgs,
+}
+
+/// Builds the tasks in the lock file, skipping the tasks that are up to date.
+pub async fn build_main(args: BuildArgs) -> Result<()> {
+    let lock_dir: ImmutPath = if let Some(lockfile) = args.lockfile {
+        lockfile.parent().context("no parent")?.into()
+    } else {
+        std::env::current_dir().context("lock directory")?.into()
+    };
+
+    let lock = LockFile::read(&lock_dir)?;
+    let cache_path = lock_dir.join(BUILD_CACHE_PATH);
+    let mut cache = if args.force {
+        BuildCache::default()
+    } else {
+        BuildCache::read(&cache_path)
+    };
+
+    let ids = args.tasks.into_iter().map(Id::new).collect::<Vec<_>>();
+    let stages = lock.schedule(&ids)?;
+
+    let diag = args.diag;
+    let printer = DiagnosticPrinter::new(diag.diagnostic_format, diag.diagnostic_encoding);
+    let printer = Arc::new(printer);
+    let failed = build_stages(&lock, &lock_dir, stages, &mut cache, &printer).await;
+    printer.finish().context_ut("print diagnostics")?;
+    let failed = failed?;
+
+    cache.write(&cache_path)?;
+
+    if failed > 0 {
+        bail!("{failed} task(s) failed");
+    }
+
+    Ok(())
+}
+
+/// Builds the scheduled stages in order, returning the number of failed tasks.
+async fn build_stages(
+    lock: &LockFile,
+    lock_dir: &ImmutPath,
+    stages: Vec<Vec<&ApplyProjectTask>>,
+    cache: &mut BuildCache,
+    printer: &Arc<DiagnosticPrinter>,
+) -> Result<usize> {
+    let mut outputs = TaskOutputs::new();
+    let mut failed = 0;
+    for stage in stages {
+        // Groups the jobs by their documents and targets, so that each document
+        // is compiled at most once per target.
+        let mut groups: Vec<(ProjectInput, bool, Vec<BuildJob>)> = vec![];
+        for task in stage {
+            outputs.insert(task.id.clone(), Some(vec![]));
+            // Previews are not built.
+            if task.task.as_export().is_none() {
+                continue;
+            }
+
+            let Some(input) = lock.get_document(task.doc_id()) else {
+                bail!(
+                    "task {} refers to an unknown document {}",
+                    task.id,
+                    task.doc_id()
+                );
+            };
+
+            let Some(input) = with_dependencies(input, &lock_dir, task, &outputs) else {
+                log::error!("task {} is skipped since its dependencies failed", task.id);
+                outputs.insert(task.id.clone(), None);
+                failed += 1;
+                continue;
+            };
+
+            // The documents to concatenate to the output.
+            let mut appendix = vec![];
+            for doc_id in task
+                .task
+                .as_export()
+                .into_iter()
+                .flat_map(|e| e.concat_documents())
+            {
+                let Some(doc) = lock.get_document(doc_id) else {
+                    bail!("task {} refers to an unknown document {doc_id}", task.id);
+                };
+                appendix.push(doc.clone());
+            }
+
+            for (variant, variant_task) in task.task.expand() {
+                let with_variant = |input: &ProjectInput| match &variant {
+                    Some(variant) => input.with_variant(variant),
+                    None => input.clone(),
+                };
+                let input = with_variant(&input);
+                let appendix = appendix.iter().map(with_variant).collect::<Vec<_>>();
+                let job = BuildJob {
+                    id: task.id.clone(),
+                    variant: variant.as_ref().map(|variant| variant.name.clone()),
+                    config: TaskFingerprint::config(&input, &appendix, &variant_task),
+                    task: variant_task,
+                    appendix,
+                };
+
+                if let Some(output) = cache.fresh_output(&job.key(), &job.config) {
+                    log::info!("task {} is up to date", job.key());
+                    record_output(&mut outputs, &job, Some(output));
+                    continue;
+                }
+
+                let is_html = matches!(job.task, ProjectTask::ExportHtml(..));
+                let group = groups
+                    .iter_mut()
+                    .find(|(doc, html, _)| *doc == input && *html == is_html);
+                match group {
+                    Some((_, _, jobs)) => jobs.push(job),
+                    None => groups.push((input, is_html, vec![job])),
+                }
+            }
+        }
+
+        let handles = groups
+            .into_iter()
+            .map(|(input, is_html, jobs)| {
+                tokio::spawn(build_document(
+                    input,
+                    lock_dir.clone(),
+                    is_html,
+                    jobs,
+                    printer.clone(),
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            for (job, res) in handle.await.context_ut("build task panicked")? {
+                let key = job.key();
+                match res {
+                    Ok(fingerprint) => {
+                        log::info!("task {key} is built");
+                        record_output(&mut outputs, &job, Some(fingerprint.output.clone()));
+                        cache.tasks.insert(key, fingerprint);
+                    }
+                    Err(err) => {
+                        log::error!("task {key} failed: {err}");
+                        record_output(&mut outputs, &job, None);
+                        cache.tasks.remove(&key);
+                        failed += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(failed)
+}
+
+/// The outputs of the tasks with the names of their variants, or `None` if a
+/// task failed.
+type TaskOutputs = HashMap<Id, Option<Vec<(Option<String>, PathBuf)>>>;
+
+/// Records the output of a job, or marks its task as failed if `output` is
+/// `None`.
+fn record_output(outputs: &mut TaskOutputs, job: &BuildJob, output: Option<Option<PathBuf>>) {
+    let entry = outputs
+        .entry(job.id.clone())
+        .or_insert_with(|| Some(vec![]));
+    match (entry, output) {
+        (Some(paths), Some(Some(path))) => paths.push((job.variant.clone(), path)),
+        (entry, None) => *entry = None,
+        _ => {}
+    }
+}
+
+/// Makes the outputs of the dependencies of a task visible through
+/// `sys.inputs`. Returns `None` if any of the dependencies failed.
+fn with_dependencies(
+    input: &ProjectInput,
+    lock_dir: &Path,
+    task: &ApplyProjectTask,
+    outputs: &TaskOutputs,
+) -> Option<ProjectInput> {
+    let mut input = input.clone();
+    if task.depends_on.is_empty() {
+        return Some(input);
+    }
+
+    let root = match &input.root {
+        Some(root) => root.to_abs_path(lock_dir),
+        None => Some(lock_dir.to_owned()),
+    };
+    for dep in &task.depends_on {
+        for (variant, output) in outputs.get(dep)?.as_ref()? {
+            let key = match variant {
+                Some(variant) => format!("tinymist.task.{dep}.{variant}"),
+                None => format!("tinymist.task.{dep}"),
+            };
+
+            // Paths inside the root are passed as absolute paths in the project,
+            // which can be read by the document directly.
+            let value = match root
+                .as_ref()
+                .and_then(|root| output.strip_prefix(root).ok())
+            {
+                Some(path) => format!("/{}", unix_slash(path)),
+                None => output.display().to_string(),
+            };
+            input.inputs.push((key, value));
+        }
+    }
+
+    Some(input)
+}
+
+/// A task, or a variant of a task, to run on a compiled document.
+struct BuildJob {
+    /// The ID of the task.
+    id: Id,
+    /// The name of the variant, if the task has a matrix.
+    variant: Option<String>,
+    /// The fingerprint of the job's configuration.
+    config: String,
+    /// The task to run.
+    task: ProjectTask,
+    /// The documents to concatenate to the output of the task.
+    appendix: Vec<ProjectInput>,
+}
+
+impl BuildJob {
+    /// The key of the job in the build cache.
+    fn key(&self) -> String {
+        match &self.variant {
+            Some(variant) => format!("{}@{variant}", self.id),
+            None => self.id.to_string(),
+        }
+    }
+}
+
+/// Compiles a document and runs the jobs on it.
+async fn build_document(
+    input: ProjectInput,
+    lock_dir: ImmutPath,
+    is_html: bool,
+    jobs: Vec<BuildJob>,
+    printer: Arc<DiagnosticPrinter>,
+) -> Vec<(BuildJob, Result<TaskFingerprint>)> {
+    let compiled = {
+        let (lock_dir, printer) = (lock_dir.clone(), printer.clone());
+        tokio::task::spawn_blocking(move || compile(input, lock_dir, is_html, &printer))
+    };
+    let compiled = compiled
+        .await
+        .context_ut("compilation panicked")
+        .and_then(|res| res);
+
+    let (compiled, materials) = match compiled {
+        Ok(compiled) => compiled,
+        Err(err) => {
+            let err = err.to_string();
+            return jobs
+                .into_iter()
+                .map(|job| (job, Err(error_once!("cannot compile", err: err))))
+                .collect();
+        }
+    };
+
+    let mut results = vec![];
+    for job in jobs {
+        let res = build_job(&job, &compiled, &materials, &lock_dir, &printer).await;
+        results.push((job, res));
+    }
+
+    results
+}
+
+/// Runs a job on a compiled document.
+async fn build_job(
+    job: &BuildJob,
+    compiled: &LspCompiledArtifact,
+    materials: &[(PathBuf, String)],
+    lock_dir: &ImmutPath,
+    printer: &Arc<DiagnosticPrinter>,
+) -> Result<TaskFingerprint> {
+    let mut materials = materials.to_vec();
+
+    let mut appendix = vec![];
+    for input in &job.appendix {
+        let (input, lock_dir, printer) = (input.clone(), lock_dir.clone(), printer.clone());
+        let compiled =
+            tokio::task::spawn_blocking(move || compile(input, lock_dir, false, &printer));
+        let (compiled, doc_materials) = compiled.await.context_ut("compilation panicked")??;
+        let Some(TypstDocument::Paged(doc)) = compiled.doc else {
+            bail!("cannot concatenate a document without paged output");
+        };
+
+        appendix.push(doc);
+        materials.extend(doc_materials);
+    }
+
+    let task = job.task.clone();
+    let output = ExportTask::do_export_with(task, compiled.clone(), None, appendix).await?;
+    Ok(TaskFingerprint {
+        config: job.config.clone(),
+        materials,
+        output,
+    })
+}
+
+/// Compiles a document, returning the artifact and the hashes of the files it
+/// depends on.
+fn compile(
+    input: ProjectInput,
+    lock_dir: ImmutPath,
+    is_html: bool,
+    printer: &DiagnosticPrinter,
+) -> Result<(LspCompiledArtifact, Vec<(PathBuf, String)>)> {
+    let universe = (input, lock_dir).resolve()?;
+    let graph = WorldComputeGraph::from_world(universe.snapshot());
+
+    let compiled = CompiledArtifact::from_graph(graph, is_html);
+    printer
+        .print(compiled.world(), compiled.diagnostics())
+        .context_ut("print diagnostics")?;
+    if compiled.has_errors() {
+        bail!("compilation failed");
+    }
+
+    let materials = compiled
+        .world()
+        .depended_fs_paths()
+        .into_iter()
+        .map(|path| {
+            let hash = file_hash(&path);
+            (path.as_ref().to_owned(), hash)
+        })
+        .collect::<Vec<_>>();
+
+    Ok((compiled, materials))
+}
+
+/// The build cache, recording the state of the last successful run of each
+/// task.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BuildCache {
+    /// The fingerprints of the tasks, indexed by the task IDs, or
+    /// `<id>@<variant>` for the variants of the tasks.
+    tasks: HashMap<String, TaskFingerprint>,
+}
+
+impl BuildCache {
+    fn read(path: &Path) -> Self {
+        let cache = std::fs::read(path).ok();
+        let cache = cache.and_then(|data| serde_json::from_slice(&data).ok());
+        cache.unwrap_or_default()
+    }
+
+    fn write(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).context("create build cache directory")?;
+        }
+        let data = serde_json::to_string(self).context("serialize build cache")?;
+        tinymist_std::fs::paths::write_atomic(path, data).context("write build cache")?;
+        Ok(())
+    }
+
+    /// Gets the output of a task if it is up to date.
+    fn fresh_output(&self, key: &str, config: &str) -> Option<Option<PathBuf>> {
+        let fingerprint = self.tasks.get(key)?;
+
+        let is_fresh = fingerprint.config == config
+            && fingerprint
+                .output
+                .as_ref()
+                .is_none_or(|output| output.exists())
+            && fingerprint
+                .materials
+                .iter()
+                .all(|(path, hash)| file_hash(path) == *hash);
+        is_fresh.then(|| fingerprint.output.clone())
+    }
+}
+
+/// The state of a task in its last successful run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TaskFingerprint {
+    /// The hash of the task, the documents and the compiler version.
+    config: String,
+    /// The files that the document depends on, with the hashes of their
+    /// content.
+    materials: Vec<(PathBuf, String)>,
+    /// The output of the task.
+    output: Option<PathBuf>,
+}
+
+impl TaskFingerprint {
+    fn config(input: &ProjectInput, appendix: &[ProjectInput], task: &ProjectTask) -> String {
+        let version = PackageVersion::compiler();
+        format!("{:032x}", hash128(&(input, appendix, task, version)))
+    }
+}
+
+/// Hashes the content of a file. A missing file has an empty hash.
+fn file_hash(path: &Path) -> String {
+    match std::fs::read(path) {
+        Ok(content) => format!("{:032x}", hash128(&content)),
+        Err(_) => String::new(),
+    }
+}
@@ -0,0 +1,490 @@
+//! Dependency graph inspection for documents.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use reflexo::path::unix_slash;
+use reflexo_typst::debug_loc::DataSource;
+use reflexo_typst::vfs::FileId;
+use reflexo_typst::WorldComputeGraph;
+use serde::{Deserialize, Serialize};
+use tinymist_query::syntax::resolve_id_by_path;
+use tinymist_query::LspWorldExt;
+use tinymist_std::error::prelude::*;
+use tinymist_std::typst::TypstDocument;
+use typst::layout::{Frame, FrameItem};
+use typst::syntax::{ast, SyntaxNode};
+use typst::World;
+
+use crate::{project::*, world::system::DiagnosticPrinter};
+
+/// The format of the dependency graph.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+#[clap(rename_all = "lowercase")]
+pub enum DepsFormat {
+    /// A JSON object with the nodes and the edges of the graph.
+    Json,
+    /// A graphviz graph.
+    Dot,
+    /// A tree of the modules, starting at the main file.
+    #[default]
+    Tree,
+}
+
+/// Dependency graph arguments.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct DepsArgs {
+    /// The argument to compile once.
+    #[clap(flatten)]
+    pub compile: CompileOnceArgs,
+    /// The arguments to print diagnostics.
+    #[clap(flatten)]
+    pub diag: DiagnosticArgs,
+    /// The format of the dependency graph.
+    #[clap(long, value_enum, default_value_t)]
+    pub format: DepsFormat,
+    /// The output path of the dependency graph. If not provided, the graph is
+    /// printed to stdout.
+    #[clap(short, long)]
+    pub output: Option<String>,
+}
+
+/// Prints the dependency graph of a document.
+pub fn deps_main(args: DepsArgs) -> Result<()> {
+    // Compiles the document
+    let universe = args.compile.resolve()?;
+    let graph = WorldComputeGraph::from_world(universe.snapshot());
+    let compiled = CompiledArtifact::from_graph(graph, false);
+
+    let printer =
+        DiagnosticPrinter::new(args.diag.diagnostic_format, args.diag.diagnostic_encoding);
+    printer
+        .print(compiled.world(), compiled.diagnostics())
+        .context_ut("print diagnostics")?;
+    printer.finish().context_ut("print diagnostics")?;
+
+    // Collects the dependencies even if the compilation fails, as the files
+    // touched by the compilation are still recorded.
+    let deps = DependencyGraph::new(compiled.world(), compiled.doc.as_ref());
+    let res = match args.format {
+        DepsFormat::Json => serde_json::to_string_pretty(&deps).context("serialize deps")?,
+        DepsFormat::Dot => deps.to_dot(),
+        DepsFormat::Tree => deps.to_tree(),
+    };
+
+    match args.output {
+        Some(output) => std::fs::write(output, res).context("write deps")?,
+        None => print!("{res}"),
+    }
+
+    Ok(())
+}
+
+/// The kind of a node in the dependency graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DepNodeKind {
+    /// A typst source file.
+    Module,
+    /// A file read by a module, e.g. an image or a data file.
+    Asset,
+    /// A package.
+    Package,
+    /// A font used by the document.
+    Font,
+}
+
+/// A node in the dependency graph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepNode {
+    /// The ID of the node. It is the rooted path of a file, prefixed by the
+    /// package spec for files in packages, the package spec of a package, or
+    /// the family name of a font.
+    pub id: String,
+    /// The kind of the node.
+    pub kind: DepNodeKind,
+    /// The path to the node on the file system.
+    pub path: Option<PathBuf>,
+    /// The package that the file belongs to.
+    pub package: Option<String>,
+    /// The size of the file in bytes.
+    pub size: Option<usize>,
+}
+
+/// The kind of an edge in the dependency graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DepEdgeKind {
+    /// The module imports a module by `import`.
+    Import,
+    /// The module includes a module by `include`.
+    Include,
+    /// The module refers to an asset by its path.
+    Read,
+    /// The content of the module is rendered with a font.
+    Font,
+}
+
+/// An edge in the dependency graph.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepEdge {
+    /// The ID of the dependent node.
+    pub from: String,
+    /// The ID of the dependency node.
+    pub to: String,
+    /// The kind of the edge.
+    pub kind: DepEdgeKind,
+}
+
+/// The dependency graph of a document, which consists of the files touched by
+/// the last compilation and the fonts used by the document.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyGraph {
+    /// The ID of the main file.
+    pub main: Option<String>,
+    /// The nodes, sorted by their kinds and IDs.
+    pub nodes: Vec<DepNode>,
+    /// The edges, sorted by their sources.
+    pub edges: Vec<DepEdge>,
+}
+
+impl DependencyGraph {
+    /// Collects the dependency graph of a compiled document.
+    ///
+    /// Note: must be called after compilation.
+    pub fn new(world: &LspWorld, doc: Option<&TypstDocument>) -> Self {
+        let mut nodes = BTreeMap::new();
+        let mut edges = BTreeSet::new();
+
+        let files = world.depended_files();
+        let ids = files.iter().map(|fid| (*fid, file_node_id(*fid)));
+        let ids = ids.collect::<HashMap<_, _>>();
+
+        for (&fid, id) in &ids {
+            let is_module =
+                fid.vpath().as_rootless_path().extension() == Some(std::ffi::OsStr::new("typ"));
+            let package = fid.package().map(ToString::to_string);
+            if let Some(package) = &package {
+                nodes.entry(package.clone()).or_insert_with(|| DepNode {
+                    id: package.clone(),
+                    kind: DepNodeKind::Package,
+                    path: None,
+                    package: None,
+                    size: None,
+                });
+            }
+
+            nodes.insert(
+                id.clone(),
+                DepNode {
+                    id: id.clone(),
+                    kind: if is_module {
+                        DepNodeKind::Module
+                    } else {
+                        DepNodeKind::Asset
+                    },
+                    path: world.path_for_id(fid).ok().map(|p| p.as_path().to_owned()),
+                    package,
+                    size: world.file(fid).ok().map(|bytes| bytes.len()),
+                },
+            );
+
+            let Some(source) = is_module.then(|| world.source(fid).ok()).flatten() else {
+                continue;
+            };
+            let mut add_edge = |path: &str, kind| {
+                let dep = resolve_id_by_path(world, fid, path);
+                let to = dep.and_then(|dep| ids.get(&dep)).filter(|to| *to != id);
+                if let Some(to) = to {
+                    let from = id.clone();
+                    edges.insert(DepEdge {
+                        from,
+                        to: to.clone(),
+                        kind,
+                    });
+                }
+            };
+            walk(source.root(), &mut |node| {
+                if let Some(import) = node.cast::<ast::ModuleImport>() {
+                    if let ast::Expr::Str(path) = import.source() {
+                        add_edge(path.get().as_str(), DepEdgeKind::Import);
+                    }
+                } else if let Some(include) = node.cast::<ast::ModuleInclude>() {
+                    if let ast::Expr::Str(path) = include.source() {
+                        add_edge(path.get().as_str(), DepEdgeKind::Include);
+                    }
+                } else if let Some(path) = node.cast::<ast::Str>() {
+                    let path = path.get();
+                    if !path.starts_with('@') && !path.ends_with(".typ") {
+                        add_edge(path.as_str(), DepEdgeKind::Read);
+                    }
+                }
+            });
+        }
+
+        if let Some(TypstDocument::Paged(doc)) = doc {
+            let mut fonts = BTreeMap::new();
+            for page in &doc.pages {
+                collect_fonts(&page.frame, &ids, &mut fonts);
+            }
+
+            for (family, (font, users)) in fonts {
+                let path = match world.font_resolver.describe_font(&font).as_deref() {
+                    Some(DataSource::Fs(fs)) => Some(PathBuf::from(&fs.path)),
+                    _ => None,
+                };
+                let size = font.data().len();
+                for from in users {
+                    let to = family.clone();
+                    edges.insert(DepEdge {
+                        from,
+                        to,
+                        kind: DepEdgeKind::Font,
+                    });
+                }
+                nodes.entry(family.clone()).or_insert(DepNode {
+                    id: family,
+                    kind: DepNodeKind::Font,
+                    path,
+                    package: None,
+                    size: Some(size),
+                });
+            }
+        }
+
+        let mut nodes = nodes.into_values().collect::<Vec<_>>();
+        nodes.sort_by(|a, b| (a.kind, &a.id).cmp(&(b.kind, &b.id)));
+
+        Self {
+            main: world.entry_state().main().map(file_node_id),
+            nodes,
+            edges: edges.into_iter().collect(),
+        }
+    }
+
+    /// Renders the graph in the graphviz format. The files of a package are
+    /// grouped in a cluster.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "digraph deps {{");
+        let _ = writeln!(out, "  rankdir=LR;");
+        let _ = writeln!(out, "  node [fontname=\"sans-serif\"];");
+
+        let mut packages = BTreeMap::<_, Vec<_>>::new();
+        for node in &self.nodes {
+            let shape = match node.kind {
+                DepNodeKind::Module => "box",
+                DepNodeKind::Asset => "note",
+                DepNodeKind::Package => continue,
+                DepNodeKind::Font => "ellipse",
+            };
+            let line = format!("{:?} [shape={shape}];", node.id);
+            match &node.package {
+                Some(package) => packages.entry(package).or_default().push(line),
+                None => {
+                    let _ = writeln!(out, "  {line}");
+                }
+            }
+        }
+        for (idx, (package, lines)) in packages.into_iter().enumerate() {
+            let _ = writeln!(out, "  subgraph cluster_{idx} {{");
+            let _ = writeln!(out, "    label={package:?};");
+            for line in lines {
+                let _ = writeln!(out, "    {line}");
+            }
+            let _ = writeln!(out, "  }}");
+        }
+
+        for edge in &self.edges {
+            let style = match edge.kind {
+                DepEdgeKind::Import => "solid",
+                DepEdgeKind::Include => "bold",
+                DepEdgeKind::Read | DepEdgeKind::Font => "dashed",
+            };
+            let _ = writeln!(out, "  {:?} -> {:?} [style={style}];", edge.from, edge.to);
+        }
+        let _ = writeln!(out, "}}");
+        out
+    }
+
+    /// Renders the graph as a tree starting at the main file. The dependencies
+    /// that are already expanded are marked with `(*)`.
+    pub fn to_tree(&self) -> String {
+        let Some(main) = &self.main else {
+            return String::new();
+        };
+
+        let mut children = BTreeMap::<_, Vec<_>>::new();
+        for edge in &self.edges {
+            children.entry(edge.from.as_str()).or_default().push(edge);
+        }
+        let mut printer = TreePrinter {
+            nodes: self
+                .nodes
+                .iter()
+                .map(|node| (node.id.as_str(), node))
+                .collect(),
+            children,
+            visited: BTreeSet::from([main.as_str()]),
+            out: String::new(),
+        };
+
+        let _ = writeln!(printer.out, "{}", printer.label(main));
+        printer.print(main, "");
+        printer.out
+    }
+}
+
+/// Prints a dependency graph as a tree.
+struct TreePrinter<'a> {
+    nodes: BTreeMap<&'a str, &'a DepNode>,
+    children: BTreeMap<&'a str, Vec<&'a DepEdge>>,
+    visited: BTreeSet<&'a str>,
+    out: String,
+}
+
+impl<'a> TreePrinter<'a> {
+    fn label(&self, id: &str) -> String {
+        match self.nodes.get(id).and_then(|node| node.size) {
+            Some(size) => format!("{id} ({size} bytes)"),
+            None => id.to_owned(),
+        }
+    }
+
+    fn print(&mut self, id: &'a str, prefix: &str) {
+        let Some(deps) = self.children.get(id).cloned() else {
+            return;
+        };
+
+        for (idx, edge) in deps.iter().enumerate() {
+            let is_last = idx + 1 == deps.len();
+            let (branch, indent) = if is_last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            let kind = match edge.kind {
+                DepEdgeKind::Import => "import",
+                DepEdgeKind::Include => "include",
+                DepEdgeKind::Read => "read",
+                DepEdgeKind::Font => "font",
+            };
+
+            let to = edge.to.as_str();
+            let first = self.visited.insert(to);
+            let mark = if !first && self.children.contains_key(to) {
+                " (*)"
+            } else {
+                ""
+            };
+            let label = self.label(to);
+            let _ = writeln!(self.out, "{prefix}{branch}{label} [{kind}]{mark}");
+
+            if first {
+                self.print(to, &format!("{prefix}{indent}"));
+            }
+        }
+    }
+}
+
+/// Gets the ID of a file node.
+fn file_node_id(fid: FileId) -> String {
+    let path = unix_slash(fid.vpath().as_rooted_path());
+    match fid.package() {
+        Some(package) => format!("{package}{path}"),
+        None => path,
+    }
+}
+
+/// Walks the syntax tree in pre-order.
+fn walk(node: &SyntaxNode, f: &mut impl FnMut(&SyntaxNode)) {
+    f(node);
+    for child in node.children() {
+        walk(child, f);
+    }
+}
+
+/// Collects the fonts used in a frame, along with the modules where the text
+/// comes from.
+fn collect_fonts(
+    frame: &Frame,
+    ids: &HashMap<FileId, String>,
+    fonts: &mut BTreeMap<String, (typst::text::Font, BTreeSet<String>)>,
+) {
+    for (_, item) in frame.items() {
+        match item {
+            FrameItem::Group(group) => collect_fonts(&group.frame, ids, fonts),
+            FrameItem::Text(text) => {
+                let family = text.font.info().family.clone();
+                let (_, users) = fonts
+                    .entry(family)
+                    .or_insert_with(|| (text.font.clone(), BTreeSet::new()));
+                let spans = text.glyphs.iter().filter_map(|glyph| glyph.span.0.id());
+                users.extend(spans.filter_map(|fid| ids.get(&fid).cloned()));
+            }
+            FrameItem::Tag(..)
+            | FrameItem::Link(..)
+            | FrameItem::Shape(..)
+            | FrameItem::Image(..) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, kind: DepNodeKind, size: Option<usize>) -> DepNode {
+        DepNode {
+            id: id.to_owned(),
+            kind,
+            path: None,
+            package: None,
+            size,
+        }
+    }
+
+    fn edge(from: &str, to: &str, kind: DepEdgeKind) -> DepEdge {
+        DepEdge {
+            from: from.to_owned(),
+            to: to.to_owned(),
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_tree() {
+        let graph = DependencyGraph {
+            main: Some("/main.typ".to_owned()),
+            nodes: vec![
+                node("/main.typ", DepNodeKind::Module, Some(10)),
+                node("/a.typ", DepNodeKind::Module, Some(20)),
+                node("/b.typ", DepNodeKind::Module, Some(30)),
+                node("/logo.png", DepNodeKind::Asset, Some(40)),
+            ],
+            edges: vec![
+                edge("/a.typ", "/b.typ", DepEdgeKind::Import),
+                edge("/a.typ", "/logo.png", DepEdgeKind::Read),
+                edge("/main.typ", "/a.typ", DepEdgeKind::Include),
+                edge("/main.typ", "/b.typ", DepEdgeKind::Import),
+                edge("/b.typ", "/a.typ", DepEdgeKind::Import),
+            ],
+        };
+
+        assert_eq!(
+            graph.to_tree(),
+            "\
+/main.typ (10 bytes)
+├── /a.typ (20 bytes) [include]
+│   ├── /b.typ (30 bytes) [import]
+│   │   └── /a.typ (20 bytes) [import] (*)
+│   └── /logo.png (40 bytes) [read]
+└── /b.typ (30 bytes) [import] (*)
+"
+        );
+    }
+}
@@ -0,0 +1,54 @@
+//! Linting of documents from the command line.
+
+use reflexo_typst::WorldComputeGraph;
+use tinymist_query::LspWorldExt;
+use tinymist_std::{bail, error::prelude::*};
+use typst::diag::Severity;
+use typst::World;
+
+use crate::{project::*, world::system::DiagnosticPrinter};
+
+/// Lint arguments.
+#[derive(Debug, Clone, clap::Parser)]
+pub struct LintArgs {
+    /// The argument to compile once.
+    #[clap(flatten)]
+    pub compile: CompileOnceArgs,
+    /// The arguments to print diagnostics.
+    #[clap(flatten)]
+    pub diag: DiagnosticArgs,
+}
+
+/// Lints the source files of a document, which are printed along with the
+/// compiler diagnostics.
+pub fn lint_main(args: LintArgs) -> Result<()> {
+    // Compiles the document to find the files it depends on
+    let universe = args.compile.resolve()?;
+    let graph = WorldComputeGraph::from_world(universe.snapshot());
+    let compiled = CompiledArtifact::from_graph(graph, false);
+    let world = compiled.world();
+
+    let mut diagnostics = compiled.diagnostics().cloned().collect::<Vec<_>>();
+    // Packages are not linted, as their authors are not the users.
+    let files = world.depended_files().into_iter().filter(|id| {
+        id.package().is_none() && id.vpath().as_rooted_path().extension() == Some("typ".as_ref())
+    });
+    for id in files {
+        if let Ok(source) = world.source(id) {
+            diagnostics.extend(tinymist_lint::lint_source(&source));
+        }
+    }
+
+    let printer =
+        DiagnosticPrinter::new(args.diag.diagnostic_format, args.diag.diagnostic_encoding);
+    printer
+        .print(world, diagnostics.iter())
+        .context_ut("print diagnostics")?;
+    printer.finish().context_ut("print diagnostics")?;
+
+    if diagnostics.iter().any(|d| d.severity == Severity::Error) {
+        bail!("compilation failed");
+    }
+
+    Ok(())
+}
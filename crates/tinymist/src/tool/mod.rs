@@ -1,8 +1,12 @@
 //! All the language tools provided by the `tinymist` crate.
 
 pub mod ast;
+pub mod build;
+pub mod deps;
+pub mod lint;
 pub mod package;
 pub mod project;
+pub mod query;
 pub mod testing;
 pub mod word_count;
 
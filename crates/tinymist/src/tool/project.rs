@@ -2,19 +2,21 @@
 
 use std::{
     borrow::Cow,
+    collections::HashMap,
     path::{Path, PathBuf},
     sync::Arc,
 };
 
 use clap_complete::Shell;
 use parking_lot::Mutex;
-use reflexo::{path::unix_slash, ImmutPath};
+use reflexo::path::{unix_slash, PathClean};
+use reflexo::ImmutPath;
 use reflexo_typst::WorldComputeGraph;
 use tinymist_query::analysis::Analysis;
 use tinymist_std::{bail, error::prelude::*};
 use tokio::sync::mpsc;
 
-use crate::{actor::editor::EditorRequest, world::system::print_diagnostics, Config};
+use crate::{actor::editor::EditorRequest, world::system::DiagnosticPrinter, Config};
 use crate::{project::*, task::ExportTask};
 
 /// Arguments for project compilation.
@@ -32,6 +34,10 @@ pub struct CompileArgs {
     /// set, the lock file will be saved.
     #[clap(long)]
     pub lockfile: Option<PathBuf>,
+
+    /// The arguments to print diagnostics.
+    #[clap(flatten)]
+    pub diag: DiagnosticArgs,
 }
 
 /// Arguments for generating a build script.
@@ -41,11 +47,27 @@ pub struct GenerateScriptArgs {
     /// will be inferred from the environment.
     #[clap(value_enum)]
     pub shell: Option<Shell>,
+    /// The format of the build script.
+    #[clap(long, value_enum, default_value = "shell")]
+    pub format: ScriptFormat,
     /// The path to the output script.
     #[clap(short, long)]
     pub output: Option<String>,
 }
 
+/// The format of a build script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ScriptFormat {
+    /// A shell script running all tasks in order.
+    Shell,
+    /// A Makefile to be included, e.g. `include build.mk`. Tasks with several
+    /// outputs, e.g. tasks with a matrix, are written as grouped targets, which
+    /// require GNU Make 4.3 or newer.
+    Make,
+    /// A Ninja build file.
+    Ninja,
+}
+
 #[cfg(feature = "preview")]
 pub use typst_preview::{PreviewArgs, PreviewMode};
 
@@ -102,6 +124,7 @@ impl LockFileExt for LockFile {
         let task = ApplyProjectTask {
             id: task_id.clone(),
             document: doc_id,
+            depends_on: vec![],
             task,
         };
 
@@ -115,7 +138,14 @@ impl LockFileExt for LockFile {
 pub async fn compile_main(args: CompileArgs) -> Result<()> {
     // Identifies the input and output
     let input = args.compile.declare.to_input();
-    let output = args.compile.to_task(input.id.clone())?;
+    let mut output = args.compile.to_task(input.id.clone())?;
+
+    if !output.depends_on.is_empty() {
+        log::warn!(
+            "dependencies of task {} are not run, which are only run by `tinymist build`",
+            output.id
+        );
+    }
 
     // Saves the lock file if the flags are set
     let save_lock = args.save_lock || args.lockfile.is_some();
@@ -126,6 +156,32 @@ pub async fn compile_main(args: CompileArgs) -> Result<()> {
         std::env::current_dir().context("lock directory")?.into()
     };
 
+    // Resolves the relative output and attachment paths against the current
+    // directory, which are saved relative to the root to keep the lock file
+    // portable.
+    let entry: EntryState = (input.clone(), lock_dir.clone()).entry()?.try_into()?;
+    let root = entry.root().context("no root")?;
+    let cwd = std::env::current_dir().context("current directory")?;
+    let resolve = |pattern: &mut PathPattern| {
+        if pattern.0.starts_with('$') || Path::new(pattern.0.as_str()).is_absolute() {
+            return;
+        }
+
+        let path = cwd.join(pattern.0.as_str()).clean();
+        *pattern = match path.strip_prefix(&root) {
+            Ok(path) => PathPattern::new(&format!("$root/{}", unix_slash(path))),
+            Err(..) => {
+                log::warn!("{path:?} is outside of the root {root:?}, which is kept absolute");
+                PathPattern::new(&unix_slash(&path))
+            }
+        };
+    };
+    let output_pattern = output.task.as_export_mut().and_then(|e| e.output.as_mut());
+    output_pattern.into_iter().for_each(resolve);
+    if let ProjectTask::ExportPdf(task) = &mut output.task {
+        task.attachments.iter_mut().for_each(resolve);
+    }
+
     if save_lock {
         LockFile::update(&lock_dir, |state| {
             state.replace_document(input.clone());
@@ -135,47 +191,82 @@ pub async fn compile_main(args: CompileArgs) -> Result<()> {
         })?;
     }
 
-    // Prepares for the compilation
-    let universe = (input, lock_dir.clone()).resolve()?;
-    let world = universe.snapshot();
-    let graph = WorldComputeGraph::from_world(world);
-
-    // Compiles the project
-    let is_html = matches!(output.task, ProjectTask::ExportHtml(..));
-    let compiled = CompiledArtifact::from_graph(graph, is_html);
-
-    let diag = compiled.diagnostics();
-    print_diagnostics(compiled.world(), diag, DiagnosticFormat::Human)
-        .context_ut("print diagnostics")?;
+    // Compiles the project once per variant
+    let printer =
+        DiagnosticPrinter::new(args.diag.diagnostic_format, args.diag.diagnostic_encoding);
+    let mut variant_deps = vec![];
+    for (variant, task) in output.task.expand() {
+        // Prepares for the compilation
+        let input = match &variant {
+            Some(variant) => input.with_variant(variant),
+            None => input.clone(),
+        };
+        let universe = (input, lock_dir.clone()).resolve()?;
+        let world = universe.snapshot();
+        let graph = WorldComputeGraph::from_world(world);
+
+        // Compiles the project
+        let is_html = matches!(task, ProjectTask::ExportHtml(..));
+        let compiled = CompiledArtifact::from_graph(graph, is_html);
+
+        let diag = compiled.diagnostics();
+        printer
+            .print(compiled.world(), diag)
+            .context_ut("print diagnostics")?;
+
+        if compiled.has_errors() {
+            printer.finish().context_ut("print diagnostics")?;
+            // todo: we should process case of compile error in fn main function
+            std::process::exit(1);
+        }
 
-    if compiled.has_errors() {
-        // todo: we should process case of compile error in fn main function
-        std::process::exit(1);
+        // Exports the compiled project. The lock file keeps the unexpanded task,
+        // whose outputs are expanded by the readers, so the variants only record
+        // the files they depend on, which are merged after the exports.
+        let lock_dir = (save_lock && variant.is_none()).then(|| lock_dir.clone());
+        if save_lock && variant.is_some() {
+            variant_deps.extend(compiled.world().depended_fs_paths());
+        }
+        ExportTask::do_export(task, compiled, lock_dir).await?;
     }
+    printer.finish().context_ut("print diagnostics")?;
 
-    // Exports the compiled project
-    let lock_dir = save_lock.then_some(lock_dir);
-    ExportTask::do_export(output.task, compiled, lock_dir).await?;
+    if !variant_deps.is_empty() {
+        let mut updater = update_lock(lock_dir);
+        updater.update_materials(input.id.clone(), variant_deps.into_iter().collect());
+        updater.commit();
+    }
 
     Ok(())
 }
 
 /// Generates a build script for compilation
 pub fn generate_script_main(args: GenerateScriptArgs) -> Result<()> {
-    let Some(shell) = args.shell.or_else(Shell::from_env) else {
-        bail!("could not infer shell");
-    };
     let output = Path::new(args.output.as_deref().unwrap_or("build"));
 
-    let output = match shell {
-        Shell::Bash | Shell::Zsh | Shell::Elvish | Shell::Fish => output.with_extension("sh"),
-        Shell::PowerShell => output.with_extension("ps1"),
-        _ => bail!("unsupported shell: {shell:?}"),
-    };
+    let (output, script) = match args.format {
+        ScriptFormat::Shell => {
+            let Some(shell) = args.shell.or_else(Shell::from_env) else {
+                bail!("could not infer shell");
+            };
 
-    let script = match shell {
-        Shell::Bash | Shell::Zsh | Shell::PowerShell => shell_build_script(shell)?,
-        _ => bail!("unsupported shell: {shell:?}"),
+            let output = match shell {
+                Shell::Bash | Shell::Zsh | Shell::Elvish | Shell::Fish => {
+                    output.with_extension("sh")
+                }
+                Shell::PowerShell => output.with_extension("ps1"),
+                _ => bail!("unsupported shell: {shell:?}"),
+            };
+
+            let script = match shell {
+                Shell::Bash | Shell::Zsh | Shell::PowerShell => shell_build_script(shell)?,
+                _ => bail!("unsupported shell: {shell:?}"),
+            };
+
+            (output, script)
+        }
+        ScriptFormat::Make => (output.with_extension("mk"), make_build_script()?),
+        ScriptFormat::Ninja => (output.with_extension("ninja"), ninja_build_script()?),
     };
 
     std::fs::write(output, script).context("write script")?;
@@ -202,6 +293,228 @@ fn shell_build_script(shell: Shell) -> Result<String> {
 
     let lock = LockFile::read(&lock_dir)?;
 
+    for (input, task) in export_tasks(&lock) {
+        let ext = task.task.extension();
+
+        output.push_str(&format!(
+            "# From {} to {} ({ext})\n",
+            task.doc_id(),
+            task.id
+        ));
+        output.push_str(&task_command(&lock_dir, input, task)?);
+        output.push('\n');
+    }
+
+    Ok(output)
+}
+
+/// Generates a Makefile, whose rules take the recorded materials of the
+/// documents as prerequisites.
+fn make_build_script() -> Result<String> {
+    let lock_dir = std::env::current_dir().context("current directory")?;
+    let lock = LockFile::read(&lock_dir)?;
+
+    let escape = |path: &Path| {
+        unix_slash(path)
+            .replace('$', "$$")
+            .replace(' ', "\\ ")
+            .replace('#', "\\#")
+    };
+
+    let mut targets = vec![];
+    let mut rules = String::new();
+    for step in build_steps(&lock_dir, &lock)? {
+        let outputs = step.outputs.iter().map(|p| escape(p)).collect::<Vec<_>>();
+        let deps = step.deps.iter().map(|p| escape(p)).collect::<Vec<_>>();
+        // Multiple outputs of a rule are produced by a single command.
+        let sep = if outputs.len() > 1 { "&:" } else { ":" };
+
+        rules.push_str(&format!(
+            "# From {} to {}\n",
+            step.task.doc_id(),
+            step.task.id
+        ));
+        rules.push_str(&format!("{} {sep} {}\n", outputs.join(" "), deps.join(" ")));
+        rules.push_str(&format!("\t{}\n\n", step.command.replace('$', "$$")));
+        targets.extend(outputs);
+    }
+
+    let mut output = String::new();
+    output.push_str("# This file is automatically @generated by tinymist.\n");
+    output.push_str("# The rules with multiple outputs are grouped targets (`&:`), which\n");
+    output.push_str("# require GNU Make 4.3 or newer.\n\n");
+    output.push_str(".PHONY: typst\n");
+    output.push_str(&format!("typst: {}\n\n", targets.join(" ")));
+    output.push_str(&rules);
+
+    Ok(output)
+}
+
+/// Generates a Ninja build file, whose edges take the recorded materials of the
+/// documents as inputs.
+fn ninja_build_script() -> Result<String> {
+    let lock_dir = std::env::current_dir().context("current directory")?;
+    let lock = LockFile::read(&lock_dir)?;
+
+    let escape = |path: &Path| {
+        unix_slash(path)
+            .replace('$', "$$")
+            .replace(' ', "$ ")
+            .replace(':', "$:")
+    };
+
+    let mut output = String::new();
+    output.push_str("# This file is automatically @generated by tinymist.\n\n");
+    output.push_str("rule tinymist\n  command = $cmd\n  description = $desc\n\n");
+
+    let mut targets = vec![];
+    for step in build_steps(&lock_dir, &lock)? {
+        let outputs = step.outputs.iter().map(|p| escape(p)).collect::<Vec<_>>();
+        let deps = step.deps.iter().map(|p| escape(p)).collect::<Vec<_>>();
+
+        output.push_str(&format!(
+            "build {}: tinymist {}\n",
+            outputs.join(" "),
+            deps.join(" ")
+        ));
+        output.push_str(&format!("  cmd = {}\n", step.command.replace('$', "$$")));
+        output.push_str(&format!(
+            "  desc = From {} to {}\n\n",
+            step.task.doc_id(),
+            step.task.id
+        ));
+        targets.extend(outputs);
+    }
+
+    output.push_str(&format!("default {}\n", targets.join(" ")));
+
+    Ok(output)
+}
+
+/// Gets the export tasks in the lock file, with their documents.
+fn export_tasks(lock: &LockFile) -> Vec<(&ProjectInput, &ApplyProjectTask)> {
+    let mut tasks = vec![];
+    for task in lock.task.iter() {
+        let Some(input) = lock.get_document(&task.document) else {
+            log::warn!(
+                "could not find document for task {:?}, whose document is {:?}",
+                task.id,
+                task.doc_id()
+            );
+            continue;
+        };
+        // todo: preview/query commands
+        if task.task.as_export().is_none() {
+            continue;
+        }
+
+        tasks.push((input, task));
+    }
+
+    tasks
+}
+
+/// A step in a build file, which runs a task.
+struct BuildStep<'a> {
+    /// The task to run.
+    task: &'a ApplyProjectTask,
+    /// The command to run the task.
+    command: String,
+    /// The outputs of the task, relative to the lock directory.
+    outputs: Vec<PathBuf>,
+    /// The files that the task depends on, relative to the lock directory.
+    deps: Vec<PathBuf>,
+}
+
+/// Gets the build steps of the export tasks in the lock file.
+fn build_steps<'a>(lock_dir: &Path, lock: &'a LockFile) -> Result<Vec<BuildStep<'a>>> {
+    let relative =
+        |path: &Path| tinymist_std::path::diff(path, lock_dir).unwrap_or_else(|| path.to_owned());
+
+    let mut steps = vec![];
+    for (input, task) in export_tasks(lock) {
+        let entry: EntryState = (input.clone(), ImmutPath::from(lock_dir))
+            .entry()?
+            .try_into()?;
+
+        let mut outputs = vec![];
+        for (_, task) in task.task.expand() {
+            let export = task.as_export().context("not an export task")?;
+            let pattern = export.output.clone().unwrap_or_default();
+            if let Some(path) = pattern.substitute(&entry) {
+                outputs.push(relative(&path.with_extension(task.extension())));
+            }
+        }
+        if outputs.is_empty() {
+            continue;
+        }
+
+        // The documents concatenated to the output are also prerequisites.
+        let mut deps = vec![];
+        let concat = task
+            .task
+            .as_export()
+            .into_iter()
+            .flat_map(|e| e.concat_documents());
+        for input in std::iter::once(input).chain(concat.flat_map(|id| lock.get_document(id))) {
+            match read_materials(lock_dir, &input.id) {
+                Some(materials) => deps.extend(materials.files.iter().map(|p| relative(p))),
+                // Falls back to the main file if the document has never been compiled.
+                None => deps.extend(input.main.to_rel_path(lock_dir)),
+            }
+        }
+
+        steps.push(BuildStep {
+            task,
+            command: task_command(lock_dir, input, task)?,
+            outputs,
+            deps,
+        });
+    }
+
+    // The outputs of the dependencies are also prerequisites.
+    let outputs = steps
+        .iter()
+        .map(|step| (step.task.id.clone(), step.outputs.clone()))
+        .collect::<HashMap<_, _>>();
+    for step in steps.iter_mut() {
+        for dep in &step.task.depends_on {
+            step.deps
+                .extend(outputs.get(dep).into_iter().flatten().cloned());
+        }
+    }
+
+    Ok(steps)
+}
+
+/// Gets the command running a task. The tasks whose transforms cannot be
+/// expressed by the arguments of `tinymist compile` are run by `tinymist build`
+/// instead, which runs the task as declared in the lock file.
+fn task_command(lock_dir: &Path, input: &ProjectInput, task: &ApplyProjectTask) -> Result<String> {
+    let export = task.task.as_export().context("not an export task")?;
+    let by_build = export.transform.iter().any(|t| {
+        matches!(
+            t,
+            ExportTransform::Merge { .. }
+                | ExportTransform::Concat { .. }
+                | ExportTransform::Script { .. }
+        )
+    });
+
+    if by_build {
+        let id = task.id.to_string();
+        return Ok(format!("tinymist build '{}'", id.replace("'", r#"'"'"'"#)));
+    }
+
+    compile_command(lock_dir, input, task)
+}
+
+/// Gets the `tinymist compile` command running a task.
+fn compile_command(
+    lock_dir: &Path,
+    input: &ProjectInput,
+    task: &ApplyProjectTask,
+) -> Result<String> {
     struct CmdBuilder(Vec<Cow<'static, str>>);
 
     impl CmdBuilder {
@@ -228,7 +541,7 @@ fn shell_build_script(shell: Shell) -> Result<String> {
     let quote = |s: &str| format!("'{}'", s.replace("'", r#"'"'"'"#));
 
     let path_of = |p: &ResourcePath, loc: &str| {
-        let Some(path) = p.to_rel_path(&lock_dir) else {
+        let Some(path) = p.to_rel_path(lock_dir) else {
             log::error!("could not resolve path for {loc}, path: {p:?}");
             return String::default();
         };
@@ -238,128 +551,153 @@ fn shell_build_script(shell: Shell) -> Result<String> {
 
     let base_cmd: Vec<&str> = vec!["tinymist", "compile", "--save-lock"];
 
-    for task in lock.task.iter() {
-        let Some(input) = lock.get_document(&task.document) else {
-            log::warn!(
-                "could not find document for task {:?}, whose document is {:?}",
-                task.id,
-                task.doc_id()
-            );
-            continue;
-        };
-        // todo: preview/query commands
-        let Some(export) = task.task.as_export() else {
-            continue;
-        };
+    let export = task.task.as_export().context("not an export task")?;
 
-        let mut cmd = CmdBuilder::new();
-        cmd.extend(base_cmd.iter().copied());
-        cmd.push("--task");
-        cmd.push(quote(&task.id.to_string()));
+    let mut cmd = CmdBuilder::new();
+    cmd.extend(base_cmd.iter().copied());
+    cmd.push("--task");
+    cmd.push(quote(&task.id.to_string()));
 
-        cmd.push(path_of(&input.main, "main"));
+    cmd.push(path_of(&input.main, "main"));
 
-        if let Some(root) = &input.root {
-            cmd.push("--root");
-            cmd.push(path_of(root, "root"));
-        }
+    if let Some(root) = &input.root {
+        cmd.push("--root");
+        cmd.push(path_of(root, "root"));
+    }
 
-        for (k, v) in &input.inputs {
-            cmd.push(format!(
-                r#"--input='{}={}'"#,
-                quote_escape(k),
-                quote_escape(v)
-            ));
-        }
+    for (k, v) in &input.inputs {
+        cmd.push(format!(
+            r#"--input='{}={}'"#,
+            quote_escape(k),
+            quote_escape(v)
+        ));
+    }
 
-        for p in &input.font_paths {
-            cmd.push("--font-path");
-            cmd.push(path_of(p, "font-path"));
-        }
+    for p in &input.font_paths {
+        cmd.push("--font-path");
+        cmd.push(path_of(p, "font-path"));
+    }
 
-        if !input.system_fonts {
-            cmd.push("--ignore-system-fonts");
-        }
+    if !input.system_fonts {
+        cmd.push("--ignore-system-fonts");
+    }
 
-        if let Some(p) = &input.package_path {
-            cmd.push("--package-path");
-            cmd.push(path_of(p, "package-path"));
-        }
+    if let Some(p) = &input.package_path {
+        cmd.push("--package-path");
+        cmd.push(path_of(p, "package-path"));
+    }
 
-        if let Some(p) = &input.package_cache_path {
-            cmd.push("--package-cache-path");
-            cmd.push(path_of(p, "package-cache-path"));
-        }
+    if let Some(p) = &input.package_cache_path {
+        cmd.push("--package-cache-path");
+        cmd.push(path_of(p, "package-cache-path"));
+    }
 
-        if let Some(p) = &export.output {
-            cmd.push("--output");
-            cmd.push(quote(&p.to_string()));
+    if let Some(p) = &export.output {
+        cmd.push("--output");
+        cmd.push(quote(&p.to_string()));
+    }
+
+    if !task.depends_on.is_empty() {
+        let deps = task.depends_on.iter().map(|id| id.to_string());
+        cmd.push("--depends-on");
+        cmd.push(quote(&deps.collect::<Vec<_>>().join(",")));
+    }
+
+    for variant in &export.matrix {
+        if !variant.font_paths.is_empty() {
+            log::warn!(
+                "font paths of variant {} in task {} are not supported by the command line",
+                variant.name,
+                task.id
+            );
         }
 
-        for t in &export.transform {
-            match t {
-                ExportTransform::Pretty { .. } => {
-                    cmd.push("--pretty");
-                }
-                ExportTransform::Pages { ranges } => {
-                    for r in ranges {
-                        cmd.push("--pages");
-                        cmd.push(r.to_string());
-                    }
+        let inputs = variant.inputs.iter().map(|(k, v)| format!("{k}={v}"));
+        let inputs = inputs.collect::<Vec<_>>().join(",");
+        cmd.push("--variant");
+        cmd.push(quote(&format!("{}:{inputs}", variant.name)));
+    }
+
+    for t in &export.transform {
+        match t {
+            ExportTransform::Pretty { .. } => {
+                cmd.push("--pretty");
+            }
+            ExportTransform::Pages { ranges } => {
+                for r in ranges {
+                    cmd.push("--pages");
+                    cmd.push(r.to_string());
                 }
-                // todo: export me
-                ExportTransform::Merge { .. } | ExportTransform::Script { .. } => {}
             }
+            // These are run by `tinymist build` instead, see `task_command`.
+            ExportTransform::Merge { .. }
+            | ExportTransform::Concat { .. }
+            | ExportTransform::Script { .. } => {}
         }
+    }
 
-        match &task.task {
-            ProjectTask::Preview(..) | ProjectTask::Query(..) => {}
-            ProjectTask::ExportPdf(task) => {
-                cmd.push("--format=pdf");
-
-                for s in &task.pdf_standards {
-                    cmd.push("--pdf-standard");
-                    let s = serde_json::to_string(s).context("pdf standard")?;
-                    cmd.push(s);
-                }
+    match &task.task {
+        ProjectTask::Preview(..) | ProjectTask::Query(..) => {}
+        ProjectTask::ExportPdf(task) => {
+            cmd.push("--format=pdf");
 
-                if let Some(output) = &task.creation_timestamp {
-                    cmd.push("--creation-timestamp");
-                    cmd.push(output.to_string());
-                }
+            for s in &task.pdf_standards {
+                cmd.push("--pdf-standard");
+                let s = serde_json::to_string(s).context("pdf standard")?;
+                cmd.push(s);
             }
-            ProjectTask::ExportSvg(..) => {
-                cmd.push("--format=svg");
+
+            if let Some(output) = &task.creation_timestamp {
+                cmd.push("--creation-timestamp");
+                cmd.push(output.to_string());
             }
-            ProjectTask::ExportSvgHtml(..) => {
-                cmd.push("--format=svg_html");
+
+            if let Some(ident) = &task.ident {
+                cmd.push("--pdf-ident");
+                cmd.push(quote(ident));
             }
-            ProjectTask::ExportMd(..) => {
-                cmd.push("--format=md");
+
+            if let Some(title) = &task.title {
+                cmd.push("--pdf-title");
+                cmd.push(quote(title));
             }
-            ProjectTask::ExportPng(..) => {
-                cmd.push("--format=png");
+
+            for author in &task.author {
+                cmd.push("--pdf-author");
+                cmd.push(quote(author));
             }
-            ProjectTask::ExportText(..) => {
-                cmd.push("--format=txt");
+
+            for keyword in &task.keywords {
+                cmd.push("--pdf-keywords");
+                cmd.push(quote(keyword));
             }
-            ProjectTask::ExportHtml(..) => {
-                cmd.push("--format=html");
+
+            for attachment in &task.attachments {
+                cmd.push("--pdf-attach");
+                cmd.push(quote(&attachment.to_string()));
             }
         }
-
-        let ext = task.task.extension();
-
-        output.push_str(&format!(
-            "# From {} to {} ({ext})\n",
-            task.doc_id(),
-            task.id
-        ));
-        output.push_str(&cmd.build());
-        output.push('\n');
+        ProjectTask::ExportSvg(..) => {
+            cmd.push("--format=svg");
+        }
+        ProjectTask::ExportSvgHtml(..) => {
+            cmd.push("--format=svg_html");
+        }
+        ProjectTask::ExportMd(..) => {
+            cmd.push("--format=md");
+        }
+        ProjectTask::ExportPng(..) => {
+            cmd.push("--format=png");
+        }
+        ProjectTask::ExportText(..) => {
+            cmd.push("--format=txt");
+        }
+        ProjectTask::ExportHtml(..) => {
+            cmd.push("--format=html");
+        }
     }
 
-    Ok(output)
+    Ok(cmd.build())
 }
 
 /// Project document commands' main
@@ -377,6 +715,9 @@ pub fn project_main(args: DocCommands) -> Result<()> {
                     priority: args.priority,
                 });
             }
+            // Building docs doesn't touch the lock file, and it is run as a
+            // language query.
+            DocCommands::Build(..) => bail!("cannot build docs with the lock file"),
         }
 
         Ok(())
@@ -0,0 +1,182 @@
+//! Headless language queries for scripting, which is used by `tinymist query`.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use lsp_types::{
+    AnnotatedTextEdit, DocumentChangeOperation, DocumentChanges, OneOf, RenameFile, ResourceOp,
+    TextEdit, Url, WorkspaceEdit,
+};
+use tinymist_query::{to_typst_range, url_to_path, LspPosition, PositionEncoding};
+use tinymist_std::error::prelude::*;
+use typst::syntax::Source;
+
+/// A position in a file, in the form of `file:line:column`. The line and the
+/// column are 1-based, and the column is counted in the position encoding of
+/// the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePosition {
+    /// The path to the file.
+    pub path: PathBuf,
+    /// The 1-based line number.
+    pub line: u32,
+    /// The 1-based column number.
+    pub column: u32,
+}
+
+impl FilePosition {
+    /// Converts the position to a 0-based LSP position.
+    pub fn to_lsp(&self) -> LspPosition {
+        LspPosition::new(self.line.saturating_sub(1), self.column.saturating_sub(1))
+    }
+}
+
+impl FromStr for FilePosition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Splits from the end, since the path may contain colons, e.g. `C:\a.typ`.
+        let mut parts = s.rsplitn(3, ':');
+        let (Some(column), Some(line), Some(path)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(format!("expected `file:line:column`, got {s:?}"));
+        };
+
+        let parse = |kind: &str, num: &str| match num.parse::<u32>() {
+            Ok(0) | Err(_) => Err(format!("invalid {kind} number {num:?} in {s:?}")),
+            Ok(num) => Ok(num),
+        };
+
+        Ok(Self {
+            path: PathBuf::from(path),
+            line: parse("line", line)?,
+            column: parse("column", column)?,
+        })
+    }
+}
+
+/// Applies a workspace edit to the files on the disk. The text edits are
+/// applied before the resource operations.
+pub fn apply_workspace_edit(edit: &WorkspaceEdit, encoding: PositionEncoding) -> Result<()> {
+    let mut edits: HashMap<&Url, Vec<&TextEdit>> = HashMap::new();
+    let mut renames: Vec<&RenameFile> = vec![];
+
+    for (uri, changes) in edit.changes.iter().flatten() {
+        edits.entry(uri).or_default().extend(changes);
+    }
+    match &edit.document_changes {
+        Some(DocumentChanges::Edits(changes)) => {
+            for change in changes {
+                let changes = change.edits.iter().map(text_edit);
+                let uri = &change.text_document.uri;
+                edits.entry(uri).or_default().extend(changes);
+            }
+        }
+        Some(DocumentChanges::Operations(ops)) => {
+            for op in ops {
+                match op {
+                    DocumentChangeOperation::Edit(change) => {
+                        let changes = change.edits.iter().map(text_edit);
+                        let uri = &change.text_document.uri;
+                        edits.entry(uri).or_default().extend(changes);
+                    }
+                    DocumentChangeOperation::Op(ResourceOp::Rename(rename)) => {
+                        renames.push(rename);
+                    }
+                    DocumentChangeOperation::Op(op) => {
+                        bail!("unsupported resource operation: {op:?}");
+                    }
+                }
+            }
+        }
+        None => {}
+    }
+
+    for (uri, edits) in edits {
+        let path = url_to_path(uri.clone());
+        let path_arg = || Some(Box::new([("path", path.display().to_string())]) as Box<[_]>);
+        let text = std::fs::read_to_string(&path).with_context("failed to read file", path_arg)?;
+        let text = apply_text_edits(Source::detached(text), &edits, encoding)
+            .with_context("failed to apply edits", path_arg)?;
+        std::fs::write(&path, text).with_context("failed to write file", path_arg)?;
+    }
+
+    for rename in renames {
+        let old_path = url_to_path(rename.old_uri.clone());
+        let new_path = url_to_path(rename.new_uri.clone());
+        if let Some(parent) = new_path.parent() {
+            std::fs::create_dir_all(parent).context("failed to create directory")?;
+        }
+        std::fs::rename(&old_path, &new_path).context("failed to rename file")?;
+    }
+
+    Ok(())
+}
+
+fn text_edit(edit: &OneOf<TextEdit, AnnotatedTextEdit>) -> &TextEdit {
+    match edit {
+        OneOf::Left(edit) => edit,
+        OneOf::Right(edit) => &edit.text_edit,
+    }
+}
+
+fn apply_text_edits(
+    source: Source,
+    edits: &[&TextEdit],
+    encoding: PositionEncoding,
+) -> Result<String> {
+    let mut ranges = edits
+        .iter()
+        .map(|edit| {
+            let range = to_typst_range(edit.range, encoding, &source)
+                .context("edit range is out of bounds")?;
+            Ok((range, edit.new_text.as_str()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    // Applies the edits from the end, so that the earlier ranges are kept valid.
+    ranges.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+
+    let mut text = source.text().to_owned();
+    for (range, new_text) in ranges {
+        text.replace_range(range, new_text);
+    }
+
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_file_position() {
+        let pos = FilePosition::from_str("main.typ:3:5").unwrap();
+        assert_eq!(pos.path, PathBuf::from("main.typ"));
+        assert_eq!(pos.to_lsp(), LspPosition::new(2, 4));
+
+        let pos = FilePosition::from_str(r"C:\doc\main.typ:1:1").unwrap();
+        assert_eq!(pos.path, PathBuf::from(r"C:\doc\main.typ"));
+
+        assert!(FilePosition::from_str("main.typ:3").is_err());
+        assert!(FilePosition::from_str("main.typ:0:1").is_err());
+        assert!(FilePosition::from_str("main.typ:a:1").is_err());
+    }
+
+    #[test]
+    fn test_apply_text_edits() {
+        let source = Source::detached("#let x = 1;\n#x + x");
+        let edit = |line, start, end| TextEdit {
+            range: lsp_types::Range::new(
+                LspPosition::new(line, start),
+                LspPosition::new(line, end),
+            ),
+            new_text: "y".into(),
+        };
+        let edits = [edit(1, 5, 6), edit(0, 5, 6), edit(1, 1, 2)];
+        let edits = edits.iter().collect::<Vec<_>>();
+
+        let text = apply_text_edits(source, &edits, PositionEncoding::Utf16).unwrap();
+        assert_eq!(text, "#let y = 1;\n#y + y");
+    }
+}
@@ -4,7 +4,8 @@ use core::fmt;
 use std::collections::HashSet;
 use std::io::Write;
 use std::path::Path;
-use std::sync::{atomic::AtomicBool, Arc};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
 
 use itertools::Either;
 use parking_lot::Mutex;
@@ -12,7 +13,7 @@ use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use reflexo::ImmutPath;
 use reflexo_typst::{vfs::FileId, TypstDocument, TypstHtmlDocument};
 use tinymist_debug::CoverageResult;
-use tinymist_project::world::{system::print_diagnostics, DiagnosticFormat};
+use tinymist_project::world::system::DiagnosticPrinter;
 use tinymist_query::analysis::Analysis;
 use tinymist_query::syntax::{cast_include_expr, find_source_by_expr, node_ancestors};
 use tinymist_query::testing::{TestCaseKind, TestSuites};
@@ -48,7 +49,11 @@ pub fn coverage_main(args: CompileOnceArgs) -> Result<()> {
         Ok(())
     });
 
-    print_diag_or_error(&world, result)
+    let diag = DiagnosticArgs::default();
+    let printer = DiagnosticPrinter::new(diag.diagnostic_format, diag.diagnostic_encoding);
+    let result = print_diag_or_error(&world, result, &printer);
+    printer.finish().context_ut("print diagnostics")?;
+    result
 }
 
 /// Testing arguments
@@ -97,6 +102,10 @@ pub struct TestConfigArgs {
     /// Style of printing coverage.
     #[clap(long, default_value = "short")]
     pub print_coverage: PrintCovStyle,
+
+    /// The arguments to print diagnostics.
+    #[clap(flatten)]
+    pub diag: DiagnosticArgs,
 }
 
 /// Style of printing coverage.
@@ -127,6 +136,11 @@ const LOG_PRELUDE: &str = "#import \"/target/testing-log.typ\": *\n#show: main";
 pub async fn test_main(args: TestArgs) -> Result<()> {
     exit_on_ctrl_c();
 
+    // Each run would write its own SARIF log, which cannot be concatenated.
+    if args.watch && args.config.diag.diagnostic_format == DiagnosticFormat::Sarif {
+        bail!("SARIF diagnostics are not supported in watch mode");
+    }
+
     // Prepares for the compilation
     let verse = args.compile.resolve()?;
 
@@ -240,6 +254,22 @@ pub async fn test_main(args: TestArgs) -> Result<()> {
 }
 
 fn test_once(world: &LspWorld, ctx: &TestContext) -> Result<bool> {
+    // The diagnostics of a run are printed at once, as a single SARIF log.
+    let diag = ctx.args.diag;
+    let printer = DiagnosticPrinter::new(diag.diagnostic_format, diag.diagnostic_encoding);
+    let passed = print_diag_or_error(world, run_tests(world, ctx, &printer), &printer);
+    printer.finish().context_ut("print diagnostics")?;
+
+    if matches!(passed, Ok(true)) {
+        log_info!("All test cases passed...");
+    } else {
+        test_error!("Fatal:", "Some test cases failed...");
+    }
+
+    passed
+}
+
+fn run_tests(world: &LspWorld, ctx: &TestContext, printer: &DiagnosticPrinter) -> Result<bool> {
     let mut actx = ctx.analysis.snapshot(world.clone());
     let doc = typst::compile::<TypstPagedDocument>(&actx.world).output?;
 
@@ -252,11 +282,11 @@ fn test_once(world: &LspWorld, ctx: &TestContext) -> Result<bool> {
         suites.examples.len()
     );
 
-    let result = if ctx.args.coverage {
+    if ctx.args.coverage {
         let (cov, result) = tinymist_debug::with_cov(world, |world| {
             let suites = suites.recheck(world);
-            let runner = TestRunner::new(ctx, world, &suites);
-            let result = print_diag_or_error(world, runner.run());
+            let runner = TestRunner::new(ctx, world, &suites, printer);
+            let result = print_diag_or_error(world, runner.run(), printer);
             comemo::evict(TEST_EVICT_MAX_AGE);
             result
         });
@@ -264,19 +294,10 @@ fn test_once(world: &LspWorld, ctx: &TestContext) -> Result<bool> {
         result
     } else {
         let suites = suites.recheck(world);
-        let runner = TestRunner::new(ctx, world, &suites);
+        let runner = TestRunner::new(ctx, world, &suites, printer);
         comemo::evict(TEST_EVICT_MAX_AGE);
         runner.run()
-    };
-
-    let passed = print_diag_or_error(world, result);
-    if matches!(passed, Ok(true)) {
-        log_info!("All test cases passed...");
-    } else {
-        test_error!("Fatal:", "Some test cases failed...");
     }
-
-    passed
 }
 
 #[derive(Clone)]
@@ -312,20 +333,30 @@ struct TestRunner<'a> {
     ctx: &'a TestContext,
     world: &'a dyn SourceWorld,
     suites: &'a TestSuites,
+    printer: &'a DiagnosticPrinter,
     diagnostics: Mutex<Vec<EcoVec<SourceDiagnostic>>>,
     examples: Mutex<HashSet<String>>,
     failed: AtomicBool,
+    /// The number of started test cases, which is reported as the progress.
+    started: AtomicUsize,
 }
 
 impl<'a> TestRunner<'a> {
-    fn new(ctx: &'a TestContext, world: &'a dyn SourceWorld, suites: &'a TestSuites) -> Self {
+    fn new(
+        ctx: &'a TestContext,
+        world: &'a dyn SourceWorld,
+        suites: &'a TestSuites,
+        printer: &'a DiagnosticPrinter,
+    ) -> Self {
         Self {
             ctx,
             world,
             suites,
+            printer,
             diagnostics: Mutex::new(Vec::new()),
             examples: Mutex::new(HashSet::new()),
             failed: AtomicBool::new(false),
+            started: AtomicUsize::new(0),
         }
     }
 
@@ -336,7 +367,9 @@ impl<'a> TestRunner<'a> {
     }
 
     fn running(&self, kind: &str, name: &str) {
-        test_info!("Running", "{kind}({name})");
+        let started = self.started.fetch_add(1, Ordering::SeqCst) + 1;
+        let total = self.suites.tests.len() + self.suites.examples.len();
+        test_info!("Running", "[{started}/{total}] {kind}({name})");
         self.put_log(format_args!("#running-{kind}({name:?})"));
     }
 
@@ -431,7 +464,8 @@ impl<'a> TestRunner<'a> {
                 if any_error {
                     Err(diagnostics)?
                 } else {
-                    print_diagnostics(self.world, diagnostics.iter(), DiagnosticFormat::Human)
+                    self.printer
+                        .print(self.world, diagnostics.iter())
                         .context_ut("print diagnostics")?;
                 }
             }
@@ -593,12 +627,17 @@ fn get_example_file(world: &dyn World, name: &str, id: FileId, span: Span) -> Re
     find_source_by_expr(world, id, included).context("cannot find example file")
 }
 
-fn print_diag_or_error<T>(world: &impl SourceWorld, result: Result<T>) -> Result<T> {
+fn print_diag_or_error<T>(
+    world: &impl SourceWorld,
+    result: Result<T>,
+    printer: &DiagnosticPrinter,
+) -> Result<T> {
     match result {
         Ok(v) => Ok(v),
         Err(err) => {
             if let Some(diagnostics) = err.diagnostics() {
-                print_diagnostics(world, diagnostics.iter(), DiagnosticFormat::Human)
+                printer
+                    .print(world, diagnostics.iter())
                     .context_ut("print diagnostics")?;
                 bail!("");
             }
@@ -1,13 +1,19 @@
 //! Word count tool for documents.
 
-use std::ops::Range;
-use std::sync::Arc;
+use std::path::PathBuf;
 
-use reflexo_typst::debug_loc::SourceSpanOffset;
 use serde::{Deserialize, Serialize};
+use tinymist_project::LspWorld;
+use tinymist_query::{LspRange, PositionEncoding};
 use tinymist_std::typst::TypstDocument;
 use tinymist_task::TextExport;
-use typst::{syntax::Span, text::TextItem};
+use typst::foundations::StyleChain;
+use typst::html::{HtmlElement, HtmlNode};
+use typst::introspection::Tag;
+use typst::layout::{Frame, FrameItem};
+use typst::model::HeadingElem;
+use typst::syntax::Span;
+use typst::World;
 use unicode_script::{Script, UnicodeScript};
 
 /// Words count for a document.
@@ -27,126 +33,294 @@ pub struct WordsCount {
 
 /// Count words in a document.
 pub fn word_count(doc: &TypstDocument) -> WordsCount {
-    // the mapping is still not use, so we prevent the warning here
-    let _ = TextContent::map_back_spans;
-
-    let mut words = 0;
-    let mut chars = 0;
-    let mut cjk_chars = 0;
-    let mut spaces = 0;
-
     // First, get text representation of the document.
     let content = TextExport::run_on_doc(doc).unwrap_or_default();
 
-    /// A automaton to count words.
-    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-    enum CountState {
-        /// Waiting for a word. (Default state)
-        InSpace,
-        /// At a word.
-        InNonCJK,
-        /// At a CJK character.
-        InCJK,
+    let mut counter = WordCounter::default();
+    counter.push_str(&content);
+    counter.count
+}
+
+/// Writing statistics for a piece of text.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WritingStats {
+    /// The words count.
+    #[serde(flatten)]
+    pub count: WordsCount,
+    /// Number of sentences.
+    pub sentences: usize,
+    /// Average number of words per sentence.
+    pub avg_sentence_length: f64,
+    /// Estimated reading time in minutes.
+    pub reading_time: f64,
+}
+
+/// Writing statistics for a section of a document, which starts at a heading
+/// and ends at the next heading.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionWordsCount {
+    /// The plain text of the heading. It is `None` for the content before the
+    /// first heading.
+    pub title: Option<String>,
+    /// The level of the heading. It is `0` for the content before the first
+    /// heading.
+    pub level: usize,
+    /// The path to the source file where the section starts.
+    pub path: Option<PathBuf>,
+    /// The range of the section in the source file.
+    pub range: Option<LspRange>,
+    /// The writing statistics of the section.
+    #[serde(flatten)]
+    pub stats: WritingStats,
+}
+
+/// Count words in a document section by section, each section is mapped back
+/// to its range in the source.
+pub fn section_word_count(
+    world: &LspWorld,
+    doc: &TypstDocument,
+    encoding: PositionEncoding,
+) -> Vec<SectionWordsCount> {
+    let mut splitter = SectionSplitter {
+        sections: vec![Section::default()],
+    };
+    splitter.doc(doc);
+    let sections = splitter.sections;
+
+    // Each section ends where the next one starts in the same file.
+    let starts = sections
+        .iter()
+        .map(|section| match section.heading {
+            Some(span) => {
+                let id = span.id()?;
+                Some((id, world.source(id).ok()?.range(span)?.start))
+            }
+            None => Some((world.main(), 0)),
+        })
+        .collect::<Vec<_>>();
+
+    let mut result = Vec::with_capacity(sections.len());
+    for (idx, section) in sections.into_iter().enumerate() {
+        // Skips the preamble if there is nothing before the first heading.
+        if idx == 0 && section.counter.count.chars == 0 && starts.len() > 1 {
+            continue;
+        }
+
+        let start = starts[idx];
+        let end = starts.get(idx + 1).copied().flatten();
+        let mapped = start.and_then(|(id, start)| {
+            let source = world.source(id).ok()?;
+            let end = match end {
+                Some((end_id, end)) if end_id == id && end >= start => end,
+                _ => source.text().len(),
+            };
+            let range = tinymist_query::to_lsp_range(start..end, &source, encoding);
+            let path = world.path_for_id(id).ok()?.as_path().to_owned();
+            Some((path, range))
+        });
+        let (path, range) = mapped.unzip();
+
+        result.push(SectionWordsCount {
+            title: section.title,
+            level: section.level,
+            path,
+            range,
+            stats: section.counter.stats(),
+        });
+    }
+
+    result
+}
+
+/// Average reading speed of non-CJK words per minute.
+const WORDS_PER_MINUTE: f64 = 238.;
+/// Average reading speed of CJK characters per minute.
+const CJK_CHARS_PER_MINUTE: f64 = 350.;
+
+/// A automaton to count words.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum CountState {
+    /// Waiting for a word. (Default state)
+    #[default]
+    InSpace,
+    /// At a word.
+    InNonCJK,
+    /// At a CJK character.
+    InCJK,
+}
+
+/// The state of a possible sentence end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum SentenceState {
+    /// Not after a sentence terminator. (Default state)
+    #[default]
+    InSentence,
+    /// Right after a terminator, e.g. `.` in `3.14` or `e.g.`.
+    AfterTerminator,
+    /// At the spaces following a terminator.
+    AfterSpace,
+}
+
+/// Counts words, characters and sentences of text pushed to it.
+#[derive(Debug, Clone)]
+struct WordCounter {
+    count: WordsCount,
+    state: CountState,
+    sentence_state: SentenceState,
+    sentences: usize,
+    words_in_sentence: usize,
+}
+
+impl Default for WordCounter {
+    fn default() -> Self {
+        Self {
+            count: WordsCount {
+                words: 0,
+                chars: 0,
+                spaces: 0,
+                cjk_chars: 0,
+            },
+            state: CountState::InSpace,
+            sentence_state: SentenceState::InSentence,
+            sentences: 0,
+            words_in_sentence: 0,
+        }
     }
+}
 
-    fn is_cjk(c: char) -> bool {
-        matches!(
-            c.script(),
-            Script::Han | Script::Hiragana | Script::Katakana | Script::Hangul
-        )
+impl WordCounter {
+    fn push_str(&mut self, text: &str) {
+        for c in text.chars() {
+            self.push(c);
+        }
     }
 
-    let mut state = CountState::InSpace;
-    for c in content.chars() {
-        chars += 1;
+    fn push(&mut self, c: char) {
+        self.push_sentence(c);
+
+        let count = &mut self.count;
+        count.chars += 1;
 
         if c.is_whitespace() {
-            if state != CountState::InSpace {
-                spaces += 1;
+            if self.state != CountState::InSpace {
+                count.spaces += 1;
             }
-            state = CountState::InSpace;
-            continue;
+            self.state = CountState::InSpace;
+            return;
         }
 
-        // Check unicode script to see if it's a CJK character.
         if is_cjk(c) {
-            words += 1;
-            cjk_chars += 1;
+            // Check unicode script to see if it's a CJK character.
+            count.words += 1;
+            count.cjk_chars += 1;
+            self.words_in_sentence += 1;
 
-            state = CountState::InCJK;
-        } else {
-            if state != CountState::InNonCJK {
-                words += 1;
+            self.state = CountState::InCJK;
+            return;
+        } else if is_sentence_end(c) || is_cjk_sentence_end(c) {
+            // A terminator neither starts nor splits a word, e.g. `.NET`.
+            return;
+        } else if self.state != CountState::InNonCJK {
+            count.words += 1;
+            self.words_in_sentence += 1;
+        }
+
+        self.state = CountState::InNonCJK;
+    }
+
+    /// Detects sentence ends. A terminator ends a sentence only if it is
+    /// followed by spaces and a word not starting in lowercase, or by the end
+    /// of the text, so that `3.14` and `e.g. this` are not sentence ends. CJK
+    /// terminators always end a sentence.
+    fn push_sentence(&mut self, c: char) {
+        use SentenceState::*;
+
+        if is_cjk_sentence_end(c) {
+            self.end_sentence();
+            self.sentence_state = InSentence;
+            return;
+        }
+
+        self.sentence_state = match self.sentence_state {
+            _ if is_sentence_end(c) => AfterTerminator,
+            AfterTerminator if is_closing_punct(c) => AfterTerminator,
+            AfterTerminator | AfterSpace if c.is_whitespace() => AfterSpace,
+            AfterSpace => {
+                if !c.is_lowercase() {
+                    self.end_sentence();
+                }
+                InSentence
             }
+            AfterTerminator | InSentence => InSentence,
+        };
+    }
 
-            state = CountState::InNonCJK;
+    fn end_sentence(&mut self) {
+        if self.words_in_sentence > 0 {
+            self.sentences += 1;
+            self.words_in_sentence = 0;
         }
     }
 
-    WordsCount {
-        words,
-        chars,
-        spaces,
-        cjk_chars,
+    fn stats(&self) -> WritingStats {
+        let count = self.count.clone();
+        // The trailing words are also a sentence, with or without a terminator.
+        let sentences = self.sentences + usize::from(self.words_in_sentence > 0);
+        let avg_sentence_length = if sentences == 0 {
+            0.
+        } else {
+            count.words as f64 / sentences as f64
+        };
+        let non_cjk_words = count.words - count.cjk_chars;
+        let reading_time =
+            non_cjk_words as f64 / WORDS_PER_MINUTE + count.cjk_chars as f64 / CJK_CHARS_PER_MINUTE;
+
+        WritingStats {
+            count,
+            sentences,
+            avg_sentence_length,
+            reading_time,
+        }
     }
 }
 
-/// Given a text range, map it back to the original document.
-#[derive(Debug, Clone)]
-pub struct MappedSpan {
-    /// The start span.
-    pub span: SourceSpanOffset,
-    /// The end span.
-    pub span_end: Option<SourceSpanOffset>,
-    /// Whether a text range is completely covered by [`Self::span`] and
-    /// [`Self::span_end`].
-    pub completed: bool,
+fn is_cjk(c: char) -> bool {
+    matches!(
+        c.script(),
+        Script::Han | Script::Hiragana | Script::Katakana | Script::Hangul
+    )
 }
 
-/// Annotated content for a font.
-#[derive(Debug, Clone)]
-pub struct TextContent {
-    /// A string of the content for slicing.
-    pub content: String,
-    /// annotating document.
-    pub doc: Arc<TypstDocument>,
+fn is_sentence_end(c: char) -> bool {
+    matches!(c, '.' | '!' | '?' | '…')
 }
 
-impl TextContent {
-    /// Map text ranges (with byte offsets) back to the original document in
-    /// batch.
-    pub fn map_back_spans(
-        &self,
-        mut spans: Vec<std::ops::Range<usize>>,
-    ) -> Vec<Option<MappedSpan>> {
-        // Sort for scanning
-        spans.sort_by_key(|r| r.start);
-
-        // Scan the document recursively to map back the spans.
-        let mut mapper = SpanMapper::default();
-        mapper.doc(&self.doc);
-
-        // Align result with the input to prevent bad scanning.
-        let mut offsets = mapper.span_offset;
-        while spans.len() < offsets.len() {
-            offsets.pop();
-        }
-        while spans.len() > offsets.len() {
-            offsets.push(None);
-        }
+fn is_cjk_sentence_end(c: char) -> bool {
+    matches!(c, '。' | '！' | '？')
+}
 
-        offsets
-    }
+fn is_closing_punct(c: char) -> bool {
+    matches!(c, '"' | '\'' | ')' | ']' | '»' | '”' | '’')
+}
+
+/// A section of a document.
+#[derive(Debug, Default)]
+struct Section {
+    /// The span of the heading starting the section.
+    heading: Option<Span>,
+    title: Option<String>,
+    level: usize,
+    counter: WordCounter,
 }
 
-#[derive(Debug, Clone, Default)]
-struct SpanMapper {
-    offset: usize,
-    spans_to_map: Vec<std::ops::Range<usize>>,
-    span_offset: Vec<Option<MappedSpan>>,
+/// Splits the text of a document into sections by headings.
+struct SectionSplitter {
+    sections: Vec<Section>,
 }
 
-impl SpanMapper {
+impl SectionSplitter {
     fn doc(&mut self, doc: &TypstDocument) {
         match doc {
             TypstDocument::Paged(paged_doc) => {
@@ -154,105 +328,103 @@ impl SpanMapper {
                     self.frame(&page.frame);
                 }
             }
-            // todo: handle html
-            TypstDocument::Html(..) => {}
+            TypstDocument::Html(html_doc) => self.element(&html_doc.root),
         }
     }
 
-    fn frame(&mut self, doc: &typst::layout::Frame) {
-        for (_, item) in doc.items() {
-            self.item(item);
+    fn frame(&mut self, frame: &Frame) {
+        for (_, item) in frame.items() {
+            match item {
+                FrameItem::Group(group) => self.frame(&group.frame),
+                FrameItem::Text(text) => self.text(&text.text),
+                FrameItem::Tag(tag) => self.tag(tag),
+                FrameItem::Link(..) | FrameItem::Shape(..) | FrameItem::Image(..) => {}
+            }
+        }
+        #[cfg(not(feature = "no-content-hint"))]
+        {
+            let c = frame.content_hint();
+            if c != '\0' {
+                self.current().counter.push(c);
+            }
         }
     }
 
-    fn item(&mut self, item: &typst::layout::FrameItem) {
-        use typst::layout::FrameItem::*;
-        match item {
-            Group(g) => self.frame(&g.frame),
-            Text(t) => {
-                self.check(t.text.as_str(), Some(t));
+    fn element(&mut self, elem: &HtmlElement) {
+        for child in elem.children.iter() {
+            match child {
+                HtmlNode::Tag(tag) => self.tag(tag),
+                HtmlNode::Element(elem) => self.element(elem),
+                HtmlNode::Text(text, _) => self.text(text),
+                HtmlNode::Frame(frame) => self.frame(frame),
             }
-            Link(..) | Shape(..) | Image(..) => {
-                self.check("object", None);
-            }
-            Tag(..) => {}
         }
     }
 
-    fn check(&mut self, text: &str, src: Option<&TextItem>) {
-        if let Some(src) = src {
-            self.do_check(src);
-        }
-        self.offset += text.len();
+    fn tag(&mut self, tag: &Tag) {
+        let Tag::Start(elem) = tag else {
+            return;
+        };
+        let Some(heading) = elem.to_packed::<HeadingElem>() else {
+            return;
+        };
+
+        self.sections.push(Section {
+            heading: Some(heading.span()),
+            title: Some(heading.body.plain_text().into()),
+            level: heading.resolve_level(StyleChain::default()).get(),
+            counter: WordCounter::default(),
+        });
     }
 
-    fn do_check(&mut self, text: &TextItem) -> Option<()> {
-        let beg = self.offset;
-        let end = beg + text.text.len();
-        loop {
-            let so = self.span_offset.len();
-            let to_check = self.spans_to_map.get(so)?;
-            if to_check.start >= end {
-                return Some(());
-            }
-            if to_check.end <= beg {
-                self.span_offset.push(None);
-                log::info!("span out of range {to_check:?}");
-                continue;
-            }
-            // todo: don't swallow the span
-            if to_check.start < beg {
-                self.span_offset.push(None);
-                log::info!("span skipped {to_check:?}");
-                continue;
-            }
+    fn text(&mut self, text: &str) {
+        self.current().counter.push_str(text);
+    }
 
-            log::info!("span checking {to_check:?} in {text:?}");
-            let inner = to_check.start - beg;
-            self.span_offset
-                .push(self.check_text_inner(text, inner..inner + (to_check.len())));
-        }
+    fn current(&mut self) -> &mut Section {
+        self.sections.last_mut().expect("there is always a section")
     }
+}
 
-    fn check_text_inner(&self, text: &TextItem, rng: std::ops::Range<usize>) -> Option<MappedSpan> {
-        let glyphs = text
-            .glyphs
-            .iter()
-            .filter(|g| rng.contains(&g.range().start));
-        let mut min_span: Option<(Range<usize>, (Span, u16))> = None;
-        let mut max_span: Option<(Range<usize>, (Span, u16))> = None;
-        let mut found = vec![];
-        for glyph in glyphs {
-            found.push(glyph.range());
-            if let Some((mii, s)) = min_span.as_ref() {
-                if glyph.range().start < mii.start && !s.0.is_detached() {
-                    // min_span = Some(glyph.range());
-                    min_span = Some((glyph.range(), glyph.span));
-                }
-            } else {
-                // min_span = Some(glyph.range());
-                min_span = Some((glyph.range(), glyph.span));
-            }
-            if let Some((mai, s)) = max_span.as_ref() {
-                if glyph.range().end > mai.end && !s.0.is_detached() {
-                    // max_span = Some(glyph.range());
-                    max_span = Some((glyph.range(), glyph.span));
-                }
-            } else {
-                // max_span = Some(glyph.range());
-                max_span = Some((glyph.range(), glyph.span));
-            }
-        }
-        found.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| a.end.cmp(&b.end)));
-        let completed = !found.is_empty()
-            && found[0].start <= rng.start
-            && found[found.len() - 1].end >= rng.end;
-        let span = min_span?.1 .0;
-        let span_end = max_span.map(|m| m.1 .0);
-        Some(MappedSpan {
-            span: SourceSpanOffset::from(span),
-            span_end: span_end.map(SourceSpanOffset::from),
-            completed,
-        })
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sentences(text: &str) -> usize {
+        let mut counter = WordCounter::default();
+        counter.push_str(text);
+        counter.stats().sentences
+    }
+
+    fn words(text: &str) -> usize {
+        let mut counter = WordCounter::default();
+        counter.push_str(text);
+        counter.stats().count.words
+    }
+
+    #[test]
+    fn test_sentences() {
+        assert_eq!(sentences("Hello world. This is a test!"), 2);
+        assert_eq!(sentences("Is it? \"Yes.\" It is"), 3);
+        assert_eq!(sentences("你好。世界！"), 2);
+    }
+
+    #[test]
+    fn test_sentences_abbreviation() {
+        assert_eq!(sentences("Use a tool, e.g. a hammer."), 1);
+        assert_eq!(sentences("Use a tool, i.e. a hammer. Then stop."), 2);
+    }
+
+    #[test]
+    fn test_sentences_number() {
+        assert_eq!(sentences("Pi is about 3.14 in decimal."), 1);
+        assert_eq!(sentences("It costs 2.5 dollars. It is 1.0.2 now"), 2);
+    }
+
+    #[test]
+    fn test_words_after_terminator() {
+        assert_eq!(words(".NET"), 1);
+        assert_eq!(words("...and"), 1);
+        assert_eq!(words("你好。Hello"), 3);
     }
 }
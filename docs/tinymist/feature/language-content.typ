@@ -15,6 +15,9 @@ Language service (LSP) features:
   - (Todo) Highlight all exit points in a function context.
   - (Todo) Highlight all captures in a closure context.
   - (Todo) Highlight all occurrences of a symbol in a document.
+- #link("https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#textDocument_linkedEditingRange")[Linked editing ranges]
+  - Edit a label and its references in the same file, a local definition and its uses, or a pair of `[`/`]` or `$` delimiters at once.
+  - In VS Code, enable it by the `editor.linkedEditing` setting.
 - #link("https://microsoft.github.io/language-server-protocol/specifications/lsp/3.17/specification/#textDocument_documentLink")[Document links]
   - Renders path or link references in the document, such as `image("path.png")` or `bibliography(style: "path.csl")`.
 - #link("https://code.visualstudio.com/docs/getstarted/userinterface#_outline-view")[Document symbols]
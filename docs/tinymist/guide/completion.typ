@@ -53,3 +53,26 @@ A frequently asked question is how to completing labels in sub files when writin
 
   We suggest to use snippet extensions powered by TextMate Scopes. For example, #link("https://github.com/OrangeX4/OrangeX4-HyperSnips")[HyperSnips] provides context-sensitive snippet completion.
 ]
+
+=== Snippet Files
+
+Besides the builtin snippets, the LSP loads snippets from snippet files, so that house-style snippets can be shared through the repository. The snippets are loaded from the following files, and an edit to them takes effect on the next completion:
++ The project snippet file, `.tinymist/snippets.toml` in the workspace root.
++ The user snippet file, `tinymist/snippets.toml` in the config directory of the system, e.g. `~/.config/tinymist/snippets.toml` on Linux.
+
+A snippet file lists snippets in TOML:
+
+```toml
+[[snippet]]
+label = "todo"
+snippet = "todo[${content}]"
+description = "Inserts a todo note."
+context = [{ mode = "markup" }, { mode = "code" }]
+imports = [{ path = "/lib/notes.typ", items = ["todo"] }]
+```
+
+- `label`: the name of the snippet, which is matched by the typed characters.
+- `snippet`: the content to insert, in which `${name}` is a placeholder.
+- `description` (optional): the description shown in the completion list.
+- `context` (optional): the modes where the snippet is applicable, which are `markup`, `code` and `math`. The snippet is applicable in any mode if it is omitted.
+- `imports` (optional): the items imported from modules, which are inserted into the document along with the snippet unless they are imported already. The `path` is either a path relative to the workspace root, starting with `/`, or a package, e.g. `@preview/cetz:0.3.4`.
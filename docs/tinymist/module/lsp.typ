@@ -49,6 +49,19 @@ tinymist lsp --mirror input.txt
 tinymist lsp --replay input.txt
 ```
 
+== Serving multiple editors
+
+By default, each editor window spawns its own server over the stdio. With `--listen`, a single long-running server serves all the editors connected to it instead, sharing the font state and the analysis caches, while each connection keeps its own opened documents.
+
+```sh
+# Listen on a TCP socket
+tinymist lsp --listen 127.0.0.1:9257
+# Listen on a Unix domain socket
+tinymist lsp --listen unix:///tmp/tinymist.sock
+# Listen on a WebSocket endpoint
+tinymist lsp --listen ws://127.0.0.1:9257
+```
+
 == Analyze memory usage with DHAT
 
 You can build the program with `dhat-heap` feature to collect memory usage with DHAT. The DHAT will instrument the allocator dynamically, so it will slow down the program significantly.